@@ -13,15 +13,19 @@ use serde::{
     Deserializer,
     de::Error
 };
-use terminos_vm::ValueCell;
+use terminos_vm::{Module, ValueCell};
 use crate::{
     account::{Nonce, CiphertextCache, VersionedBalance, VersionedNonce},
+    asset::AssetData,
     block::{TopoHeight, Algorithm, BlockVersion, EXTRA_NONCE_SIZE},
     crypto::{Address, Hash},
     difficulty::{CumulativeDifficulty, Difficulty},
     network::Network,
     time::{TimestampMillis, TimestampSeconds},
-    transaction::extra_data::{SharedKey, UnknownExtraDataFormat},
+    transaction::{
+        extra_data::{PlaintextData, SharedKey, UnknownExtraDataFormat},
+        FeeType
+    },
 };
 use super::{default_true_value, DataElement, RPCContractOutput, RPCTransaction};
 
@@ -131,6 +135,29 @@ pub struct GetMempoolSummaryResult<'a> {
     pub total: usize,
 }
 
+#[derive(Serialize, Deserialize, Default)]
+pub struct MempoolTxTypeStats {
+    pub transfers: usize,
+    pub burns: usize,
+    pub multisig: usize,
+    pub invoke_contracts: usize,
+    pub deploy_contracts: usize,
+    pub energy: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetMempoolStatsResult {
+    // How many TXs in total available in mempool
+    pub total: usize,
+    // Sum of all TXs size in bytes
+    pub total_size: usize,
+    // TXs count per transaction type
+    pub by_type: MempoolTxTypeStats,
+    // Transfers TXs count per asset
+    // A TX with transfers of several assets is counted for each of them
+    pub transfers_by_asset: HashMap<Hash, usize>,
+}
+
 pub type BlockResponse = RPCBlockResponse<'static>;
 
 #[derive(Serialize, Deserialize)]
@@ -222,7 +249,12 @@ pub struct SubmitBlockParams {
 #[derive(Serialize, Deserialize)]
 pub struct GetBalanceParams<'a> {
     pub address: Cow<'a, Address>,
-    pub asset: Cow<'a, Hash>
+    pub asset: Cow<'a, Hash>,
+    // Only used by get_stable_balance
+    // Custom confirmation depth from the top topoheight,
+    // it can't be less than the stable one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmations: Option<u64>
 }
 
 #[derive(Serialize, Deserialize)]
@@ -270,6 +302,18 @@ pub struct GetNonceResult {
     pub version: VersionedNonce
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetPendingNonceResult {
+    // Topoheight of the last on-chain nonce
+    pub topoheight: TopoHeight,
+    // Nonce stored on chain
+    pub nonce: Nonce,
+    // Highest nonce used by the account TXs in mempool
+    pub mempool_nonce: Option<Nonce>,
+    // Nonce to use for the next TX of this account
+    pub next_nonce: Nonce
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct HasNonceResult {
     pub exist: bool
@@ -285,7 +329,11 @@ pub struct GetBalanceResult {
 pub struct GetStableBalanceResult {
     pub version: VersionedBalance,
     pub stable_topoheight: TopoHeight,
-    pub stable_block_hash: Hash 
+    pub stable_block_hash: Hash,
+    // Topoheight used as the confirmation point
+    // stable topoheight of the chain or the one from the requested confirmations
+    #[serde(default)]
+    pub confirmed_topoheight: TopoHeight
 }
 
 #[derive(Serialize, Deserialize)]
@@ -335,6 +383,17 @@ pub struct SubmitTransactionParams {
     pub data: String // should be in hex format
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct VerifyTransactionResult<'a> {
+    pub hash: Cow<'a, Hash>,
+    pub valid: bool,
+    // Same error code as submit_transaction would return
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<i16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetTransactionParams<'a> {
     pub hash: Cow<'a, Hash>
@@ -356,7 +415,16 @@ pub struct GetPeersResponse<'a> {
     // All peers connected
     pub total_peers: usize,
     // Peers that asked to not be listed
-    pub hidden_peers: usize
+    pub hidden_peers: usize,
+    // Peers not matching the requested version requirement
+    #[serde(default)]
+    pub filtered_peers: usize
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetPeersParams<'a> {
+    // Semver requirement that the peers version must match (ex: >=1.17.0)
+    pub version_requirement: Option<Cow<'a, str>>
 }
 
 #[derive(Serialize, Deserialize)]
@@ -376,6 +444,26 @@ pub struct PeerEntry<'a> {
     pub connected_on: TimestampSeconds,
     pub bytes_sent: usize,
     pub bytes_recv: usize,
+    // Measured rates in bytes per second
+    #[serde(default)]
+    pub send_rate: u64,
+    #[serde(default)]
+    pub recv_rate: u64,
+    // Average round trip time in milliseconds measured using object requests
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    // Mempool size advertised by the peer in its last ping
+    #[serde(default)]
+    pub mempool_size: Option<u64>,
+    // Packets waiting to be sent to the peer
+    #[serde(default)]
+    pub pending_send: usize,
+    // Lifetime successful connections to this address
+    #[serde(default)]
+    pub connection_count: u64,
+    // Lifetime failed connection attempts to this address
+    #[serde(default)]
+    pub failure_count: u64,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -386,7 +474,13 @@ pub struct P2pStatusResult<'a> {
     pub our_topoheight: TopoHeight,
     pub best_topoheight: TopoHeight,
     pub median_topoheight: TopoHeight,
-    pub peer_id: u64
+    pub peer_id: u64,
+    #[serde(default)]
+    pub inbound_peers: usize,
+    #[serde(default)]
+    pub outbound_peers: usize,
+    #[serde(default)]
+    pub max_inbound_peers: usize,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -401,6 +495,22 @@ pub struct GetHeightRangeParams {
     pub end_height: Option<u64>
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetBlocksAtTopoHeightRangeParams {
+    pub start: TopoHeight,
+    pub end: TopoHeight,
+    #[serde(default)]
+    pub include_txs: bool
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetBlocksAtTopoHeightRangeResult<T> {
+    // Blocks ordered by topoheight
+    pub blocks: Vec<T>,
+    // Topoheight to continue from if the size limit was reached
+    pub next_topoheight: Option<TopoHeight>
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetTransactionsParams {
     pub tx_hashes: Vec<Hash>
@@ -438,6 +548,20 @@ pub struct GetAccountHistoryParams {
     // Any outgoing funds tracked
     #[serde(default = "default_true_value")]
     pub outgoing_flow: bool,
+    // Cursor for pagination, only entries strictly below it are returned
+    #[serde(default)]
+    pub before_topoheight: Option<TopoHeight>,
+    // Minimum entries count to return, a topoheight is never split
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetAccountHistoryPageResult {
+    // Entries in descending topoheight order
+    pub entries: Vec<AccountHistoryEntry>,
+    // Topoheight to use as `before_topoheight` to get the next page
+    pub next_before_topoheight: Option<TopoHeight>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -476,7 +600,18 @@ pub struct AccountHistoryEntry {
 pub struct GetAccountAssetsParams<'a> {
     pub address: Cow<'a, Address>,
     pub skip: Option<usize>,
-    pub maximum: Option<usize>
+    pub maximum: Option<usize>,
+    // Include the latest balance of each asset in the response
+    #[serde(default)]
+    pub include_balances: bool
+}
+
+// Asset returned by get_account_assets when balances are included
+#[derive(Serialize, Deserialize)]
+pub struct AccountAssetBalance {
+    pub asset: Hash,
+    pub version: VersionedBalance,
+    pub topoheight: TopoHeight
 }
 
 #[derive(Serialize, Deserialize)]
@@ -484,6 +619,23 @@ pub struct GetAssetParams<'a> {
     pub asset: Cow<'a, Hash>
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetAssetSupplyHistoryParams<'a> {
+    pub asset: Cow<'a, Hash>,
+    // Default to the pruned topoheight if any
+    pub start_topoheight: Option<TopoHeight>,
+    // Default to the current topoheight
+    pub end_topoheight: Option<TopoHeight>,
+    // Interval between two points, default to 1
+    pub step: Option<u64>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AssetSupplyPoint {
+    pub topoheight: TopoHeight,
+    pub supply: u64
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetAssetsParams {
     pub skip: Option<usize>,
@@ -528,7 +680,7 @@ pub struct DevFeeThreshold {
 }
 
 // Struct to define hard fork
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct HardFork {
     // block height to start hard fork
     pub height: u64,
@@ -541,6 +693,23 @@ pub struct HardFork {
     pub version_requirement: Option<&'static str>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct HardForkEntry<'a> {
+    #[serde(flatten)]
+    pub hard_fork: Cow<'a, HardFork>,
+    // Is it the hard fork currently in use
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetHardForksScheduleResult<'a> {
+    pub hard_forks: Vec<HardForkEntry<'a>>,
+    // Height used to determine the active hard fork
+    pub height: u64,
+    // Height of the next scheduled hard fork
+    pub next_height: Option<u64>,
+}
+
 // Struct to returns the size of the blockchain on disk
 #[derive(Serialize, Deserialize)]
 pub struct SizeOnDiskResult {
@@ -548,11 +717,38 @@ pub struct SizeOnDiskResult {
     pub size_formatted: String
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct CreateSnapshotParams<'a> {
+    // Directory in which the snapshot is created
+    // It must not exist yet
+    pub path: Cow<'a, str>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateSnapshotResult {
+    // Topoheight of the chain state exported
+    pub topoheight: TopoHeight
+}
+
+// Result of a manual storage compaction
+#[derive(Serialize, Deserialize)]
+pub struct StorageCompactResult {
+    // Time spent compacting in milliseconds
+    pub elapsed_ms: u64
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetMempoolCacheParams<'a> {
     pub address: Cow<'a, Address>
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetMempoolBySourceParams<'a> {
+    pub address: Cow<'a, Address>,
+    pub maximum: Option<usize>,
+    pub skip: Option<usize>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetMempoolCacheResult {
     // lowest nonce used
@@ -565,6 +761,13 @@ pub struct GetMempoolCacheResult {
     balances: HashMap<Hash, CiphertextCache>
 }
 
+// Parameters for the fee rates estimation based on the mempool and the last blocks
+#[derive(Serialize, Deserialize)]
+pub struct EstimateFeeRatesParams {
+    // How many recent blocks to include in addition to the mempool
+    pub blocks: Option<u64>,
+}
+
 // This struct is used to store the fee rate estimation for the following priority levels:
 // 1. Low
 // 2. Medium
@@ -586,6 +789,25 @@ pub struct GetDifficultyResult {
     pub hashrate_formatted: String
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetDifficultyHistoryParams {
+    // Default to 0
+    pub start_height: Option<u64>,
+    // Default to the current height
+    pub end_height: Option<u64>,
+    // Interval between two points, default to 1
+    pub step: Option<u64>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DifficultyPoint {
+    pub height: u64,
+    // Highest difficulty of the blocks at this height
+    pub difficulty: Difficulty,
+    pub hashrate: Difficulty,
+    pub hashrate_formatted: String
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ValidateAddressParams<'a> {
     pub address: Cow<'a, Address>,
@@ -624,7 +846,22 @@ pub struct MakeIntegratedAddressParams<'a> {
 #[derive(Serialize, Deserialize)]
 pub struct DecryptExtraDataParams<'a> {
     pub shared_key: Cow<'a, SharedKey>,
-    pub extra_data: Cow<'a, UnknownExtraDataFormat>
+    pub extra_data: Cow<'a, UnknownExtraDataFormat>,
+    // Return the plaintext with its decoding result
+    // instead of failing if it's not a valid DataElement
+    #[serde(default)]
+    pub parse: bool
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DecryptExtraDataResult {
+    // Decrypted bytes
+    pub plaintext: PlaintextData,
+    // Set if the plaintext is a valid DataElement
+    pub data: Option<DataElement>,
+    // Fields decoded from the data if it is a map,
+    // as used by integrated addresses
+    pub integrated_data: Option<HashMap<String, DataElement>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -681,9 +918,82 @@ pub struct GetContractOutputsParams<'a> {
     pub transaction: Cow<'a, Hash>
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetContractOutputsByContractParams<'a> {
+    pub contract: Cow<'a, Hash>,
+    // Included, default to 0
+    pub start_topoheight: Option<TopoHeight>,
+    // Maximum transactions to return
+    pub limit: Option<usize>
+}
+
+// Outputs produced by a contract in a transaction
+#[derive(Serialize, Deserialize)]
+pub struct ContractTransactionOutputs<'a> {
+    // Topoheight at which the transaction was executed
+    pub topoheight: TopoHeight,
+    pub tx_hash: Cow<'a, Hash>,
+    pub outputs: Vec<RPCContractOutput<'a>>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetBlockExecutionParams<'a> {
+    pub block_hash: Cow<'a, Hash>
+}
+
+// A transaction executed by a block
+#[derive(Serialize, Deserialize)]
+pub struct ExecutedTransaction<'a> {
+    pub hash: Cow<'a, Hash>,
+    // Fee paid by the transaction
+    pub fee: u64,
+    pub fee_type: FeeType,
+    // Outputs produced if the transaction invoked a contract
+    pub outputs: Vec<RPCContractOutput<'a>>
+}
+
+// A transaction included in the block but not executed by it
+#[derive(Serialize, Deserialize)]
+pub struct SkippedTransaction<'a> {
+    pub hash: Cow<'a, Hash>,
+    // Block that executed the transaction, if any
+    pub executed_in_block: Option<Cow<'a, Hash>>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetBlockExecutionResult<'a> {
+    // Topoheight of the block, None if not ordered in the DAG
+    pub topoheight: Option<TopoHeight>,
+    // Transactions executed by the block, in execution order
+    pub executed: Vec<ExecutedTransaction<'a>>,
+    // Transactions included in the block but executed elsewhere or orphaned
+    pub skipped: Vec<SkippedTransaction<'a>>,
+    // Sum of the TOS fees paid by the executed transactions
+    pub total_fees: u64
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetContractModuleParams<'a> {
-    pub contract: Cow<'a, Hash>
+    pub contract: Cow<'a, Hash>,
+    // Set it to false to only retrieve the metadata
+    #[serde(default = "default_true_value")]
+    pub include_module: bool
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetContractModuleResult<'a> {
+    // Topoheight of the latest contract version
+    pub topoheight: TopoHeight,
+    pub previous_topoheight: Option<TopoHeight>,
+    // Module of the contract, None if deleted or not requested
+    pub data: Option<Cow<'a, Module>>,
+    // Serialized module size in bytes
+    pub module_size: Option<usize>,
+    // Topoheight at which the contract got deployed
+    // None if the deploy TX is not available anymore
+    pub deployed_topoheight: Option<TopoHeight>,
+    // Source of the deploy TX
+    pub deployer: Option<Address>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -699,6 +1009,30 @@ pub struct GetContractDataAtTopoHeightParams<'a> {
     pub topoheight: TopoHeight
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetContractDataRangeParams<'a> {
+    pub contract: Cow<'a, Hash>,
+    // Key to start from (included), use the `next_key` of the previous page
+    pub start_key: Option<Cow<'a, ValueCell>>,
+    pub limit: Option<usize>,
+    // Snapshot of the contract data at this topoheight
+    // Use the current topoheight if not set
+    pub topoheight: Option<TopoHeight>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ContractDataEntry {
+    pub key: ValueCell,
+    pub value: ValueCell
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetContractDataRangeResult {
+    pub entries: Vec<ContractDataEntry>,
+    // Key to use as `start_key` to fetch the next page
+    pub next_key: Option<ValueCell>
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetContractBalanceParams<'a> {
     pub contract: Cow<'a, Hash>,
@@ -712,6 +1046,15 @@ pub struct GetContractBalanceAtTopoHeightParams<'a> {
     pub topoheight: TopoHeight
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetContractBalancesForAssetsParams<'a> {
+    pub contract: Cow<'a, Hash>,
+    pub assets: IndexSet<Cow<'a, Hash>>,
+    // Maximum topoheight to use for each balance
+    // Use the current topoheight if not set
+    pub topoheight: Option<TopoHeight>
+}
+
 
 #[derive(Serialize, Deserialize)]
 pub struct GetContractBalancesParams<'a> {
@@ -746,6 +1089,48 @@ pub struct FreezeRecordInfo {
     pub remaining_blocks: u64,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetFreezeHistoryParams<'a> {
+    pub address: Cow<'a, Address>,
+    // Highest topoheight to start from (inclusive)
+    // Defaults to the last nonce update of the account
+    #[serde(default)]
+    pub start_topoheight: Option<TopoHeight>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FreezeHistoryType {
+    Freeze {
+        amount: u64,
+        duration: String,
+        energy_gained: u64,
+        unlock_topoheight: TopoHeight,
+    },
+    Unfreeze {
+        amount: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FreezeHistoryEntry {
+    pub topoheight: TopoHeight,
+    pub hash: Hash,
+    pub block_timestamp: TimestampMillis,
+    #[serde(flatten)]
+    pub history_type: FreezeHistoryType,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetFreezeHistoryResult {
+    // Entries in descending topoheight order
+    pub entries: Vec<FreezeHistoryEntry>,
+    // Topoheight to use as `start_topoheight` to get the next page
+    pub next_start_topoheight: Option<TopoHeight>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct RPCVersioned<T> {
     pub topoheight: TopoHeight,
@@ -763,6 +1148,23 @@ pub struct P2pBlockPropagationResult {
     pub processing_at: Option<TimestampMillis>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetPropagationStatsParams {
+    // How many accepted blocks to use
+    pub count: Option<usize>,
+}
+
+// Delays are in milliseconds between the first time
+// we saw the block and its inclusion in our chain
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PropagationStats {
+    // How many blocks were used to compute the stats
+    pub count: usize,
+    pub mean: Option<u64>,
+    pub median: Option<u64>,
+    pub p95: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetP2pBlockPropagation<'a> {
     pub hash: Cow<'a, Hash>,
@@ -772,6 +1174,114 @@ pub struct GetP2pBlockPropagation<'a> {
     pub incoming: bool,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct P2pDisconnectPeerParams {
+    pub peer_id: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct P2pProbeParams {
+    pub address: SocketAddr,
+}
+
+// Information advertised by a node in its handshake
+#[derive(Serialize, Deserialize)]
+pub struct ProbeResult {
+    pub addr: SocketAddr,
+    pub id: u64,
+    pub tag: Option<String>,
+    pub version: String,
+    pub top_block_hash: Hash,
+    pub topoheight: TopoHeight,
+    pub height: u64,
+    pub pruned_topoheight: Option<TopoHeight>,
+    pub cumulative_difficulty: CumulativeDifficulty,
+    // Time in milliseconds to connect and exchange the handshakes
+    pub elapsed_ms: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct P2pResyncMempoolParams {
+    pub peer_id: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetSyncStatusResult {
+    // If our chain is synced with the median topoheight of our peers
+    pub synced: bool,
+    // If we are currently syncing the chain with a peer
+    pub syncing: bool,
+    // Our current topoheight
+    pub topoheight: TopoHeight,
+    // Median topoheight of our peers, None if no peer is connected
+    pub network_topoheight: Option<TopoHeight>,
+    // Count of connected peers
+    pub peers: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct P2pSyncFromPeerParams {
+    // Peer to use for the next chain sync
+    pub peer_id: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetNetworkTimeOffsetResult {
+    // Median offset in seconds between the peers clocks and ours
+    // Positive if the network is ahead of us, None if no peer is connected
+    pub offset: Option<i64>,
+    // Count of peers used to compute the offset
+    pub peers: usize
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AddCheckpointParams<'a> {
+    pub block_hash: Cow<'a, Hash>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AddCheckpointResult {
+    // Topoheight of the block used as checkpoint
+    pub topoheight: TopoHeight
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RewindChainParams {
+    // Topoheight to rewind the chain to
+    pub topoheight: TopoHeight
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RewindChainResult {
+    // Topoheight of the chain after the rewind
+    pub topoheight: TopoHeight,
+    // Tips of the chain after the rewind
+    pub tips: Vec<Hash>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetObjectTrackerStatusResult {
+    // Requests still waiting for a response
+    pub pending_requests: usize,
+    // Age of the oldest request sent still waiting for a response
+    pub oldest_request_age_ms: Option<u64>,
+    // Requests that timed out since the node started
+    pub timed_out_requests: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct P2pSetTxPropagationParams {
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct P2pBanSubnetParams<'a> {
+    // Subnet in CIDR notation (ex: 192.168.1.0/24)
+    pub subnet: Cow<'a, str>,
+    // Ban duration in seconds
+    pub duration: u64,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum NotifyEvent {
@@ -784,6 +1294,9 @@ pub enum NotifyEvent {
     // When a block that was ordered is not in the new DAG order
     // it contains BlockOrphanedEvent that got orphaned
     BlockOrphaned,
+    // When the DAG got reorganized and at least one block got orphaned
+    // it is fired once per reorg and contains ChainReorgEvent as value
+    ChainReorg,
     // When stable height has changed (different than the previous one)
     // it contains StableHeightChangedEvent struct as value
     StableHeightChanged,
@@ -796,6 +1309,9 @@ pub enum NotifyEvent {
     // When a new transaction is added in mempool
     // it contains TransactionAddedInMempoolEvent struct as value
     TransactionAddedInMempool,
+    // When a transaction is evicted from the mempool without being executed
+    // it contains MempoolTransactionEvictedEvent struct as value
+    MempoolTransactionEvicted,
     // When a transaction has been included in a valid block & executed on chain
     // it contains TransactionExecutedEvent struct as value
     TransactionExecuted,
@@ -823,6 +1339,9 @@ pub enum NotifyEvent {
     // When a new asset has been registered
     // It contains NewAssetEvent struct as value
     NewAsset,
+    // When the data of an asset has been updated
+    // It contains AssetUpdatedEvent struct as value
+    AssetUpdated,
     // When a new peer has connected to us
     // It contains PeerConnectedEvent struct as value
     PeerConnected,
@@ -840,7 +1359,11 @@ pub enum NotifyEvent {
     // It contains PeerPeerDisconnectedEvent as value
     PeerPeerDisconnected,
     // A new block template has been created
+    // It contains NewBlockTemplateEvent as value
     NewBlockTemplate,
+    // When a step of the fast sync got processed
+    // It contains BootstrapProgressEvent as value
+    BootstrapProgress,
 }
 
 // Value of NotifyEvent::NewBlock
@@ -864,6 +1387,18 @@ pub struct BlockOrphanedEvent<'a> {
     pub old_topoheight: TopoHeight
 }
 
+// Value of NotifyEvent::ChainReorg
+#[derive(Serialize, Deserialize)]
+pub struct ChainReorgEvent<'a> {
+    // Last block that stayed at the same topoheight
+    pub common_base_hash: Cow<'a, Hash>,
+    pub common_base_topoheight: TopoHeight,
+    // Blocks that are no longer ordered, in their old order
+    pub orphaned: Vec<Hash>,
+    // Blocks (re)ordered above the common base, in their new order
+    pub applied: Vec<Hash>,
+}
+
 // Value of NotifyEvent::StableHeightChanged
 #[derive(Serialize, Deserialize)]
 pub struct StableHeightChangedEvent {
@@ -884,6 +1419,30 @@ pub type TransactionAddedInMempoolEvent = MempoolTransactionSummary<'static>;
 // Value of NotifyEvent::TransactionOrphaned
 pub type TransactionOrphanedEvent = TransactionResponse<'static>;
 
+// Reason of a TX eviction from the mempool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MempoolEvictionReason {
+    // Replaced by another TX
+    Replaced,
+    // TX is no longer valid against the chain state
+    // or depends on a TX from the same source that was evicted
+    Invalidated,
+    // TX nonce is no longer usable for the source
+    NonceConflict,
+    // Mempool is full
+    // Reserved: the mempool has no capacity limit yet, so it is never emitted
+    CapacityPressure,
+}
+
+// Value of NotifyEvent::MempoolTransactionEvicted
+#[derive(Serialize, Deserialize)]
+pub struct MempoolTransactionEvictedEvent<'a> {
+    pub hash: Cow<'a, Hash>,
+    pub source: Address,
+    pub reason: MempoolEvictionReason,
+}
+
 // Value of NotifyEvent::TransactionExecuted
 #[derive(Serialize, Deserialize)]
 pub struct TransactionExecutedEvent<'a> {
@@ -900,6 +1459,16 @@ pub struct NewAssetEvent<'a> {
     pub topoheight: TopoHeight,
 }
 
+// Value of NotifyEvent::AssetUpdated
+#[derive(Serialize, Deserialize)]
+pub struct AssetUpdatedEvent<'a> {
+    pub asset: Cow<'a, Hash>,
+    // New data of the asset
+    pub data: Cow<'a, AssetData>,
+    pub block_hash: Cow<'a, Hash>,
+    pub topoheight: TopoHeight,
+}
+
 // Value of NotifyEvent::ContractTransfer
 #[derive(Serialize, Deserialize)]
 pub struct ContractTransferEvent<'a> {
@@ -942,6 +1511,34 @@ pub struct PeerPeerDisconnectedEvent {
     pub peer_addr: SocketAddr
 }
 
+// Value of NotifyEvent::NewBlockTemplate
+#[derive(Serialize, Deserialize)]
+pub struct NewBlockTemplateEvent<'a> {
+    #[serde(flatten)]
+    pub template: GetBlockTemplateResult,
+    // Tips used by the new template
+    pub tips: Cow<'a, IndexSet<Hash>>,
+    // Count of TXs included in the new template
+    pub txs_count: usize,
+    // Set to true if the height, tips or TXs set changed
+    // compared to the previous template
+    // If false, only the timestamp/extra nonce differ
+    pub previous_stale: bool
+}
+
+// Value of NotifyEvent::BootstrapProgress
+#[derive(Serialize, Deserialize)]
+pub struct BootstrapProgressEvent<'a> {
+    // Current step of the fast sync
+    pub step: Cow<'a, str>,
+    // Items processed in this step so far
+    pub processed: u64,
+    // Items expected in this step, if known in advance
+    pub total: Option<u64>,
+    // Rough progress of the whole fast sync, from 0 to 100
+    pub percentage: f64
+}
+
 // Value of NotifyEvent::InvokeContract
 #[derive(Serialize, Deserialize)]
 pub struct InvokeContractEvent<'a> {
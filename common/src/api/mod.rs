@@ -33,6 +33,8 @@ use crate::{
         TransferPayload,
         TxVersion,
         FeeType,
+        EXTRA_DATA_LIMIT_SIZE,
+        EXTRA_DATA_LIMIT_SUM_SIZE,
     }
 };
 pub use data::*;
@@ -67,6 +69,58 @@ pub struct RPCTransferPayload<'a> {
     pub ct_validity_proof: Cow<'a, CiphertextValidityProof>,
 }
 
+impl<'a> RPCTransferPayload<'a> {
+    // Size in bytes of the extra data as verified by the protocol
+    // Zero if no extra data is set
+    pub fn extra_data_size(&self) -> usize {
+        self.extra_data.as_ref()
+            .as_ref()
+            .map_or(0, |extra_data| extra_data.size())
+    }
+}
+
+// Extra data size of a transfer compared to the protocol limit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TransferExtraDataSize {
+    pub size: usize,
+    // If the size is within EXTRA_DATA_LIMIT_SIZE
+    pub within_limit: bool,
+}
+
+// Extra data sizes of all the transfers of a transaction
+// This allows to flag the oversized payloads without decrypting them
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExtraDataSizeReport {
+    pub transfers: Vec<TransferExtraDataSize>,
+    pub total: usize,
+    // If the total is within EXTRA_DATA_LIMIT_SUM_SIZE
+    pub within_total_limit: bool,
+}
+
+impl ExtraDataSizeReport {
+    // Build the report from the extra data size of each transfer
+    pub fn new(sizes: impl IntoIterator<Item = usize>) -> Self {
+        let transfers: Vec<_> = sizes.into_iter()
+            .map(|size| TransferExtraDataSize {
+                size,
+                within_limit: size <= EXTRA_DATA_LIMIT_SIZE
+            })
+            .collect();
+
+        let total = transfers.iter().map(|transfer| transfer.size).sum();
+        Self {
+            transfers,
+            total,
+            within_total_limit: total <= EXTRA_DATA_LIMIT_SUM_SIZE
+        }
+    }
+
+    // If all the extra data respect the protocol limits
+    pub fn is_valid(&self) -> bool {
+        self.within_total_limit && self.transfers.iter().all(|transfer| transfer.within_limit)
+    }
+}
+
 impl<'a> From<RPCTransferPayload<'a>> for TransferPayload {
     fn from(transfer: RPCTransferPayload<'a>) -> Self {
         TransferPayload::new(
@@ -183,6 +237,15 @@ impl<'a> RPCTransaction<'a> {
             size: tx.size()
         }
     }
+
+    // Report the extra data size of each transfer
+    // None if the transaction is not a transfer
+    pub fn extra_data_size_report(&self) -> Option<ExtraDataSizeReport> {
+        match &self.data {
+            RPCTransactionType::Transfers(transfers) => Some(ExtraDataSizeReport::new(transfers.iter().map(RPCTransferPayload::extra_data_size))),
+            _ => None
+        }
+    }
 }
 
 impl<'a> From<RPCTransaction<'a>> for Transaction {
@@ -310,4 +373,29 @@ fn default_true_value() -> bool {
 // same here
 fn default_false_value() -> bool {
     false
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extra_data_size_report() {
+        let report = ExtraDataSizeReport::new([0, 10, EXTRA_DATA_LIMIT_SIZE]);
+        assert_eq!(report.total, 10 + EXTRA_DATA_LIMIT_SIZE);
+        assert!(report.within_total_limit);
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn test_extra_data_size_report_oversized() {
+        let report = ExtraDataSizeReport::new([EXTRA_DATA_LIMIT_SIZE + 1]);
+        assert!(!report.transfers[0].within_limit);
+        assert!(!report.is_valid());
+
+        let report = ExtraDataSizeReport::new(vec![EXTRA_DATA_LIMIT_SIZE; EXTRA_DATA_LIMIT_SUM_SIZE / EXTRA_DATA_LIMIT_SIZE + 1]);
+        assert!(report.transfers.iter().all(|transfer| transfer.within_limit));
+        assert!(!report.within_total_limit);
+        assert!(!report.is_valid());
+    }
+}
@@ -105,7 +105,9 @@ pub struct ContractEventTracker {
     // All the transfers made by all contracts
     pub transfers: HashMap<PublicKey, HashMap<Hash, u64>>,
     // All assets registered by all contracts
-    pub assets_created: HashSet<Hash>
+    pub assets_created: HashSet<Hash>,
+    // All assets having their data updated by contracts
+    pub assets_updated: HashSet<Hash>
 }
 
 // Build the environment for the contract
@@ -138,11 +140,13 @@ pub fn build_environment<P: ContractProvider>() -> EnvironmentBuilder<'static> {
     let hash_type = Type::Opaque(env.register_opaque::<Hash>("Hash", true));
     let address_type = Type::Opaque(env.register_opaque::<Address>("Address", true));
     let signature_type = Type::Opaque(env.register_opaque::<Signature>("Signature", true));
+    let public_key_type = Type::Opaque(env.register_opaque::<PublicKey>("PublicKey", true));
 
     // Crypto
     let ciphertext_type = Type::Opaque(env.register_opaque::<CiphertextCache>("Ciphertext", true));
     let _ = Type::Opaque(env.register_opaque::<CiphertextValidityProof>("CiphertextValidityProof", true));
     let _ = Type::Opaque(env.register_opaque::<RangeProofWrapper>("RangeProof", true));
+    let block_info_type = Type::Opaque(env.register_opaque::<BlockInfo>("BlockInfo", true));
 
     // Transaction
     {
@@ -296,6 +300,50 @@ pub fn build_environment<P: ContractProvider>() -> EnvironmentBuilder<'static> {
             1,
             Some(Type::U32)
         );
+        env.register_native_function(
+            "topoheight",
+            Some(block_type.clone()),
+            vec![],
+            block_topoheight,
+            5,
+            Some(Type::U64)
+        );
+        env.register_native_function(
+            "info",
+            Some(block_type.clone()),
+            vec![],
+            block_info,
+            5,
+            Some(block_info_type.clone())
+        );
+    }
+
+    // BlockInfo
+    {
+        env.register_native_function(
+            "height",
+            Some(block_info_type.clone()),
+            vec![],
+            block_info_height,
+            1,
+            Some(Type::U64)
+        );
+        env.register_native_function(
+            "topoheight",
+            Some(block_info_type.clone()),
+            vec![],
+            block_info_topoheight,
+            1,
+            Some(Type::U64)
+        );
+        env.register_native_function(
+            "timestamp",
+            Some(block_info_type.clone()),
+            vec![],
+            block_info_timestamp,
+            1,
+            Some(Type::U64)
+        );
     }
 
     // Storage
@@ -453,6 +501,42 @@ pub fn build_environment<P: ContractProvider>() -> EnvironmentBuilder<'static> {
         );
     }
 
+    // PublicKey
+    {
+        env.register_native_function(
+            "to_bytes",
+            Some(public_key_type.clone()),
+            vec![],
+            public_key_to_bytes,
+            5,
+            Some(Type::Bytes)
+        );
+        env.register_native_function(
+            "to_address",
+            Some(public_key_type.clone()),
+            vec![],
+            public_key_to_address,
+            10,
+            Some(address_type.clone())
+        );
+        env.register_static_function(
+            "from_bytes",
+            public_key_type.clone(),
+            vec![("bytes", Type::Bytes)],
+            public_key_from_bytes,
+            75,
+            Some(public_key_type.clone())
+        );
+        env.register_static_function(
+            "from_address",
+            public_key_type.clone(),
+            vec![("address", address_type.clone())],
+            public_key_from_address,
+            5,
+            Some(public_key_type.clone())
+        );
+    }
+
     // Hash
     {
         env.register_native_function(
@@ -167,6 +167,7 @@ pub fn asset_transfer_ownership<P: ContractProvider>(zelf: FnInstance, mut param
         Some(data) if *data.get_contract() == contract => {
             data.set_contract(param);
             changes.data.0.mark_updated();
+            state.tracker.assets_updated.insert(asset.hash.clone());
             Primitive::Boolean(true)
         },
         _ => Primitive::Boolean(false)
@@ -1,5 +1,7 @@
 use anyhow::Context as AnyhowContext;
+use serde::{Deserialize, Serialize};
 use terminos_vm::{
+    impl_opaque,
     traits::{JSONHelper, Serializable},
     Context,
     FnInstance,
@@ -10,7 +12,12 @@ use terminos_vm::{
     ValueCell
 };
 
-use crate::{block::Block, contract::ChainState};
+use crate::{
+    block::{Block, TopoHeight},
+    contract::{ChainState, BLOCK_INFO_OPAQUE_ID},
+    serializer::*,
+    time::TimestampMillis
+};
 
 use super::OpaqueTransaction;
 
@@ -21,6 +28,62 @@ impl JSONHelper for OpaqueBlock {}
 
 impl Serializable for OpaqueBlock {}
 
+// Copy of the current block metadata
+// Unlike the Block opaque, it can be stored by the contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlockInfo {
+    pub height: u64,
+    pub topoheight: TopoHeight,
+    pub timestamp: TimestampMillis
+}
+
+impl_opaque!(
+    "BlockInfo",
+    BlockInfo
+);
+impl_opaque!(
+    "BlockInfo",
+    BlockInfo,
+    json
+);
+
+impl Serializer for BlockInfo {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u64(&self.height);
+        writer.write_u64(&self.topoheight);
+        writer.write_u64(&self.timestamp);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self {
+            height: reader.read_u64()?,
+            topoheight: reader.read_u64()?,
+            timestamp: reader.read_u64()?
+        })
+    }
+
+    fn size(&self) -> usize {
+        8 * 3
+    }
+}
+
+impl Serializable for BlockInfo {
+    fn get_size(&self) -> usize {
+        1 + self.size()
+    }
+
+    fn is_serializable(&self) -> bool {
+        true
+    }
+
+    fn serialize(&self, buffer: &mut Vec<u8>) -> usize {
+        let mut writer = Writer::new(buffer);
+        writer.write_u8(BLOCK_INFO_OPAQUE_ID);
+        self.write(&mut writer);
+        writer.total_write()
+    }
+}
+
 pub fn block_current(_: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
     Ok(Some(Primitive::Opaque(OpaqueWrapper::new(OpaqueBlock)).into()))
 }
@@ -102,6 +165,38 @@ pub fn block_height(_: FnInstance, _: FnParams, context: &mut Context) -> FnRetu
     Ok(Some(Primitive::U64(block.get_height()).into()))
 }
 
+pub fn block_topoheight(_: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
+    let state: &ChainState = context.get().context("chain state not found")?;
+    Ok(Some(Primitive::U64(state.topoheight).into()))
+}
+
+pub fn block_info(_: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
+    let block: &Block = context.get().context("current block not found")?;
+    let state: &ChainState = context.get().context("chain state not found")?;
+
+    let info = BlockInfo {
+        height: block.get_height(),
+        topoheight: state.topoheight,
+        timestamp: block.get_timestamp()
+    };
+    Ok(Some(Primitive::Opaque(OpaqueWrapper::new(info)).into()))
+}
+
+pub fn block_info_height(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
+    let info: &BlockInfo = zelf?.as_opaque_type()?;
+    Ok(Some(Primitive::U64(info.height).into()))
+}
+
+pub fn block_info_topoheight(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
+    let info: &BlockInfo = zelf?.as_opaque_type()?;
+    Ok(Some(Primitive::U64(info.topoheight).into()))
+}
+
+pub fn block_info_timestamp(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
+    let info: &BlockInfo = zelf?.as_opaque_type()?;
+    Ok(Some(Primitive::U64(info.timestamp).into()))
+}
+
 pub fn block_extra_nonce(_: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
     let block: &Block = context.get().context("current block not found")?;
     let extra_nonce = block.get_extra_nonce()
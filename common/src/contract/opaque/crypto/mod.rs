@@ -1,9 +1,11 @@
 mod hash;
 mod signature;
+mod public_key;
 mod ciphertext;
 mod proofs;
 
 pub use hash::*;
 pub use signature::*;
+pub use public_key::*;
 pub use ciphertext::*;
 pub use proofs::*;
\ No newline at end of file
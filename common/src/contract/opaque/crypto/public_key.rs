@@ -0,0 +1,72 @@
+use anyhow::Context as AnyhowContext;
+use terminos_vm::{
+    traits::Serializable,
+    Context,
+    EnvironmentError,
+    FnInstance,
+    FnParams,
+    FnReturnType,
+    OpaqueWrapper,
+    Primitive,
+    ValueCell
+};
+use crate::{
+    contract::{ChainState, PUBLIC_KEY_OPAQUE_ID},
+    crypto::{elgamal::RISTRETTO_COMPRESSED_SIZE, Address, PublicKey},
+    serializer::{Serializer, Writer}
+};
+
+impl Serializable for PublicKey {
+    fn serialize(&self, buffer: &mut Vec<u8>) -> usize {
+        let mut writer = Writer::new(buffer);
+        writer.write_u8(PUBLIC_KEY_OPAQUE_ID);
+        self.write(&mut writer);
+        writer.total_write()
+    }
+
+    fn get_size(&self) -> usize {
+        1 + self.size()
+    }
+
+    fn is_serializable(&self) -> bool {
+        true
+    }
+}
+
+pub fn public_key_to_bytes(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
+    let key: &PublicKey = zelf?.as_opaque_type()?;
+    Ok(Some(ValueCell::Bytes(key.as_bytes().into())))
+}
+
+// Convert the public key to a normal address for the current network
+pub fn public_key_to_address(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
+    let key: &PublicKey = zelf?.as_opaque_type()?;
+    let state: &ChainState = context.get().context("chain state not found")?;
+
+    let address = key.as_address(state.mainnet);
+    Ok(Some(Primitive::Opaque(OpaqueWrapper::new(address)).into()))
+}
+
+pub fn public_key_from_bytes(_: FnInstance, mut params: FnParams, _: &mut Context) -> FnReturnType {
+    let param = params.remove(0)
+        .into_owned()?;
+    let bytes = param.as_bytes()?;
+
+    if bytes.len() != RISTRETTO_COMPRESSED_SIZE {
+        return Err(EnvironmentError::InvalidParameter);
+    }
+
+    let key = PublicKey::from_bytes(&bytes)
+        .map_err(|_| EnvironmentError::InvalidParameter)?;
+
+    Ok(Some(Primitive::Opaque(OpaqueWrapper::new(key)).into()))
+}
+
+// Extract the public key from an address, dropping its network and integrated data
+pub fn public_key_from_address(_: FnInstance, mut params: FnParams, _: &mut Context) -> FnReturnType {
+    let param = params.remove(0)
+        .into_owned()?;
+    let address: Address = param.into_opaque_type()?;
+
+    Ok(Some(Primitive::Opaque(OpaqueWrapper::new(address.to_public_key())).into()))
+}
@@ -17,7 +17,7 @@ use terminos_vm::{tid, traits::JSON_REGISTRY, OpaqueWrapper};
 use crate::{
     account::CiphertextCache,
     block::Block,
-    crypto::{proofs::CiphertextValidityProof, Address, Hash, Signature},
+    crypto::{proofs::CiphertextValidityProof, Address, Hash, PublicKey, Signature},
     serializer::*,
     transaction::Transaction
 };
@@ -39,6 +39,21 @@ pub const SIGNATURE_OPAQUE_ID: u8 = 2;
 pub const CIPHERTEXT_OPAQUE_ID: u8 = 3;
 pub const CIPHERTEXT_VALIDITY_PROOF_OPAQUE_ID: u8 = 4;
 pub const RANGE_PROOF_OPAQUE_ID: u8 = 5;
+pub const BLOCK_INFO_OPAQUE_ID: u8 = 6;
+pub const PUBLIC_KEY_OPAQUE_ID: u8 = 7;
+
+// All opaque types serialization IDs with their name
+// Any new opaque ID must be added here to be checked at registration
+pub const OPAQUE_IDS: [(u8, &str); 8] = [
+    (HASH_OPAQUE_ID, "Hash"),
+    (ADDRESS_OPAQUE_ID, "Address"),
+    (SIGNATURE_OPAQUE_ID, "Signature"),
+    (CIPHERTEXT_OPAQUE_ID, "Ciphertext"),
+    (CIPHERTEXT_VALIDITY_PROOF_OPAQUE_ID, "CiphertextValidityProof"),
+    (RANGE_PROOF_OPAQUE_ID, "RangeProof"),
+    (BLOCK_INFO_OPAQUE_ID, "BlockInfo"),
+    (PUBLIC_KEY_OPAQUE_ID, "PublicKey"),
+];
 
 impl_opaque!(
     "Hash",
@@ -52,6 +67,12 @@ impl_opaque!(
     display,
     json
 );
+impl_opaque!(
+    "PublicKey",
+    PublicKey,
+    display,
+    json
+);
 impl_opaque!(
     "OpaqueTransaction",
     OpaqueTransaction
@@ -87,8 +108,22 @@ tid!(Hash);
 tid!(Transaction);
 tid!(Block);
 
+// Verify that no two opaque types share the same serialization ID
+// A collision would silently break the deserialization of one of them
+fn check_opaque_ids(ids: &[(u8, &str)]) {
+    let mut seen: [Option<&str>; 256] = [None; 256];
+    for &(id, name) in ids {
+        if let Some(other) = seen[id as usize] {
+            panic!("Opaque ID {} is used by both {} and {}", id, other, name);
+        }
+        seen[id as usize] = Some(name);
+    }
+}
+
 pub fn register_opaque_types() {
     debug!("Registering opaque types");
+    check_opaque_ids(&OPAQUE_IDS);
+
     let mut registry = JSON_REGISTRY.write().expect("Failed to lock JSON_REGISTRY");
     register_opaque_json!(registry, "Hash", Hash);
     register_opaque_json!(registry, "Address", Address);
@@ -96,6 +131,8 @@ pub fn register_opaque_types() {
     register_opaque_json!(registry, "Ciphertext", CiphertextCache);
     register_opaque_json!(registry, "CiphertextValidityProof", CiphertextValidityProof);
     register_opaque_json!(registry, "RangeProof", RangeProofWrapper);
+    register_opaque_json!(registry, "BlockInfo", BlockInfo);
+    register_opaque_json!(registry, "PublicKey", PublicKey);
 }
 
 impl Serializer for OpaqueWrapper {
@@ -111,6 +148,8 @@ impl Serializer for OpaqueWrapper {
             CIPHERTEXT_OPAQUE_ID => OpaqueWrapper::new(CiphertextCache::read(reader)?),
             CIPHERTEXT_VALIDITY_PROOF_OPAQUE_ID => OpaqueWrapper::new(CiphertextValidityProof::read(reader)?),
             RANGE_PROOF_OPAQUE_ID => OpaqueWrapper::new(RangeProofWrapper(RangeProof::read(reader)?)),
+            BLOCK_INFO_OPAQUE_ID => OpaqueWrapper::new(BlockInfo::read(reader)?),
+            PUBLIC_KEY_OPAQUE_ID => OpaqueWrapper::new(PublicKey::read(reader)?),
             _ => return Err(ReaderError::InvalidValue)
         })
     }
@@ -124,6 +163,28 @@ mod tests {
     use serde_json::json;
     use terminos_vm::OpaqueWrapper;
 
+    #[test]
+    fn test_opaque_ids_unique() {
+        check_opaque_ids(&OPAQUE_IDS);
+    }
+
+    #[test]
+    fn test_opaque_ids_match_reader() {
+        // The reader must accept exactly the IDs listed in OPAQUE_IDS
+        // A known ID fails on the missing payload, an unknown one on the ID itself
+        for id in 0..=u8::MAX {
+            let listed = OPAQUE_IDS.iter().any(|(opaque_id, _)| *opaque_id == id);
+            let rejected = matches!(OpaqueWrapper::from_bytes(&[id]), Err(ReaderError::InvalidValue));
+            assert_eq!(listed, !rejected, "Opaque ID {} is not in sync between OPAQUE_IDS and the reader", id);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Opaque ID 0 is used by both Hash and Other")]
+    fn test_opaque_ids_collision() {
+        check_opaque_ids(&[(HASH_OPAQUE_ID, "Hash"), (ADDRESS_OPAQUE_ID, "Address"), (0, "Other")]);
+    }
+
     #[test]
     fn test_address_serde() {
         register_opaque_types();
@@ -140,6 +201,22 @@ mod tests {
         assert_eq!(address, address2);
     }
 
+    #[test]
+    fn test_public_key_serde() {
+        register_opaque_types();
+
+        let key = KeyPair::new().get_public_key().compress();
+        let opaque = OpaqueWrapper::new(key.clone());
+        let v = json!(opaque);
+
+        let opaque: OpaqueWrapper = serde_json::from_value(v)
+            .unwrap();
+        let key2: PublicKey = opaque.into_inner()
+            .expect("Failed to unwrap");
+
+        assert_eq!(key, key2);
+    }
+
     #[test]
     fn test_hash_serde() {
         register_opaque_types();
@@ -155,4 +232,41 @@ mod tests {
 
         assert_eq!(hash, hash2);
     }
+
+    #[test]
+    fn test_block_info_serde() {
+        register_opaque_types();
+
+        let info = BlockInfo {
+            height: 42,
+            topoheight: 50,
+            timestamp: 1_700_000_000_000
+        };
+        let opaque = OpaqueWrapper::new(info);
+        let v = json!(opaque);
+
+        let opaque: OpaqueWrapper = serde_json::from_value(v)
+            .unwrap();
+        let info2: BlockInfo = opaque.into_inner()
+            .expect("Failed to unwrap");
+
+        assert_eq!(info, info2);
+    }
+
+    #[test]
+    fn test_block_info_serializer() {
+        let info = BlockInfo {
+            height: 1,
+            topoheight: 2,
+            timestamp: 3
+        };
+        let opaque = OpaqueWrapper::new(info);
+
+        let opaque = OpaqueWrapper::from_bytes(&opaque.to_bytes())
+            .unwrap();
+        let info2: BlockInfo = opaque.into_inner()
+            .expect("Failed to unwrap");
+
+        assert_eq!(info, info2);
+    }
 }
\ No newline at end of file
@@ -42,6 +42,12 @@ pub trait ContractStorage {
 
     // check if a contract hash exists in the storage
     fn has_contract(&self, contract: &Hash, topoheight: TopoHeight) -> Result<bool, anyhow::Error>;
+
+    // iterate over all the entries of a contract at a maximum topoheight
+    // each key is returned with its latest value at or below the topoheight, deleted entries are skipped
+    // the order is deterministic for a given backend: it follows the byte order of the stored keys
+    // and not the order of the keys values
+    fn iter_data<'a>(&'a self, contract: &'a Hash, topoheight: TopoHeight) -> Result<Box<dyn Iterator<Item = Result<(ValueCell, ValueCell), anyhow::Error>> + 'a>, anyhow::Error>;
 }
 
 impl JSONHelper for OpaqueStorage {}
@@ -1,3 +1,4 @@
+use std::fmt;
 use curve25519_dalek::{ristretto::CompressedRistretto, Scalar};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -140,6 +141,12 @@ impl CompressedPublicKey {
     }
 }
 
+impl fmt::Display for CompressedPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.as_bytes()))
+    }
+}
+
 impl Serializer for CompressedRistretto {
     fn write(&self, writer: &mut Writer) {
         writer.write_bytes(self.as_bytes());
@@ -1,7 +1,7 @@
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 use curve25519_dalek::{RistrettoPoint, Scalar};
-use rand::rngs::OsRng;
+use rand::{rngs::OsRng, CryptoRng, RngCore};
 
 use crate::crypto::{
     proofs::{G, PC_GENS},
@@ -21,7 +21,7 @@ impl PedersenOpening {
     }
 
     pub fn generate_new() -> Self {
-        PedersenOpening(Scalar::random(&mut OsRng))
+        OsRng.generate_opening()
     }
 
     pub fn as_scalar(&self) -> Scalar {
@@ -29,6 +29,19 @@ impl PedersenOpening {
     }
 }
 
+// Source of the openings used to build commitments
+// It is implemented for any cryptographically secure RNG
+// A seeded RNG must only be used for testing purposes
+pub trait OpeningProvider {
+    fn generate_opening(&mut self) -> PedersenOpening;
+}
+
+impl<R: RngCore + CryptoRng> OpeningProvider for R {
+    fn generate_opening(&mut self) -> PedersenOpening {
+        PedersenOpening(Scalar::random(self))
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PedersenCommitment(RistrettoPoint);
 
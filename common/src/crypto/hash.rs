@@ -4,6 +4,7 @@ use std::{
     convert::TryInto,
     fmt::{Display, Error, Formatter},
     hash::Hasher,
+    io::Read,
     str::FromStr
 };
 use serde::de::Error as SerdeError;
@@ -61,6 +62,15 @@ pub fn hash(value: &[u8]) -> Hash {
     Hash(result)
 }
 
+// Hash all the bytes from a reader using the blake3 algorithm
+// The content is streamed and never fully loaded in memory
+pub fn hash_reader<R: Read>(reader: R) -> std::io::Result<Hash> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(reader)?;
+    let result: [u8; HASH_SIZE] = hasher.finalize().into();
+    Ok(Hash(result))
+}
+
 // Perform a PoW hash using the given algorithm
 pub fn pow_hash(work: &[u8], algorithm: Algorithm) -> Result<Hash, TerminosHashError> {
     match algorithm {
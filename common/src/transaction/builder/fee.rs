@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-use crate::crypto::elgamal::CompressedPublicKey;
+use crate::{
+    crypto::{elgamal::CompressedPublicKey, Hash},
+    transaction::FeeType
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, Copy)]
 #[serde(rename_all = "snake_case")]
@@ -10,7 +13,13 @@ pub enum FeeBuilder {
     // set a direct value of how much fees you want to pay exactly
     Value(u64),
     // how much we want to pay above the calculated/needed fees.
-    Boost(u64)
+    Boost(u64),
+    // calculate tx fees based on its size and multiply by this value
+    // but never pay more than the max value
+    Capped {
+        multiplier: f64,
+        max: u64
+    }
 }
 
 impl Default for FeeBuilder {
@@ -19,6 +28,18 @@ impl Default for FeeBuilder {
     }
 }
 
+// Estimated fees of a transaction
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FeeEstimate {
+    // Asset against which the fee is charged
+    // Fees are always charged on the native asset
+    pub asset: Hash,
+    // Amount of fees, in energy units if the fee type is Energy
+    pub amount: u64,
+    // How the fee is paid
+    pub fee_type: FeeType
+}
+
 pub trait FeeHelper {
     type Error;
 
@@ -5,13 +5,17 @@ mod state;
 mod fee;
 mod unsigned;
 mod payload;
+mod schema;
 
 pub use state::AccountState;
-pub use fee::{FeeHelper, FeeBuilder};
-pub use unsigned::UnsignedTransaction;
+pub use fee::{FeeHelper, FeeBuilder, FeeEstimate};
+pub use unsigned::{UnsignedTransaction, MultiSigSigningError};
+pub use schema::describe_builder_schema;
 
 use indexmap::{IndexMap, IndexSet};
+use log::debug;
 use merlin::Transcript;
+use rand::rngs::OsRng;
 use bulletproofs::RangeProof;
 use curve25519_dalek::Scalar;
 use serde::{Deserialize, Serialize};
@@ -21,6 +25,8 @@ use std::{
     iter,
 };
 use crate::{
+    account::Nonce,
+    api::DataElement,
     config::{BURN_PER_CONTRACT, MAX_GAS_USAGE_PER_TX, TERMINOS_ASSET},
     crypto::{
         elgamal::{
@@ -28,6 +34,7 @@ use crate::{
             CompressedPublicKey,
             DecryptHandle,
             KeyPair,
+            OpeningProvider,
             PedersenCommitment,
             PedersenOpening,
             PublicKey,
@@ -42,12 +49,13 @@ use crate::{
             PC_GENS,
             BULLET_PROOF_SIZE,
         },
+        hash,
         Hash,
         ProtocolTranscript,
         HASH_SIZE,
         SIGNATURE_SIZE
     },
-    serializer::Serializer,
+    serializer::{Serializer, Writer},
     utils::{calculate_tx_fee, calculate_energy_fee}
 };
 use thiserror::Error;
@@ -176,9 +184,16 @@ impl TransactionBuilder {
         self
     }
 
+    /// Get the fee type used by this transaction
+    /// If none was set explicitly, TOS is used
+    pub fn get_fee_type(&self) -> FeeType {
+        self.fee_type.clone().unwrap_or(FeeType::TOS)
+    }
+
     /// Estimate by hand the bytes size of a final TX
     // Returns bytes size and transfers count
-    pub fn estimate_size(&self) -> usize {
+    // Contract modules are decoded to use their real serialized size
+    pub fn estimate_size<E>(&self) -> Result<usize, GenerationError<E>> {
         let assets_used = self.data.used_assets().len();
         // Version byte
         let mut size = 1
@@ -258,9 +273,12 @@ impl TransactionBuilder {
                 size += deposits_size;
             },
             TransactionTypeBuilder::DeployContract(payload) => {
-                // Module is in hex format, so we need to divide by 2 for its bytes size
+                // Module is in hex format, decode it to get its real bytes size
+                let module = Module::from_hex(&payload.module)
+                    .map_err(|_| GenerationError::InvalidModule)?;
+
                 // + 1 for the invoke option
-                size += payload.module.len() / 2 + 1;
+                size += module.size() + 1;
                 if let Some(invoke) = payload.invoke.as_ref() {
                     let (commitments, deposits_size) = self.estimate_deposits_size(&invoke.deposits);
 
@@ -305,7 +323,7 @@ impl TransactionBuilder {
         // G_vec len
         + 2 * RISTRETTO_COMPRESSED_SIZE * lg_n;
 
-        size
+        Ok(size)
     }
 
     fn estimate_deposits_size(&self, deposits: &IndexMap<Hash, ContractDepositBuilder>) -> (usize, usize) {
@@ -332,14 +350,37 @@ impl TransactionBuilder {
         (commitments_count, size)
     }
 
+    // Validate the TX data without building any proof
+    // This is also done when building the TX
+    pub fn validate<E>(&self) -> Result<(), GenerationError<E>> {
+        if let TransactionTypeBuilder::MultiSig(payload) = &self.data {
+            if payload.participants.len() > MAX_MULTISIG_PARTICIPANTS {
+                return Err(GenerationError::MultiSigParticipants);
+            }
+
+            if payload.threshold as usize > payload.participants.len() || (payload.threshold == 0 && !payload.participants.is_empty()) {
+                return Err(GenerationError::MultiSigThreshold);
+            }
+
+            // You can't contains yourself in the participants
+            if payload.participants.iter().any(|addr| *addr.get_public_key() == self.source) {
+                return Err(GenerationError::MultiSigSelfParticipant);
+            }
+        }
+
+        Ok(())
+    }
+
     // Estimate the fees for this TX
-    pub fn estimate_fees<B: FeeHelper>(&self, state: &mut B) -> Result<u64, GenerationError<B::Error>> {
+    // Fees are only charged against the native asset
+    pub fn estimate_fees<B: FeeHelper>(&self, state: &mut B) -> Result<FeeEstimate, GenerationError<B::Error>> {
+        let fee_type = self.get_fee_type();
         let calculated_fee = match self.fee_builder {
             // If the value is set, use it
             FeeBuilder::Value(value) => value,
             _ => {
                 // Compute the size and transfers count
-                let size = self.estimate_size();
+                let size = self.estimate_size()?;
                 let (transfers, new_addresses) = if let TransactionTypeBuilder::Transfers(transfers) = &self.data {
                     let mut new_addresses = 0;
                     for transfer in transfers {
@@ -358,7 +399,7 @@ impl TransactionBuilder {
                     if *fee_type == FeeType::Energy && matches!(self.data, TransactionTypeBuilder::Transfers(_)) {
                         // Use energy fee calculation for transfer transactions
                         let energy_fee = calculate_energy_fee(size, transfers, new_addresses);
-                        println!("[ESTIMATE DEBUG] Energy fee calculation: size={}, transfers={}, new_addresses={}, energy_fee={}", size, transfers, new_addresses, energy_fee);
+                        debug!("Energy fee calculation: size={}, transfers={}, new_addresses={}, energy_fee={}", size, transfers, new_addresses, energy_fee);
                         energy_fee
                     } else {
                         // Use regular TOS fee calculation
@@ -372,12 +413,23 @@ impl TransactionBuilder {
                 match self.fee_builder {
                     FeeBuilder::Multiplier(multiplier) => (expected_fee as f64 * multiplier) as u64,
                     FeeBuilder::Boost(boost) => expected_fee + boost,
+                    FeeBuilder::Capped { multiplier, max } => ((expected_fee as f64 * multiplier) as u64).min(max),
                     _ => expected_fee,
                 }
             },
         };
 
-        Ok(calculated_fee)
+        debug!("Estimated fees: {} with fee type {:?}", calculated_fee, fee_type);
+        Ok(FeeEstimate {
+            asset: TERMINOS_ASSET,
+            amount: calculated_fee,
+            fee_type,
+        })
+    }
+
+    // Estimate the fees for this TX and only return its amount
+    pub fn estimate_fees_value<B: FeeHelper>(&self, state: &mut B) -> Result<u64, GenerationError<B::Error>> {
+        self.estimate_fees(state).map(|estimate| estimate.amount)
     }
 
     // Compute the new source ciphertext
@@ -521,16 +573,98 @@ impl TransactionBuilder {
         cost
     }
 
+    /// Compute a fingerprint of the transaction intent for the given nonce
+    /// It covers the version, source, nonce and the plaintext payload
+    /// (destinations, assets, amounts, contract calls...)
+    /// Fees and extra data are excluded, and because it is computed before any proof is generated,
+    /// the same logical transaction rebuilt with fresh randomness has the same fingerprint
+    /// Wallets can use it to avoid submitting twice the same payment
+    /// NOTE: this is NOT the transaction hash and must never be used for consensus
+    pub fn intent_fingerprint(&self, nonce: Nonce) -> Hash {
+        let mut buffer = Vec::new();
+        let mut writer = Writer::new(&mut buffer);
+
+        self.version.write(&mut writer);
+        self.source.write(&mut writer);
+        nonce.write(&mut writer);
+        match &self.data {
+            TransactionTypeBuilder::Burn(payload) => {
+                writer.write_u8(0);
+                payload.write(&mut writer);
+            },
+            TransactionTypeBuilder::Transfers(transfers) => {
+                writer.write_u8(1);
+                writer.write_u8(transfers.len() as u8);
+                for transfer in transfers {
+                    transfer.asset.write(&mut writer);
+                    transfer.destination.get_public_key().write(&mut writer);
+                    writer.write_u64(&transfer.amount);
+                }
+            },
+            TransactionTypeBuilder::MultiSig(payload) => {
+                writer.write_u8(2);
+                writer.write_u8(payload.threshold);
+                writer.write_u8(payload.participants.len() as u8);
+                for participant in &payload.participants {
+                    participant.get_public_key().write(&mut writer);
+                }
+            },
+            TransactionTypeBuilder::InvokeContract(payload) => {
+                writer.write_u8(3);
+                payload.contract.write(&mut writer);
+                Self::write_deposits_intent(&payload.deposits, &mut writer);
+                writer.write_u16(payload.chunk_id);
+                writer.write_u64(&payload.max_gas);
+                writer.write_u8(payload.parameters.len() as u8);
+                for parameter in &payload.parameters {
+                    parameter.write(&mut writer);
+                }
+            },
+            TransactionTypeBuilder::DeployContract(payload) => {
+                writer.write_u8(4);
+                // The module hex may be above the string limit
+                hash(payload.module.as_bytes()).write(&mut writer);
+                if let Some(invoke) = &payload.invoke {
+                    writer.write_bool(true);
+                    writer.write_u64(&invoke.max_gas);
+                    Self::write_deposits_intent(&invoke.deposits, &mut writer);
+                } else {
+                    writer.write_bool(false);
+                }
+            },
+            TransactionTypeBuilder::Energy(payload) => {
+                writer.write_u8(5);
+                writer.write_u64(&payload.amount);
+                writer.write_bool(payload.is_freeze);
+                payload.freeze_duration.write(&mut writer);
+            }
+        };
+
+        hash(&buffer)
+    }
+
+    // Write the deposits for the intent fingerprint
+    fn write_deposits_intent(deposits: &IndexMap<Hash, ContractDepositBuilder>, writer: &mut Writer) {
+        writer.write_u8(deposits.len() as u8);
+        for (asset, deposit) in deposits {
+            asset.write(writer);
+            writer.write_u64(&deposit.amount);
+            writer.write_bool(deposit.private);
+        }
+    }
+
     // Build the deposits commitments for the contract
-    fn build_deposits_commitments<E>(
+    // Private deposits require the contract key to be known
+    fn build_deposits_commitments<E, P: OpeningProvider>(
         deposits: &IndexMap<Hash, ContractDepositBuilder>,
         public_key: &PublicKey,
         contract_key: &Option<PublicKey>,
+        provider: &mut P,
     ) -> Result<HashMap<Hash, DepositWithCommitment>, GenerationError<E>> {
         let mut deposits_commitments = HashMap::new();
         for (asset, deposit) in deposits.iter() {
             if deposit.private {
-                let amount_opening = PedersenOpening::generate_new();
+                let amount_opening = provider.generate_opening();
                 let commitment = PedersenCommitment::new_with_opening(deposit.amount, &amount_opening);
                 let sender_handle = public_key.decrypt_handle(&amount_opening);
                 let receiver_handle = contract_key
@@ -545,7 +679,6 @@ impl TransactionBuilder {
                     receiver_handle,
                     amount_opening,
                 });
-                todo!("support private deposits")
             } else {
                 if deposit.amount == 0 {
                     return Err(GenerationError::DepositZero);
@@ -622,13 +755,62 @@ impl TransactionBuilder {
         Ok(unsigned.finalize(source_keypair))
     }
 
+    // Build the TX and return the openings used for each transfer commitment
+    // Openings are sensitive: anyone knowing them can reveal the transfer amounts
+    // This must only be used when these openings need to be kept (ex: auditing)
+    pub fn build_with_openings<B: AccountState>(
+        self,
+        state: &mut B,
+        source_keypair: &KeyPair,
+    ) -> Result<BuildWithOpenings, GenerationError<B::Error>> where for<'a> <B as FeeHelper>::Error: std::convert::From<&'a str> {
+        let (unsigned, openings) = self.build_unsigned_internal(state, source_keypair, true, &mut OsRng)?;
+        Ok(BuildWithOpenings {
+            transaction: unsigned.finalize(source_keypair),
+            openings: openings.unwrap_or_default(),
+        })
+    }
+
     pub fn build_unsigned<B: AccountState>(
-        mut self,
+        self,
+        state: &mut B,
+        source_keypair: &KeyPair,
+    ) -> Result<UnsignedTransaction, GenerationError<B::Error>> where <B as FeeHelper>::Error: for<'a> std::convert::From<&'a str> {
+        self.build_unsigned_with_opening_provider(state, source_keypair, &mut OsRng)
+    }
+
+    // Build the unsigned TX using the given source for the commitments openings
+    // This is intended for deterministic tests only, production must use a secure RNG
+    // Note that the proofs are still generated using their own randomness
+    pub fn build_unsigned_with_opening_provider<B: AccountState, P: OpeningProvider>(
+        self,
         state: &mut B,
         source_keypair: &KeyPair,
+        provider: &mut P,
     ) -> Result<UnsignedTransaction, GenerationError<B::Error>> where <B as FeeHelper>::Error: for<'a> std::convert::From<&'a str> {
+        self.build_unsigned_internal(state, source_keypair, false, provider)
+            .map(|(unsigned, _)| unsigned)
+    }
+
+    // Build the unsigned TX
+    // If requested, the openings of the transfers commitments are returned
+    fn build_unsigned_internal<B: AccountState, P: OpeningProvider>(
+        mut self,
+        state: &mut B,
+        source_keypair: &KeyPair,
+        keep_openings: bool,
+        provider: &mut P,
+    ) -> Result<(UnsignedTransaction, Option<IndexMap<usize, TransferOpening>>), GenerationError<B::Error>> where <B as FeeHelper>::Error: for<'a> std::convert::From<&'a str> {
+        // Verify the TX data before any proof generation
+        self.validate()?;
+
+        // Only allow Energy for Transfer transactions
+        let fee_type = self.get_fee_type();
+        if fee_type.is_energy() && !matches!(self.data, TransactionTypeBuilder::Transfers(_)) {
+            return Err(GenerationError::State("Energy fee_type only allowed for Transfer transactions".into()));
+        }
+
         // Compute the fees
-        let fee = self.estimate_fees(state)?;
+        let fee = self.estimate_fees_value(state)?;
 
         // Get the nonce
         let nonce = state.get_nonce().map_err(GenerationError::State)?;
@@ -639,6 +821,7 @@ impl TransactionBuilder {
         // Data is mutable only to extract extra data
         let mut transfers_commitments = Vec::new();
         let mut deposits_commitments = HashMap::new();
+        let mut contract_key = None;
         match &mut self.data {
             TransactionTypeBuilder::Transfers(transfers) => {
                 if transfers.len() == 0 {
@@ -691,7 +874,7 @@ impl TransactionBuilder {
                             .decompress()
                             .map_err(|err| GenerationError::Proof(err.into()))?;
     
-                        let amount_opening = PedersenOpening::generate_new();
+                        let amount_opening = provider.generate_opening();
                         let commitment =
                             PedersenCommitment::new_with_opening(transfer.amount, &amount_opening);
                         let sender_handle =
@@ -714,10 +897,12 @@ impl TransactionBuilder {
                     return Err(GenerationError::MaxGasReached.into())
                 }
 
-                deposits_commitments = Self::build_deposits_commitments::<B::Error>(
+                contract_key = Some(PublicKey::from_hash(&payload.contract));
+                deposits_commitments = Self::build_deposits_commitments::<B::Error, _>(
                     &payload.deposits,
                     source_keypair.get_public_key(),
-                    &None
+                    &contract_key,
+                    provider
                 )?;
             },
             TransactionTypeBuilder::DeployContract(payload) => {
@@ -726,10 +911,13 @@ impl TransactionBuilder {
                         return Err(GenerationError::MaxGasReached.into())
                     }
 
-                    deposits_commitments = Self::build_deposits_commitments::<B::Error>(
+                    // The contract key is derived from the TX hash
+                    // which is not known yet, so no private deposits are possible
+                    deposits_commitments = Self::build_deposits_commitments::<B::Error, _>(
                         &invoke.deposits,
                         source_keypair.get_public_key(),
-                        &None
+                        &None,
+                        provider
                     )?;
                 }
             },
@@ -745,7 +933,7 @@ impl TransactionBuilder {
         let used_assets = self.data.used_assets();
 
         let mut range_proof_openings: Vec<_> =
-            iter::repeat_with(|| PedersenOpening::generate_new().as_scalar())
+            iter::repeat_with(|| provider.generate_opening().as_scalar())
                 .take(used_assets.len())
                 .collect();
 
@@ -768,14 +956,6 @@ impl TransactionBuilder {
             })
             .collect::<Result<Vec<_>, GenerationError<B::Error>>>()?;
 
-        // Determine fee type: use explicit fee_type if set, otherwise use default logic
-        let fee_type = if let Some(ref explicit_fee_type) = self.fee_type {
-            explicit_fee_type.clone()
-        } else {
-            // Default logic: use TOS for all transactions
-            FeeType::TOS
-        };
-
         // Prepare the transcript used for proofs
         let mut transcript = Transaction::prepare_transcript(self.version, &self.source, fee, &fee_type, nonce);
 
@@ -830,6 +1010,11 @@ impl TransactionBuilder {
 
         let mut transfers = Vec::new();
         let mut deposits = IndexMap::new();
+        let mut openings = if keep_openings {
+            Some(IndexMap::new())
+        } else {
+            None
+        };
         match &mut self.data {
             TransactionTypeBuilder::Transfers(_) => {
                 range_proof_values.reserve(transfers_commitments.len());
@@ -838,7 +1023,8 @@ impl TransactionBuilder {
                 let mut total_cipher_size = 0;
                 transfers = transfers_commitments
                     .into_iter()
-                    .map(|transfer| {
+                    .enumerate()
+                    .map(|(index, transfer)| {
                         let commitment = transfer.commitment.compress();
                         let sender_handle = transfer.sender_handle.compress();
                         let receiver_handle = transfer.receiver_handle.compress();
@@ -865,6 +1051,13 @@ impl TransactionBuilder {
     
                         range_proof_values.push(transfer.inner.amount);
                         range_proof_openings.push(transfer.amount_opening.as_scalar());
+
+                        if let Some(openings) = openings.as_mut() {
+                            openings.insert(index, TransferOpening {
+                                amount: transfer.inner.amount,
+                                opening: transfer.amount_opening.clone(),
+                            });
+                        }
     
                         // Encrypt the extra data if it exists
                         let extra_data = if let Some(extra_data) = transfer.inner.extra_data {
@@ -924,7 +1117,7 @@ impl TransactionBuilder {
                     &mut payload.deposits,
                     deposits_commitments,
                     source_keypair,
-                    &None
+                    &contract_key
                 );
             },
             TransactionTypeBuilder::DeployContract(payload) => {
@@ -972,14 +1165,6 @@ impl TransactionBuilder {
                 TransactionType::Burn(payload.clone())
             },
             TransactionTypeBuilder::MultiSig(ref payload) => {
-                if payload.participants.len() > MAX_MULTISIG_PARTICIPANTS {
-                    return Err(GenerationError::MultiSigParticipants);
-                }
-
-                if payload.threshold as usize > payload.participants.len() || (payload.threshold == 0 && !payload.participants.is_empty()) {
-                    return Err(GenerationError::MultiSigThreshold);
-                }
-
                 transcript.multisig_proof_domain_separator();
                 transcript.append_u64(b"multisig_threshold", payload.threshold as u64);
 
@@ -990,11 +1175,6 @@ impl TransactionBuilder {
                     keys.insert(key);
                 }
 
-                // You can't contains yourself in the participants
-                if keys.contains(&self.source) {
-                    return Err(GenerationError::MultiSigSelfParticipant);
-                }
-
                 TransactionType::MultiSig(MultiSigPayload {
                     participants: keys,
                     threshold: payload.threshold,
@@ -1079,21 +1259,6 @@ impl TransactionBuilder {
         )
         .map_err(ProofGenerationError::from)?;
 
-        // Determine fee type: use explicit fee_type if set, otherwise use default logic
-        let fee_type = if let Some(ref explicit_fee_type) = self.fee_type {
-            // Only allow Energy for Transfer transactions
-            if *explicit_fee_type == super::FeeType::Energy {
-                match &self.data {
-                    TransactionTypeBuilder::Transfers(_) => {},
-                    _ => return Err(GenerationError::State("Energy fee_type only allowed for Transfer transactions".into())),
-                }
-            }
-            explicit_fee_type.clone()
-        } else {
-            // Default: TOS
-            super::FeeType::TOS
-        };
-
         let transaction = UnsignedTransaction::new_with_fee_type(
             self.version,
             self.source,
@@ -1106,10 +1271,26 @@ impl TransactionBuilder {
             range_proof,
         );
 
-        Ok(transaction)
+        Ok((transaction, openings))
     }
 }
 
+// Opening of a transfer commitment
+// This is sensitive: anyone knowing it can reveal the transfer amount
+#[derive(Clone, Debug)]
+pub struct TransferOpening {
+    pub amount: u64,
+    pub opening: PedersenOpening,
+}
+
+// Result of a build keeping the transfers openings
+// Openings are indexed by the transfer position in the TX
+#[derive(Debug)]
+pub struct BuildWithOpenings {
+    pub transaction: Transaction,
+    pub openings: IndexMap<usize, TransferOpening>,
+}
+
 // Internal struct for build
 struct TransferWithCommitment {
     inner: TransferBuilder,
@@ -1151,7 +1332,58 @@ impl DepositWithCommitment {
     }
 }
 
+// Split the transfers into batches that can each be built as a TX
+// Each batch respects the maximum transfers count and the extra data size limit
+// A transfer with too large extra data is still put alone in its own batch
+pub fn split_transfers(transfers: Vec<TransferBuilder>) -> Vec<Vec<TransferBuilder>> {
+    let mut batches = Vec::new();
+    let mut current = Vec::new();
+    let mut current_extra_data_size = 0;
+    for transfer in transfers {
+        let extra_data_size = transfer.extra_data.as_ref()
+            .or(transfer.destination.get_extra_data())
+            .map_or(0, |extra_data| ExtraDataType::estimate_size(extra_data, transfer.encrypt_extra_data));
+
+        if !current.is_empty() && (current.len() >= MAX_TRANSFER_COUNT || current_extra_data_size + extra_data_size > EXTRA_DATA_LIMIT_SUM_SIZE) {
+            batches.push(current);
+            current = Vec::new();
+            current_extra_data_size = 0;
+        }
+
+        current_extra_data_size += extra_data_size;
+        current.push(transfer);
+    }
+
+    if !current.is_empty() {
+        batches.push(current);
+    }
+
+    batches
+}
+
 impl TransactionTypeBuilder {
+    // Create a transfers type with the same extra data for each transfer
+    // Extra data limits are verified here to fail early before any proof generation
+    // The size is estimated in its final form, as encryption makes it bigger
+    pub fn transfers_with_common_extra_data<E>(mut transfers: Vec<TransferBuilder>, extra_data: DataElement, encrypt: bool) -> Result<Self, GenerationError<E>> {
+        let size = ExtraDataType::estimate_size(&extra_data, encrypt);
+        if size > EXTRA_DATA_LIMIT_SIZE || size * transfers.len() > EXTRA_DATA_LIMIT_SUM_SIZE {
+            return Err(GenerationError::ExtraDataTooLarge);
+        }
+
+        for transfer in transfers.iter_mut() {
+            // Integrated addresses already have their own extra data
+            if !transfer.destination.is_normal() {
+                return Err(GenerationError::ExtraDataAndIntegratedAddress);
+            }
+
+            transfer.extra_data = Some(extra_data.clone());
+            transfer.encrypt_extra_data = encrypt;
+        }
+
+        Ok(Self::Transfers(transfers))
+    }
+
     // Get the assets used in the transaction
     pub fn used_assets<'a>(&'a self) -> HashSet<&'a Hash> {
         let mut consumed = HashSet::new();
@@ -2,7 +2,7 @@ use indexmap::{IndexMap, IndexSet};
 use serde::{Deserialize, Serialize};
 use terminos_vm::ValueCell;
 use crate::{
-    api::DataElement,
+    api::{daemon::FreezeRecordInfo, DataElement},
     crypto::{Address, Hash},
     account::FreezeDuration,
 };
@@ -76,6 +76,15 @@ pub struct EnergyBuilder {
     pub freeze_duration: Option<FreezeDuration>,
 }
 
+/// Preview of an unfreeze operation based on the current freeze records
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UnfreezePreview {
+    /// Total amount of TOS that can be unfrozen now
+    pub unlockable_amount: u64,
+    /// Energy that would be removed by this unfreeze operation
+    pub energy_removed: u64,
+}
+
 impl EnergyBuilder {
     /// Create a new freeze TOS builder with specified duration
     pub fn freeze_tos(amount: u64, duration: FreezeDuration) -> Self {
@@ -111,6 +120,54 @@ impl EnergyBuilder {
         }
     }
 
+    /// Preview the unfreeze operation against the freeze records of the account
+    /// This follows the same rules as the unfreeze applied on chain:
+    /// only whole TOS are unfrozen, from the unlocked records in order
+    pub fn preview_unfreeze(&self, records: &[FreezeRecordInfo]) -> Result<UnfreezePreview, &'static str> {
+        if self.is_freeze {
+            return Err("Preview is only available for unfreeze operations");
+        }
+
+        self.validate()?;
+
+        let mut remaining = (self.amount / crate::config::COIN_VALUE) * crate::config::COIN_VALUE;
+        if remaining == 0 {
+            return Err("Cannot unfreeze 0 TOS");
+        }
+
+        let mut unlockable_amount = 0;
+        let mut energy_removed = 0;
+        for record in records.iter().filter(|record| record.can_unlock && record.remaining_blocks == 0) {
+            unlockable_amount += record.amount;
+            if remaining == 0 {
+                continue;
+            }
+
+            let duration = Self::parse_record_duration(&record.duration)?;
+            let amount = remaining.min(record.amount);
+            energy_removed += (amount / crate::config::COIN_VALUE) * duration.reward_multiplier();
+            remaining -= amount;
+        }
+
+        if remaining > 0 {
+            return Err("Insufficient unlocked TOS to unfreeze");
+        }
+
+        Ok(UnfreezePreview {
+            unlockable_amount,
+            energy_removed,
+        })
+    }
+
+    // Parse the duration of a freeze record (ex: "7_days")
+    fn parse_record_duration(duration: &str) -> Result<FreezeDuration, &'static str> {
+        let days = duration.strip_suffix("_days")
+            .and_then(|days| days.parse().ok())
+            .ok_or("Invalid freeze record duration")?;
+
+        FreezeDuration::new(days)
+    }
+
     /// Validate the builder configuration
     pub fn validate(&self) -> Result<(), &'static str> {
         if self.amount == 0 {
@@ -129,6 +186,27 @@ impl EnergyBuilder {
 
         Ok(())
     }
+
+    /// Validate the builder configuration against the current frozen state of the account
+    /// If a maximum total frozen TOS is set, a freeze operation that
+    /// would exceed it (including the current freeze records) is rejected
+    pub fn validate_with_frozen_state(&self, records: &[FreezeRecordInfo], max_total_frozen: Option<u64>) -> Result<(), &'static str> {
+        self.validate()?;
+
+        if let Some(max_total_frozen) = max_total_frozen.filter(|_| self.is_freeze) {
+            // Only whole TOS are frozen
+            let amount = (self.amount / crate::config::COIN_VALUE) * crate::config::COIN_VALUE;
+            let total_frozen = records.iter()
+                .try_fold(amount, |total, record| total.checked_add(record.amount))
+                .ok_or("Total frozen TOS overflow")?;
+
+            if total_frozen > max_total_frozen {
+                return Err("Freeze would exceed the maximum total frozen TOS");
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -184,6 +262,59 @@ mod tests {
         assert!(builder.validate().is_err());
     }
 
+    #[test]
+    fn test_energy_builder_validate_with_frozen_state() {
+        let record = |amount: u64| FreezeRecordInfo {
+            amount,
+            duration: "7_days".to_string(),
+            freeze_topoheight: 0,
+            unlock_topoheight: 100,
+            energy_gained: (amount / COIN_VALUE) * 14,
+            can_unlock: false,
+            remaining_blocks: 100,
+        };
+        let records = [record(2 * COIN_VALUE), record(3 * COIN_VALUE)];
+        let duration = FreezeDuration::new(7).unwrap();
+
+        // No maximum configured
+        assert!(EnergyBuilder::freeze_tos(100 * COIN_VALUE, duration).validate_with_frozen_state(&records, None).is_ok());
+        // Exactly at the maximum
+        assert!(EnergyBuilder::freeze_tos(5 * COIN_VALUE, duration).validate_with_frozen_state(&records, Some(10 * COIN_VALUE)).is_ok());
+        // Above the maximum
+        assert!(EnergyBuilder::freeze_tos(6 * COIN_VALUE, duration).validate_with_frozen_state(&records, Some(10 * COIN_VALUE)).is_err());
+        // Unfreeze operations are never capped
+        assert!(EnergyBuilder::unfreeze_tos(6 * COIN_VALUE).validate_with_frozen_state(&records, Some(COIN_VALUE)).is_ok());
+        // Base validation still applies
+        assert!(EnergyBuilder::freeze_tos(0, duration).validate_with_frozen_state(&records, None).is_err());
+    }
+
+    #[test]
+    fn test_energy_builder_preview_unfreeze() {
+        let record = |amount: u64, days: u32, remaining_blocks: u64| FreezeRecordInfo {
+            amount,
+            duration: format!("{}_days", days),
+            freeze_topoheight: 0,
+            unlock_topoheight: remaining_blocks,
+            energy_gained: (amount / COIN_VALUE) * (days as u64 * 2),
+            can_unlock: remaining_blocks == 0,
+            remaining_blocks,
+        };
+        let records = [record(2 * COIN_VALUE, 3, 0), record(COIN_VALUE, 7, 100), record(3 * COIN_VALUE, 14, 0)];
+
+        let preview = EnergyBuilder::unfreeze_tos(4 * COIN_VALUE).preview_unfreeze(&records).unwrap();
+        assert_eq!(preview, UnfreezePreview {
+            unlockable_amount: 5 * COIN_VALUE,
+            energy_removed: 2 * 6 + 2 * 28,
+        });
+
+        // Locked record can't be used
+        assert!(EnergyBuilder::unfreeze_tos(6 * COIN_VALUE).preview_unfreeze(&records).is_err());
+        // Less than a whole TOS
+        assert!(EnergyBuilder::unfreeze_tos(COIN_VALUE / 2).preview_unfreeze(&records).is_err());
+        // Freeze operations can't be previewed
+        assert!(EnergyBuilder::freeze_tos(COIN_VALUE, FreezeDuration::new(3).unwrap()).preview_unfreeze(&records).is_err());
+    }
+
     #[test]
     fn test_different_duration_rewards() {
         let amounts = [100000000, 200000000, 300000000]; // 1, 2, 3 TOS
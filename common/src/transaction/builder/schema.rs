@@ -0,0 +1,242 @@
+use serde_json::{json, Map, Value};
+
+// Describe a field of a builder
+// Optional fields can be omitted from the JSON
+fn field(kind: &str, optional: bool) -> Value {
+    json!({
+        "type": kind,
+        "optional": optional
+    })
+}
+
+// Describe a struct based on its fields
+fn object(fields: &[(&str, Value)]) -> Value {
+    let fields = fields.iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect::<Map<_, _>>();
+
+    json!({
+        "type": "object",
+        "fields": fields
+    })
+}
+
+// Describe the JSON format expected by the transaction builder
+// It follows the serde definitions of the builders:
+// - `transaction_type` lists the variants of `TransactionTypeBuilder` by their tag
+// - `definitions` describes each struct referenced by a type name
+//
+// Primitive types used are: `hash` and `hex` (hexadecimal strings), `address`,
+// `bool`, `u8`, `u16`, `u32`, `u64`, `f64`, `data_element` and `value_cell`
+// Collections are described as `array<T>` and `map<K, V>`
+pub fn describe_builder_schema() -> Value {
+    json!({
+        "transaction_type": {
+            "type": "enum",
+            "variants": {
+                "transfers": field("array<transfer>", false),
+                "burn": field("burn", false),
+                "multi_sig": field("multi_sig", false),
+                "invoke_contract": field("invoke_contract", false),
+                "deploy_contract": field("deploy_contract", false),
+                "energy": field("energy", false)
+            }
+        },
+        "fee_builder": {
+            "type": "enum",
+            "variants": {
+                "multiplier": field("f64", false),
+                "value": field("u64", false),
+                "boost": field("u64", false),
+                "capped": object(&[
+                    ("multiplier", field("f64", false)),
+                    ("max", field("u64", false))
+                ])
+            }
+        },
+        "definitions": {
+            "transfer": object(&[
+                ("asset", field("hash", false)),
+                ("amount", field("u64", false)),
+                ("destination", field("address", false)),
+                ("extra_data", field("data_element", true)),
+                ("encrypt_extra_data", field("bool", true))
+            ]),
+            "burn": object(&[
+                ("asset", field("hash", false)),
+                ("amount", field("u64", false))
+            ]),
+            "multi_sig": object(&[
+                ("participants", field("array<address>", false)),
+                ("threshold", field("u8", false))
+            ]),
+            "contract_deposit": object(&[
+                ("amount", field("u64", false)),
+                ("private", field("bool", true))
+            ]),
+            "invoke_contract": object(&[
+                ("contract", field("hash", false)),
+                ("max_gas", field("u64", false)),
+                ("chunk_id", field("u16", false)),
+                ("parameters", field("array<value_cell>", false)),
+                ("deposits", field("map<hash, contract_deposit>", true))
+            ]),
+            "deploy_contract": object(&[
+                ("module", field("hex", false)),
+                ("invoke", field("deploy_contract_invoke", true))
+            ]),
+            "deploy_contract_invoke": object(&[
+                ("max_gas", field("u64", false)),
+                ("deposits", field("map<hash, contract_deposit>", true))
+            ]),
+            "energy": object(&[
+                ("amount", field("u64", false)),
+                ("is_freeze", field("bool", false)),
+                ("freeze_duration", field("freeze_duration", true))
+            ]),
+            "freeze_duration": object(&[
+                ("days", field("u32", false))
+            ])
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::{IndexMap, IndexSet};
+    use terminos_vm::{Primitive, ValueCell};
+    use crate::{
+        account::FreezeDuration,
+        api::{DataElement, DataValue},
+        crypto::{Hash, KeyPair},
+        transaction::BurnPayload
+    };
+    use super::super::{
+        ContractDepositBuilder,
+        DeployContractBuilder,
+        DeployContractInvokeBuilder,
+        EnergyBuilder,
+        InvokeContractBuilder,
+        MultiSigBuilder,
+        TransactionTypeBuilder,
+        TransferBuilder
+    };
+    use super::*;
+
+    // Check a serialized value against a type of the schema
+    fn check_value(schema: &Value, kind: &str, value: &Value, path: &str) {
+        if let Some(inner) = kind.strip_prefix("array<").and_then(|v| v.strip_suffix('>')) {
+            let values = value.as_array().unwrap_or_else(|| panic!("{} should be an array", path));
+            for (i, value) in values.iter().enumerate() {
+                check_value(schema, inner, value, &format!("{}[{}]", path, i));
+            }
+            return;
+        }
+
+        if let Some(inner) = kind.strip_prefix("map<").and_then(|v| v.strip_suffix('>')) {
+            let (_, value_kind) = inner.split_once(", ").unwrap();
+            let values = value.as_object().unwrap_or_else(|| panic!("{} should be a map", path));
+            for (key, value) in values {
+                check_value(schema, value_kind, value, &format!("{}.{}", path, key));
+            }
+            return;
+        }
+
+        let valid = match kind {
+            "hash" | "hex" | "address" => value.is_string(),
+            "bool" => value.is_boolean(),
+            "u8" | "u16" | "u32" | "u64" => value.is_u64(),
+            "f64" => value.is_number(),
+            "data_element" | "value_cell" => !value.is_null(),
+            _ => {
+                let definition = schema["definitions"][kind]["fields"].as_object()
+                    .unwrap_or_else(|| panic!("missing definition {} for {}", kind, path));
+                let fields = value.as_object().unwrap_or_else(|| panic!("{} should be an object", path));
+
+                // Every serialized field must be described
+                for name in fields.keys() {
+                    assert!(definition.contains_key(name), "missing field {} for {}", name, kind);
+                }
+
+                for (name, field) in definition {
+                    let path = format!("{}.{}", path, name);
+                    match fields.get(name).filter(|v| !v.is_null()) {
+                        Some(value) => check_value(schema, field["type"].as_str().unwrap(), value, &path),
+                        None => assert!(field["optional"].as_bool().unwrap(), "{} is not optional", path)
+                    }
+                }
+
+                true
+            }
+        };
+
+        assert!(valid, "{} is not a valid {}", path, kind);
+    }
+
+    #[test]
+    fn test_schema_variants_match_serde() {
+        let schema = describe_builder_schema();
+        let variants = schema["transaction_type"]["variants"].as_object().unwrap();
+
+        let destination = KeyPair::new().get_public_key().to_address(false);
+        let mut deposits = IndexMap::new();
+        deposits.insert(Hash::zero(), ContractDepositBuilder { amount: 10, private: true });
+
+        let builders = [
+            TransactionTypeBuilder::Transfers(vec![
+                TransferBuilder {
+                    asset: Hash::zero(),
+                    amount: 1,
+                    destination: destination.clone(),
+                    extra_data: Some(DataElement::Value(DataValue::String("hello".to_owned()))),
+                    encrypt_extra_data: false
+                },
+                TransferBuilder {
+                    asset: Hash::zero(),
+                    amount: 2,
+                    destination: destination.clone(),
+                    extra_data: None,
+                    encrypt_extra_data: true
+                }
+            ]),
+            TransactionTypeBuilder::Burn(BurnPayload { asset: Hash::zero(), amount: 1 }),
+            TransactionTypeBuilder::MultiSig(MultiSigBuilder { participants: IndexSet::from([destination]), threshold: 1 }),
+            TransactionTypeBuilder::InvokeContract(InvokeContractBuilder {
+                contract: Hash::zero(),
+                max_gas: 1000,
+                chunk_id: 0,
+                parameters: vec![ValueCell::Default(Primitive::U64(42))],
+                deposits: deposits.clone()
+            }),
+            TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+                module: "00".to_owned(),
+                invoke: None
+            }),
+            TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+                module: "00".to_owned(),
+                invoke: Some(DeployContractInvokeBuilder { max_gas: 1000, deposits })
+            }),
+            TransactionTypeBuilder::Energy(EnergyBuilder::freeze_tos(1, FreezeDuration::new(3).unwrap())),
+            TransactionTypeBuilder::Energy(EnergyBuilder::unfreeze_tos(1)),
+        ];
+
+        let mut covered = IndexSet::new();
+        for builder in builders {
+            let value = serde_json::to_value(&builder).unwrap();
+            let (tag, inner) = value.as_object().unwrap().iter().next().unwrap();
+            let variant = variants.get(tag).unwrap_or_else(|| panic!("missing variant {}", tag));
+            check_value(&schema, variant["type"].as_str().unwrap(), inner, tag);
+
+            // The described JSON must be accepted back by the builder
+            let builder: TransactionTypeBuilder = serde_json::from_value(value.clone()).unwrap();
+            assert_eq!(serde_json::to_value(&builder).unwrap(), value);
+
+            covered.insert(tag.clone());
+        }
+
+        // Every variant of the schema must be covered above
+        for tag in variants.keys() {
+            assert!(covered.contains(tag), "variant {} is not tested", tag);
+        }
+    }
+}
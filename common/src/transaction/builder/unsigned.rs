@@ -1,5 +1,6 @@
 use bulletproofs::RangeProof;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use crate::{
     account::Nonce,
     crypto::{
@@ -17,6 +18,7 @@ use crate::{
     transaction::{
         multisig::{MultiSig, SignatureId},
         FeeType,
+        MultiSigPayload,
         Reference,
         SourceCommitment,
         Transaction,
@@ -25,6 +27,22 @@ use crate::{
     }
 };
 
+#[derive(Error, Debug, Clone)]
+pub enum MultiSigSigningError {
+    #[error("Unknown multisig participant with id {0}")]
+    UnknownParticipant(u8),
+    #[error("Invalid public key for multisig participant {0}")]
+    InvalidParticipantKey(u8),
+    #[error("Invalid signature for multisig participant {0}")]
+    InvalidSignature(u8),
+    #[error("Multisig participant {0} has already signed")]
+    AlreadySigned(u8),
+    #[error("Multisig threshold of {0} signatures already reached")]
+    ThresholdReached(u8),
+    #[error("Not enough multisig signatures, got {0} but {1} are required")]
+    NotEnoughSignatures(usize, u8),
+}
+
 // Used to build the final transaction
 // It can include the multi-signature logic
 // by signing it
@@ -112,6 +130,45 @@ impl UnsignedTransaction {
         multisig.add_signature(SignatureId { id, signature });
     }
 
+    /// Add a signature from a multisig participant
+    /// The signature is verified against the participants of the multisig configuration
+    /// No more signatures than the threshold can be added
+    pub fn add_multisig_signature(&mut self, config: &MultiSigPayload, signature: SignatureId) -> Result<(), MultiSigSigningError> {
+        let id = signature.id;
+        let key = config.participants.get_index(id as usize)
+            .ok_or(MultiSigSigningError::UnknownParticipant(id))?
+            .decompress()
+            .map_err(|_| MultiSigSigningError::InvalidParticipantKey(id))?;
+
+        let hash = self.get_hash_for_multisig();
+        if !signature.signature.verify(hash.as_bytes(), &key) {
+            return Err(MultiSigSigningError::InvalidSignature(id));
+        }
+
+        let multisig = self.multisig.get_or_insert_with(MultiSig::new);
+        if multisig.get_signatures().contains(&signature) {
+            return Err(MultiSigSigningError::AlreadySigned(id));
+        }
+
+        if multisig.len() >= config.threshold as usize {
+            return Err(MultiSigSigningError::ThresholdReached(config.threshold));
+        }
+
+        multisig.add_signature(signature);
+
+        Ok(())
+    }
+
+    /// Finalize the transaction only if the multisig threshold is reached
+    pub fn finalize_multisig(self, config: &MultiSigPayload, keypair: &KeyPair) -> Result<Transaction, MultiSigSigningError> {
+        let count = self.multisig.as_ref().map_or(0, |multisig| multisig.len());
+        if count < config.threshold as usize {
+            return Err(MultiSigSigningError::NotEnoughSignatures(count, config.threshold));
+        }
+
+        Ok(self.finalize(keypair))
+    }
+
     // Get the bytes that need to be signed for the multi-signature
     fn write_no_signature(&self, writer: &mut Writer) {
         self.version.write(writer);
@@ -131,13 +188,19 @@ impl UnsignedTransaction {
         self.reference.write(writer);
     }
 
-    // Get the hash of the transaction for the multi-signature
-    // This hash must be signed by each participant of the multisig
-    pub fn get_hash_for_multisig(&self) -> Hash {
+    // Get the bytes of the transaction for the multi-signature
+    // Participants sign the hash of these bytes
+    pub fn get_multisig_signing_bytes(&self) -> Vec<u8> {
         let mut buffer = Vec::new();
         let mut writer = Writer::new(&mut buffer);
         self.write_no_signature(&mut writer);
-        hash(&buffer)
+        buffer
+    }
+
+    // Get the hash of the transaction for the multi-signature
+    // This hash must be signed by each participant of the multisig
+    pub fn get_hash_for_multisig(&self) -> Hash {
+        hash(&self.get_multisig_signing_bytes())
     }
 
     // Finalize the transaction by signing it
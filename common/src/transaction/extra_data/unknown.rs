@@ -19,6 +19,7 @@ use super::{
     Cipher,
     ExtraData,
     ExtraDataType,
+    PlaintextData,
     PlaintextExtraData,
     SharedKey
 };
@@ -32,12 +33,17 @@ pub struct UnknownExtraDataFormat(pub Vec<u8>);
 impl UnknownExtraDataFormat {
     // Decrypt the encrypted data using the shared key
     pub fn decrypt_with_shared_key(&self, shared_key: &SharedKey) -> Result<DataElement, Error> {
-        let e = ExtraData::from_bytes(&self.0)?;
-        let plaintext = e.decrypt_with_shared_key(shared_key)?;
+        let plaintext = self.decrypt_plaintext_with_shared_key(shared_key)?;
         let data = DataElement::from_bytes(&plaintext.0)?;
         Ok(data)
     }
 
+    // Decrypt the encrypted data using the shared key without decoding it
+    pub fn decrypt_plaintext_with_shared_key(&self, shared_key: &SharedKey) -> Result<PlaintextData, Error> {
+        let e = ExtraData::from_bytes(&self.0)?;
+        Ok(e.decrypt_with_shared_key(shared_key)?)
+    }
+
     // Decrypt from the versioned extra data format
     fn decrypt_typed(&self, private_key: &PrivateKey, role: Role) -> Result<PlaintextExtraData, Error> {
         let typed = ExtraDataType::from_bytes(&self.0)?;
@@ -1,7 +1,9 @@
 use std::{borrow::Cow, collections::HashMap, sync::Arc};
 use async_trait::async_trait;
 use curve25519_dalek::Scalar;
+use rand::{rngs::StdRng, SeedableRng};
 use indexmap::IndexSet;
+use merlin::Transcript;
 use terminos_vm::{Chunk, Environment, Module};
 use crate::{
     account::{CiphertextCache, Nonce},
@@ -9,12 +11,13 @@ use crate::{
     block::BlockVersion,
     config::{BURN_PER_CONTRACT, COIN_VALUE, TERMINOS_ASSET},
     crypto::{
-        elgamal::{Ciphertext, PedersenOpening},
+        elgamal::{Ciphertext, PedersenCommitment, PedersenOpening},
         proofs::{G, ProofVerificationError},
         Address,
         Hash,
         Hashable,
         KeyPair,
+        ProtocolTranscript,
         PublicKey,
     },
     serializer::Serializer,
@@ -29,15 +32,20 @@ use crate::{
             MultiSigBuilder,
             ContractDepositBuilder,
             DeployContractBuilder,
+            DeployContractInvokeBuilder,
             InvokeContractBuilder,
             GenerationError,
+            MultiSigSigningError,
+            split_transfers,
         },
         extra_data::{
             derive_shared_key_from_opening,
             PlaintextData,
         },
-        verify::{ZKPCache, NoZKPCache, VerificationError, BlockchainVerificationState},
+        multisig::SignatureId,
+        verify::{verify_burn, ZKPCache, NoZKPCache, VerificationError, BlockchainVerificationState},
         MAX_TRANSFER_COUNT,
+        EXTRA_DATA_LIMIT_SUM_SIZE,
         Transaction,
         BurnPayload,
         Reference,
@@ -147,7 +155,7 @@ fn create_tx_for(account: Account, destination: Address, amount: u64, extra_data
     }]);
 
     let builder = TransactionBuilder::new(TxVersion::T0, account.keypair.get_public_key().compress(), None, data, FeeBuilder::default()); // Use T0 for all operations
-    let estimated_size = builder.estimate_size();
+    let estimated_size = builder.estimate_size::<()>().unwrap();
     let tx = builder.build(&mut state, &account.keypair).unwrap();
     let actual_size = tx.size();
     let to_bytes_size = tx.to_bytes().len();
@@ -269,6 +277,68 @@ async fn test_tx_verify() {
 }
 
 
+// Compare the existing batched range proofs verification of Transaction::verify_batch
+// against one verification per TX on a block of 10 transfers
+#[tokio::test]
+async fn test_batched_range_proofs_verification() {
+    let mut bob = Account::new();
+    bob.set_balance(TERMINOS_ASSET, 0);
+
+    let mut state = ChainState::new();
+    let mut txs = Vec::new();
+    for _ in 0..10 {
+        let mut alice = Account::new();
+        alice.set_balance(TERMINOS_ASSET, 100 * COIN_VALUE);
+
+        let tx = create_tx_for(alice.clone(), bob.address(), 50, None);
+        let hash = tx.hash();
+        txs.push((tx, hash));
+
+        let mut balances = HashMap::new();
+        for (asset, balance) in &alice.balances {
+            balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+        }
+        state.accounts.insert(alice.keypair.get_public_key().compress(), AccountChainState {
+            balances,
+            nonce: alice.nonce,
+        });
+    }
+
+    {
+        let mut balances = HashMap::new();
+        for (asset, balance) in &bob.balances {
+            balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+        }
+        state.accounts.insert(bob.keypair.get_public_key().compress(), AccountChainState {
+            balances,
+            nonce: bob.nonce,
+        });
+    }
+
+    // Sequential verification: one range proof verified per TX
+    let mut sequential_state = state.clone();
+    for (tx, hash) in &txs {
+        tx.verify(hash, &mut sequential_state, &NoZKPCache).await.unwrap();
+    }
+
+    // Batched verification: all the range proofs are verified at once
+    let mut batched_state = state.clone();
+    Transaction::verify_batch(txs.iter(), &mut batched_state, &NoZKPCache).await.unwrap();
+
+    let bob_key = bob.keypair.get_public_key().compress();
+    let expected = Scalar::from(10 * 50u64) * (*G);
+    assert_eq!(bob.keypair.decrypt_to_point(&sequential_state.accounts[&bob_key].balances[&TERMINOS_ASSET]), expected);
+    assert_eq!(bob.keypair.decrypt_to_point(&batched_state.accounts[&bob_key].balances[&TERMINOS_ASSET]), expected);
+
+    // A single invalid range proof must invalidate the whole batch
+    let mut invalid = txs[0].0.as_ref().clone();
+    invalid.range_proof = txs[1].0.range_proof.clone();
+    txs[0].0 = Arc::new(invalid);
+
+    let mut batched_state = state.clone();
+    assert!(Transaction::verify_batch(txs.iter(), &mut batched_state, &NoZKPCache).await.is_err());
+}
+
 #[tokio::test]
 async fn test_tx_verify_with_zkp_cache() {
     let mut alice = Account::new();
@@ -353,7 +423,7 @@ async fn test_burn_tx_verify() {
             asset: TERMINOS_ASSET,
         });
         let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
-        let estimated_size = builder.estimate_size();
+        let estimated_size = builder.estimate_size::<()>().unwrap();
         let tx = builder.build(&mut state, &alice.keypair).unwrap();
         assert!(estimated_size == tx.size());
         assert!(tx.to_bytes().len() == estimated_size);
@@ -412,7 +482,7 @@ async fn test_tx_invoke_contract() {
             ].into_iter().collect()
         });
         let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default()); // Use T0 for InvokeContract
-        let estimated_size = builder.estimate_size();
+        let estimated_size = builder.estimate_size::<()>().unwrap();
         let tx = builder.build(&mut state, &alice.keypair).unwrap();
         assert!(estimated_size == tx.size());
         assert!(tx.to_bytes().len() == estimated_size);
@@ -449,6 +519,146 @@ async fn test_tx_invoke_contract() {
 }
 
 
+#[tokio::test]
+async fn test_tx_invoke_contract_private_deposit() {
+    let mut alice = Account::new();
+
+    alice.set_balance(TERMINOS_ASSET, 100 * COIN_VALUE);
+
+    let tx = {
+        let mut state = AccountStateImpl {
+            balances: alice.balances.clone(),
+            nonce: alice.nonce,
+            reference: Reference {
+                topoheight: 0,
+                hash: Hash::zero(),
+            },
+        };
+
+        let data = TransactionTypeBuilder::InvokeContract(InvokeContractBuilder {
+            contract: Hash::zero(),
+            chunk_id: 0,
+            max_gas: 1000,
+            parameters: Vec::new(),
+            deposits: [
+                (TERMINOS_ASSET, ContractDepositBuilder {
+                    amount: 50 * COIN_VALUE,
+                    private: true
+                })
+            ].into_iter().collect()
+        });
+        let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+        let estimated_size = builder.estimate_size::<()>().unwrap();
+        let tx = builder.build(&mut state, &alice.keypair).unwrap();
+        assert!(estimated_size == tx.size());
+        assert!(tx.to_bytes().len() == estimated_size);
+
+        Arc::new(tx)
+    };
+
+    let mut state = ChainState::new();
+    let mut module = Module::new();
+    module.add_entry_chunk(Chunk::new());
+    state.contracts.insert(Hash::zero(), module);
+
+    // Create the chain state
+    {
+        let mut balances = HashMap::new();
+        for (asset, balance) in &alice.balances {
+            balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+        }
+        state.accounts.insert(alice.keypair.get_public_key().compress(), AccountChainState {
+            balances,
+            nonce: alice.nonce,
+        });
+    }
+
+    // The deposit commitment, handles and proofs built with the contract key must be accepted
+    let hash = tx.hash();
+    tx.verify(&hash, &mut state, &NoZKPCache).await.unwrap();
+
+    // Check Alice balance
+    let balance = alice.keypair.decrypt_to_point(&state.accounts[&alice.keypair.get_public_key().compress()].balances[&TERMINOS_ASSET]);
+    // 50 coins private deposit + tx fee + 1000 gas fee
+    let total_spend = (50 * COIN_VALUE) + tx.fee + 1000;
+
+    assert_eq!(balance, Scalar::from((100 * COIN_VALUE) - total_spend) * (*G));
+}
+
+#[test]
+fn test_tx_deploy_contract_private_deposit() {
+    let mut alice = Account::new();
+
+    alice.set_balance(TERMINOS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    // The contract key depends on the TX hash, so private deposits can't be built
+    // This is detected before the module constructor is checked
+    let mut module = Module::new();
+    module.add_chunk(Chunk::new());
+    let data = TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+        module: module.to_hex(),
+        invoke: Some(DeployContractInvokeBuilder {
+            max_gas: 1000,
+            deposits: [
+                (TERMINOS_ASSET, ContractDepositBuilder {
+                    amount: 50 * COIN_VALUE,
+                    private: true
+                })
+            ].into_iter().collect()
+        })
+    });
+    let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    assert!(matches!(builder.build(&mut state, &alice.keypair), Err(GenerationError::MissingContractKey)));
+}
+
+
+#[test]
+fn test_tx_deploy_contract_estimate_size() {
+    let mut alice = Account::new();
+
+    alice.set_balance(TERMINOS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let mut module = Module::new();
+    module.add_chunk(Chunk::from_instructions(vec![0u8; 300]));
+    module.add_entry_chunk(Chunk::from_instructions(vec![1u8; 1001]));
+    let data = TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+        module: module.to_hex(),
+        invoke: None
+    });
+    let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    let estimated_size = builder.estimate_size::<()>().unwrap();
+    let tx = builder.build(&mut state, &alice.keypair).unwrap();
+    assert!(estimated_size == tx.size(), "expected {} bytes got {} bytes", tx.size(), estimated_size);
+    assert!(tx.to_bytes().len() == estimated_size);
+
+    // Invalid hex must be rejected during the estimation
+    let data = TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+        module: "0".to_owned(),
+        invoke: None
+    });
+    let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    assert!(matches!(builder.estimate_size::<()>(), Err(GenerationError::InvalidModule)));
+}
+
+
 #[tokio::test]
 async fn test_tx_deploy_contract() {
     let mut alice = Account::new();
@@ -472,7 +682,7 @@ async fn test_tx_deploy_contract() {
             invoke: None
         });
         let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default()); // Use T0 for DeployContract
-        let estimated_size = builder.estimate_size();
+        let estimated_size = builder.estimate_size::<()>().unwrap();
         let tx = builder.build(&mut state, &alice.keypair).unwrap();
         assert!(estimated_size == tx.size(), "expected {} bytes got {} bytes", tx.size(), estimated_size);
         assert!(tx.to_bytes().len() == estimated_size);
@@ -536,7 +746,7 @@ async fn test_max_transfers() {
 
         let data = TransactionTypeBuilder::Transfers(transfers);
         let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
-        let estimated_size = builder.estimate_size();
+        let estimated_size = builder.estimate_size::<()>().unwrap();
         let tx = builder.build(&mut state, &alice.keypair).unwrap();
         assert!(estimated_size == tx.size());
         assert!(tx.to_bytes().len() == estimated_size);
@@ -597,7 +807,7 @@ async fn test_multisig_setup() {
             participants: IndexSet::from_iter(vec![bob.keypair.get_public_key().to_address(false), charlie.keypair.get_public_key().to_address(false)]),
         });
         let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default()); // Use T0 for MultiSig
-        let estimated_size = builder.estimate_size();
+        let estimated_size = builder.estimate_size::<()>().unwrap();
         let tx = builder.build(&mut state, &alice.keypair).unwrap();
         assert!(estimated_size == tx.size());
         assert!(tx.to_bytes().len() == estimated_size);
@@ -711,6 +921,103 @@ async fn test_multisig() {
     tx.verify(&hash, &mut state, &NoZKPCache).await.unwrap();
 }
 
+#[tokio::test]
+async fn test_multisig_partial_signing() {
+    let mut alice = Account::new();
+    let mut bob = Account::new();
+
+    // Signers
+    let charlie = Account::new();
+    let dave = Account::new();
+    let eve = Account::new();
+
+    alice.set_balance(TERMINOS_ASSET, 100 * COIN_VALUE);
+    bob.set_balance(TERMINOS_ASSET, 0);
+
+    let config = MultiSigPayload {
+        threshold: 2,
+        participants: IndexSet::from_iter(vec![
+            charlie.keypair.get_public_key().compress(),
+            dave.keypair.get_public_key().compress(),
+            eve.keypair.get_public_key().compress()
+        ]),
+    };
+
+    let tx = {
+        let mut state = AccountStateImpl {
+            balances: alice.balances.clone(),
+            nonce: alice.nonce,
+            reference: Reference {
+                topoheight: 0,
+                hash: Hash::zero(),
+            },
+        };
+
+        let data = TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+            amount: 1,
+            destination: bob.address(),
+            asset: TERMINOS_ASSET,
+            extra_data: None,
+            encrypt_extra_data: true,
+        }]);
+        let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), Some(2), data, FeeBuilder::default());
+        let mut tx = builder.build_unsigned(&mut state, &alice.keypair).unwrap();
+
+        // Each participant signs the digest independently
+        let digest = tx.get_hash_for_multisig();
+        let sign = |account: &Account, id: u8| SignatureId {
+            id,
+            signature: account.keypair.sign(digest.as_bytes())
+        };
+
+        assert!(matches!(tx.clone().finalize_multisig(&config, &alice.keypair), Err(MultiSigSigningError::NotEnoughSignatures(0, 2))));
+
+        tx.add_multisig_signature(&config, sign(&charlie, 0)).unwrap();
+        assert!(matches!(tx.add_multisig_signature(&config, sign(&charlie, 0)), Err(MultiSigSigningError::AlreadySigned(0))));
+        assert!(matches!(tx.add_multisig_signature(&config, sign(&charlie, 1)), Err(MultiSigSigningError::InvalidSignature(1))));
+        assert!(matches!(tx.add_multisig_signature(&config, sign(&bob, 3)), Err(MultiSigSigningError::UnknownParticipant(3))));
+        assert!(matches!(tx.clone().finalize_multisig(&config, &alice.keypair), Err(MultiSigSigningError::NotEnoughSignatures(1, 2))));
+
+        tx.add_multisig_signature(&config, sign(&eve, 2)).unwrap();
+        assert!(matches!(tx.add_multisig_signature(&config, sign(&dave, 1)), Err(MultiSigSigningError::ThresholdReached(2))));
+
+        Arc::new(tx.finalize_multisig(&config, &alice.keypair).unwrap())
+    };
+
+    // Create the chain state
+    let mut state = ChainState::new();
+
+    // Alice
+    {
+        let mut balances = HashMap::new();
+        for (asset, balance) in alice.balances {
+            balances.insert(asset, balance.ciphertext.take_ciphertext().unwrap());
+        }
+        state.accounts.insert(alice.keypair.get_public_key().compress(), AccountChainState {
+            balances,
+            nonce: alice.nonce,
+        });
+    }
+
+    // Bob
+    {
+        let mut balances = HashMap::new();
+        for (asset, balance) in bob.balances {
+            balances.insert(asset, balance.ciphertext.take_ciphertext().unwrap());
+        }
+
+        state.accounts.insert(bob.keypair.get_public_key().compress(), AccountChainState {
+            balances,
+            nonce: alice.nonce,
+        });
+    }
+
+    state.multisig.insert(alice.keypair.get_public_key().compress(), config);
+
+    let hash = tx.hash();
+    tx.verify(&hash, &mut state, &NoZKPCache).await.unwrap();
+}
+
 #[tokio::test]
 async fn test_transfer_extra_data_limits() {
     let mut alice = Account::new();
@@ -845,6 +1152,292 @@ async fn test_transfer_extra_data_limits() {
     }
 }
 
+#[test]
+fn test_fee_builder_variants() {
+    let mut alice = Account::new();
+    alice.set_balance(TERMINOS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let mut estimate = |fee_builder: FeeBuilder| {
+        let data = TransactionTypeBuilder::Burn(BurnPayload {
+            asset: TERMINOS_ASSET,
+            amount: 50 * COIN_VALUE,
+        });
+        let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, fee_builder);
+        builder.estimate_fees_value(&mut state).unwrap()
+    };
+
+    let base = estimate(FeeBuilder::default());
+    assert!(base > 0);
+    assert_eq!(estimate(FeeBuilder::Value(5)), 5);
+    assert_eq!(estimate(FeeBuilder::Boost(10)), base + 10);
+    assert_eq!(estimate(FeeBuilder::Multiplier(2.0)), base * 2);
+    assert_eq!(estimate(FeeBuilder::Capped { multiplier: 2.0, max: u64::MAX }), base * 2);
+    assert_eq!(estimate(FeeBuilder::Capped { multiplier: 10.0, max: base + 1 }), base + 1);
+}
+
+#[test]
+fn test_split_transfers() {
+    let bob = Account::new();
+    let transfer = TransferBuilder {
+        amount: 1,
+        destination: bob.address(),
+        asset: TERMINOS_ASSET,
+        extra_data: None,
+        encrypt_extra_data: true,
+    };
+
+    let batches = split_transfers(vec![transfer.clone(); MAX_TRANSFER_COUNT * 2 + 10]);
+    assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![MAX_TRANSFER_COUNT, MAX_TRANSFER_COUNT, 10]);
+
+    // Extra data limit is reached before the transfers count
+    let mut transfer = transfer;
+    transfer.extra_data = Some(DataElement::Value(DataValue::Blob(vec![0u8; 900])));
+    let batches = split_transfers(vec![transfer; 100]);
+    assert!(batches.len() > 1);
+    assert_eq!(batches.iter().map(Vec::len).sum::<usize>(), 100);
+    for batch in batches {
+        let size = batch.iter()
+            .map(|t| t.extra_data.as_ref().unwrap().size())
+            .sum::<usize>();
+        assert!(size <= EXTRA_DATA_LIMIT_SUM_SIZE);
+    }
+}
+
+#[test]
+fn test_build_with_openings() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+
+    alice.set_balance(TERMINOS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let data = TransactionTypeBuilder::Transfers(vec![
+        TransferBuilder {
+            amount: 10,
+            destination: bob.address(),
+            asset: TERMINOS_ASSET,
+            extra_data: None,
+            encrypt_extra_data: true,
+        },
+        TransferBuilder {
+            amount: 25,
+            destination: bob.address(),
+            asset: TERMINOS_ASSET,
+            extra_data: None,
+            encrypt_extra_data: true,
+        }
+    ]);
+    let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    let result = builder.build_with_openings(&mut state, &alice.keypair).unwrap();
+
+    let TransactionType::Transfers(transfers) = result.transaction.get_data() else {
+        panic!("Expected transfers");
+    };
+
+    assert_eq!(result.openings.len(), transfers.len());
+    for (index, transfer) in transfers.iter().enumerate() {
+        let opening = &result.openings[&index];
+        let commitment = PedersenCommitment::new_with_opening(opening.amount, &opening.opening).compress();
+        assert_eq!(&commitment, transfer.get_commitment());
+    }
+    assert_eq!(result.openings[&1].amount, 25);
+}
+
+#[test]
+fn test_deterministic_openings() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+
+    alice.set_balance(TERMINOS_ASSET, 100 * COIN_VALUE);
+
+    let build = |seed: u64| {
+        let mut state = AccountStateImpl {
+            balances: alice.balances.clone(),
+            nonce: alice.nonce,
+            reference: Reference {
+                topoheight: 0,
+                hash: Hash::zero(),
+            },
+        };
+
+        let data = TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+            amount: 10,
+            destination: bob.address(),
+            asset: TERMINOS_ASSET,
+            extra_data: None,
+            encrypt_extra_data: true,
+        }]);
+        let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+        let tx = builder.build_unsigned_with_opening_provider(&mut state, &alice.keypair, &mut StdRng::seed_from_u64(seed))
+            .unwrap()
+            .finalize(&alice.keypair);
+
+        match tx.get_data() {
+            TransactionType::Transfers(transfers) => transfers[0].get_commitment().clone(),
+            _ => panic!("Expected transfers")
+        }
+    };
+
+    assert_eq!(build(42), build(42));
+    assert_ne!(build(42), build(43));
+}
+
+#[test]
+fn test_burn_energy_fees_rejected() {
+    let mut alice = Account::new();
+    // No balance: the fee type must be rejected before any balance check
+    alice.set_balance(TERMINOS_ASSET, 0);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let data = TransactionTypeBuilder::Burn(BurnPayload {
+        asset: TERMINOS_ASSET,
+        amount: 50 * COIN_VALUE,
+    });
+    let builder = TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default())
+        .with_energy_fees();
+    assert!(matches!(builder.build(&mut state, &alice.keypair), Err(GenerationError::State(_))));
+}
+
+#[test]
+fn test_verify_burn() {
+    let payload = BurnPayload {
+        asset: TERMINOS_ASSET,
+        amount: 50 * COIN_VALUE,
+    };
+
+    // Same transcript as the one built by the transaction builder
+    let mut expected = Transcript::new(b"test");
+    expected.burn_proof_domain_separator();
+    expected.append_hash(b"burn_asset", &payload.asset);
+    expected.append_u64(b"burn_amount", payload.amount);
+
+    let mut transcript = Transcript::new(b"test");
+    assert!(verify_burn(&payload, &mut transcript).is_ok());
+    assert_eq!(transcript.challenge_scalar(b"c"), expected.challenge_scalar(b"c"));
+
+    let payload = BurnPayload {
+        asset: TERMINOS_ASSET,
+        amount: 0,
+    };
+    assert!(matches!(verify_burn(&payload, &mut Transcript::new(b"test")), Err(ProofVerificationError::Format)));
+}
+
+#[test]
+fn test_intent_fingerprint() {
+    let alice = Account::new();
+    let bob = Account::new();
+    let charlie = Account::new();
+
+    let builder_for = |destination: Address, amount: u64| {
+        let data = TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+            amount,
+            destination,
+            asset: TERMINOS_ASSET,
+            extra_data: None,
+            encrypt_extra_data: true,
+        }]);
+        TransactionBuilder::new(TxVersion::T0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default())
+    };
+
+    // Same payment, the fingerprint doesn't depend on the proofs randomness
+    let builder = builder_for(bob.address(), 50);
+    let fingerprint = builder.intent_fingerprint(0);
+    assert_eq!(fingerprint, builder_for(bob.address(), 50).intent_fingerprint(0));
+
+    // Not the TX hash
+    let mut alice_account = alice.clone();
+    alice_account.set_balance(TERMINOS_ASSET, 100 * COIN_VALUE);
+    let tx = create_tx_for(alice_account, bob.address(), 50, None);
+    assert_ne!(tx.hash(), fingerprint);
+
+    // Different amount
+    assert_ne!(fingerprint, builder_for(bob.address(), 51).intent_fingerprint(0));
+
+    // Different destination
+    assert_ne!(fingerprint, builder_for(charlie.address(), 50).intent_fingerprint(0));
+
+    // Different nonce
+    assert_ne!(fingerprint, builder.intent_fingerprint(1));
+}
+
+#[test]
+fn test_multisig_validate() {
+    let alice = Account::new();
+    let bob = Account::new();
+
+    let source = alice.keypair.get_public_key().compress();
+    let build = |threshold: u8, participants: Vec<Address>| {
+        let data = TransactionTypeBuilder::MultiSig(MultiSigBuilder {
+            threshold,
+            participants: IndexSet::from_iter(participants),
+        });
+        TransactionBuilder::new(TxVersion::T0, source.clone(), None, data, FeeBuilder::default())
+    };
+
+    assert!(build(1, vec![bob.address()]).validate::<()>().is_ok());
+    assert!(matches!(build(2, vec![bob.address()]).validate::<()>(), Err(GenerationError::MultiSigThreshold)));
+    assert!(matches!(build(0, vec![bob.address()]).validate::<()>(), Err(GenerationError::MultiSigThreshold)));
+    assert!(matches!(build(1, vec![bob.address(), alice.address()]).validate::<()>(), Err(GenerationError::MultiSigSelfParticipant)));
+}
+
+#[test]
+fn test_transfers_with_common_extra_data() {
+    let bob = Account::new();
+    let transfer = TransferBuilder {
+        amount: 1,
+        destination: bob.address(),
+        asset: TERMINOS_ASSET,
+        extra_data: None,
+        encrypt_extra_data: true,
+    };
+
+    let extra_data = DataElement::Value(DataValue::Blob(vec![0u8; 1000]));
+    let data = TransactionTypeBuilder::transfers_with_common_extra_data::<()>(vec![transfer.clone(); 2], extra_data.clone(), false).unwrap();
+    match data {
+        TransactionTypeBuilder::Transfers(transfers) => {
+            assert!(transfers.iter().all(|t| t.extra_data.is_some() && !t.encrypt_extra_data));
+        },
+        _ => panic!("Expected transfers")
+    }
+
+    // 33 * 1000 bytes is above the total limit
+    let result = TransactionTypeBuilder::transfers_with_common_extra_data::<()>(vec![transfer.clone(); 33], extra_data, true);
+    assert!(matches!(result, Err(GenerationError::ExtraDataTooLarge)));
+
+    // The plaintext fits in the total limit, but not once encrypted
+    let extra_data = DataElement::Value(DataValue::Blob(vec![0u8; 900]));
+    assert!(extra_data.size() * 34 <= EXTRA_DATA_LIMIT_SUM_SIZE);
+    assert!(TransactionTypeBuilder::transfers_with_common_extra_data::<()>(vec![transfer.clone(); 34], extra_data.clone(), false).is_ok());
+
+    let result = TransactionTypeBuilder::transfers_with_common_extra_data::<()>(vec![transfer; 34], extra_data, true);
+    assert!(matches!(result, Err(GenerationError::ExtraDataTooLarge)));
+}
+
 #[async_trait]
 impl<'a> BlockchainVerificationState<'a, TestError> for ChainState {
 
@@ -58,6 +58,7 @@ use crate::{
     }
 };
 use super::{
+    BurnPayload,
     ContractDeposit,
     FeeType,
     Role,
@@ -106,6 +107,22 @@ struct DecompressedDepositCt {
     receiver_handle: DecryptHandle,
 }
 
+// Verify a burn payload and bind it to the transaction transcript
+// This is used by the transaction verification and can be used
+// by any tool to verify independently a proof of burn
+pub fn verify_burn(payload: &BurnPayload, transcript: &mut Transcript) -> Result<(), ProofVerificationError> {
+    // Burn of zero are not allowed
+    if payload.amount == 0 {
+        return Err(ProofVerificationError::Format);
+    }
+
+    transcript.burn_proof_domain_separator();
+    transcript.append_hash(b"burn_asset", &payload.asset);
+    transcript.append_u64(b"burn_amount", payload.amount);
+
+    Ok(())
+}
+
 impl Transaction {
     pub fn has_valid_version_format(&self) -> bool {
         match self.version {
@@ -881,9 +898,7 @@ impl Transaction {
             },
             TransactionType::Burn(payload) => {
                 if self.get_version() >= TxVersion::T0 {
-                    transcript.burn_proof_domain_separator();
-                    transcript.append_hash(b"burn_asset", &payload.asset);
-                    transcript.append_u64(b"burn_amount", payload.amount);
+                    verify_burn(payload, &mut transcript)?;
                 }
             },
             TransactionType::MultiSig(payload) => {
@@ -1029,6 +1044,10 @@ impl Transaction {
                 .map_err(|_| ProofVerificationError::GenericProof)?;
 
             if !prepared.is_empty() {
+                // All the range proofs share the same generators,
+                // verify them in a single multiscalar multiplication
+                // The daemon splits the block TXs in up to `txs_verification_threads_count`
+                // batches (see Blockchain::add_new_block), each one verified here
                 RangeProof::verify_batch(
                     prepared.iter_mut()
                         .map(|(tx, transcript, commitments)| {
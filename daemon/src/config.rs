@@ -110,6 +110,12 @@ pub const DEV_ADDRESS: &str = "tos:qsl6sj2u0gp37tr6drrq964rd4d8gnaxnezgytmt0cflt
 // Chain sync config
 // minimum X seconds between each chain sync request per peer
 pub const CHAIN_SYNC_DELAY: u64 = 5;
+// default maximum jitter in percent of the chain sync delay
+// This is used to spread the chain sync requests of nodes started at the same time
+pub const CHAIN_SYNC_DEFAULT_JITTER_PERCENT: u8 = 20;
+// maximum jitter allowed in percent of the chain sync delay
+// A peer rejects our chain sync requests if they are sent under 2/3 of the delay
+pub const CHAIN_SYNC_MAX_JITTER_PERCENT: u8 = 30;
 // wait maximum between each chain sync request to peers
 pub const CHAIN_SYNC_TIMEOUT_SECS: u64 = CHAIN_SYNC_DELAY * 3;
 // first 30 blocks are sent in linear way, then it's exponential
@@ -124,6 +130,9 @@ pub const CHAIN_SYNC_DEFAULT_RESPONSE_BLOCKS: usize = 4096;
 pub const CHAIN_SYNC_RESPONSE_MAX_BLOCKS: usize = u16::MAX as _;
 // send last 10 heights
 pub const CHAIN_SYNC_TOP_BLOCKS: usize = 10;
+// Factor of the chain response size above which the gap with a peer
+// is considered too large for the normal chain sync and the fast sync is recommended
+pub const CHAIN_SYNC_GAP_FAST_SYNC_FACTOR: u64 = 100;
 
 // P2p rules
 // time between each ping
@@ -132,16 +141,30 @@ pub const P2P_PING_DELAY: u64 = 10;
 pub const P2P_PING_PEER_LIST_DELAY: u64 = 60 * 5;
 // maximum number of addresses to be send
 pub const P2P_PING_PEER_LIST_LIMIT: usize = 16;
+// Minimum version of a peer to exchange the handshake extensions with it
+// Older versions only parse the original handshake and reject any unknown packet,
+// so the extensions are never sent to them
+pub const P2P_EXTENSIONS_VERSION_REQUIREMENT: &str = ">=0.2.0";
 // default number of maximum peers
 pub const P2P_DEFAULT_MAX_PEERS: usize = 32;
 // default number of maximum outgoing peers
 pub const P2P_DEFAULT_MAX_OUTGOING_PEERS: usize = 8;
+// default number of maximum inbound peers
+// Keep enough free slots for our outgoing peers
+pub const P2P_DEFAULT_MAX_INBOUND_PEERS: usize = P2P_DEFAULT_MAX_PEERS - P2P_DEFAULT_MAX_OUTGOING_PEERS;
 // time in seconds between each time we try to connect to a new peer
 pub const P2P_EXTEND_PEERLIST_DELAY: u64 = 60;
 // time in seconds between each time we try to connect to a outgoing peer
 // At least 5 minutes of countdown to retry to connect to the same peer
 // This will be multiplied by the number of fails
 pub const P2P_PEERLIST_RETRY_AFTER: u64 = 60 * 15;
+// Initial backoff in millis before reconnecting to an unreachable exclusive or seed node
+// It is doubled on each failed attempt
+pub const P2P_RECONNECT_BACKOFF_MIN: u64 = 5_000;
+// Maximum backoff in millis before reconnecting to an unreachable exclusive or seed node
+pub const P2P_RECONNECT_BACKOFF_MAX: u64 = 5 * 60 * 1000;
+// Default number of retries with another peer for a failed block request
+pub const P2P_DEFAULT_BLOCK_REQUEST_RETRIES: u8 = 2;
 // Delay in second to connect to priority nodes
 pub const P2P_AUTO_CONNECT_PRIORITY_NODES_DELAY: u64 = 5;
 // Default number of concurrent tasks for incoming p2p connections
@@ -151,6 +174,16 @@ pub const P2P_HEARTBEAT_INTERVAL: u64 = P2P_PING_DELAY / 2;
 // Timeout in seconds
 // If we didn't receive any packet from a peer during this time, we disconnect it
 pub const P2P_PING_TIMEOUT: u64 = P2P_PING_DELAY * 6;
+// Interval in millis to check if the pending blocks/txs are processed while draining
+pub const P2P_DRAIN_CHECK_INTERVAL: u64 = 100;
+// Warn if the median clock offset with our peers is above this threshold in seconds
+// Based on the limit accepted for blocks in the future
+pub const P2P_TIME_OFFSET_WARN_THRESHOLD: i64 = (TIMESTAMP_IN_FUTURE_LIMIT / MILLIS_PER_SECOND) as i64;
+// Minimum peers required to compute a meaningful median clock offset
+pub const P2P_TIME_OFFSET_MIN_PEERS: usize = 3;
+// Maximum topoheight difference with the peers median topoheight
+// to consider our chain as synced
+pub const P2P_SYNCED_TOPOHEIGHT_TOLERANCE: u64 = STABLE_LIMIT;
 
 // Peer rules
 // number of seconds to reset the counter
@@ -160,6 +193,9 @@ pub const PEER_FAIL_TIME_RESET: u64 = 30 * 60;
 pub const PEER_FAIL_LIMIT: u8 = 50;
 // number of fail during handshake before temp ban
 pub const PEER_FAIL_TO_CONNECT_LIMIT: u8 = 3;
+// number of unsolicited responses (objects, chain, bootstrap chain) before temp ban
+// It is tracked separately from the fail count
+pub const PEER_UNSOLICITED_RESPONSES_LIMIT: u32 = 20;
 // number of seconds to temp ban the peer in case of fail reached during handshake
 // It is only used for incoming connections
 // Set to 1 minute
@@ -171,6 +207,8 @@ pub const PEER_TEMP_BAN_TIME: u64 = 15 * 60;
 pub const PEER_TIMEOUT_REQUEST_OBJECT: u64 = 15_000;
 // How many objects requests can be concurrently requested?
 pub const PEER_OBJECTS_CONCURRENCY: usize = 64;
+// How many objects can be requested in a single batch request
+pub const PEER_OBJECTS_BATCH_MAX_LEN: usize = 32;
 // millis until we timeout during a bootstrap request
 pub const PEER_TIMEOUT_BOOTSTRAP_STEP: u64 = 60_000;
 // millis until we timeout during a handshake
@@ -179,6 +217,11 @@ pub const PEER_TIMEOUT_INIT_CONNECTION: u64 = 5_000;
 pub const PEER_TIMEOUT_INIT_OUTGOING_CONNECTION: u64 = 30_000;
 // millis until we timeout during a handshake
 pub const PEER_TIMEOUT_DISCONNECT: u64 = 1_500;
+// Minimum packet size in bytes before compressing it
+// Smaller packets are sent raw as compression is not worth it
+pub const PEER_COMPRESSION_THRESHOLD: usize = 1024;
+// zstd compression level used for packets
+pub const PEER_COMPRESSION_LEVEL: i32 = 3;
 // Maximum packet size set to 5 MiB
 pub const PEER_MAX_PACKET_SIZE: u32 = 5 * (BYTES_PER_KB * BYTES_PER_KB) as u32;
 // Peer TX cache size
@@ -188,7 +231,8 @@ pub const PEER_TX_CACHE_SIZE: usize = 1024;
 pub const PEER_PEERS_CACHE_SIZE: usize = 1024;
 // Peer Block cache size
 pub const PEER_BLOCK_CACHE_SIZE: usize = 1024;
-// Peer packet channel size
+// Default peer packet channel size
+// A peer with more packets waiting to be sent is disconnected
 pub const PEER_PACKET_CHANNEL_SIZE: usize = 1024;
 // Peer timeout for packet channel
 // Millis
@@ -9,6 +9,7 @@ use terminos_common::{
         daemon::{
             BlockOrderedEvent,
             BlockOrphanedEvent,
+            ChainReorgEvent,
             BlockType,
             NotifyEvent,
             StableHeightChangedEvent,
@@ -18,8 +19,11 @@ use terminos_common::{
             NewContractEvent,
             InvokeContractEvent,
             NewAssetEvent,
+            AssetUpdatedEvent,
             ContractTransferEvent,
             ContractEvent,
+            MempoolTransactionEvictedEvent,
+            MempoolEvictionReason,
             MempoolTransactionSummary,
         },
         RPCContractOutput,
@@ -71,7 +75,7 @@ use terminos_common::{
         spawn_task,
         is_multi_threads_supported,
         net::lookup_host,
-        sync::{Mutex, RwLock, Semaphore}
+        sync::{Mutex, MutexGuard, RwLock, Semaphore}
     },
     varuint::VarUint,
     contract::build_environment,
@@ -94,12 +98,12 @@ use crate::{
         nonce_checker::NonceChecker,
         simulator::Simulator,
         storage::{DagOrderProvider, DifficultyProvider, Storage},
-        tx_selector::{TxSelector, TxSelectorEntry},
+        tx_selector::{SelectionStrategy, TxSelector, TxSelectorEntry},
         state::{ChainState, ApplicableChainState},
         hard_fork::*,
         TxCache,
     },
-    p2p::P2pServer,
+    p2p::{P2pServer, proxy::ProxyPool},
     rpc::{
         rpc::{
             get_block_type_for_block,
@@ -117,10 +121,10 @@ use std::{
         HashSet,
         VecDeque
     },
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     num::NonZeroUsize,
     sync::{
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc
     },
     time::{Duration, Instant}
@@ -174,6 +178,8 @@ pub struct Blockchain<S: Storage> {
     add_block_semaphore: Semaphore,
     // Contract environment stdlib
     environment: Environment,
+    // Only one manual storage compaction at a time
+    compaction_lock: Mutex<()>,
     // P2p module
     p2p: RwLock<Option<Arc<P2pServer<S>>>>,
     // RPC module
@@ -183,10 +189,27 @@ pub struct Blockchain<S: Storage> {
     difficulty: Mutex<Difficulty>,
     // if a simulator is set
     simulator: Option<Simulator>,
+    // Held while simulated blocks are produced
+    // so the simulator task never interleaves its blocks with simulator_mine
+    simulator_lock: Mutex<()>,
     // if we should skip PoW verification
     skip_pow_verification: bool,
     // Should we skip block template TXs verification
     skip_block_template_txs_verification: bool,
+    // Strategy used to select the TXs for the block template
+    tx_selection_strategy: SelectionStrategy,
+    // Maximum tips referenced by a block template
+    // Never above TIPS_LIMIT
+    block_template_tips_limit: usize,
+    // Are new transactions accepted in the mempool
+    // This can be disabled during a maintenance
+    accept_transactions: AtomicBool,
+    // Minimum connected peers and synced state required
+    // before answering the balance and nonce RPC queries
+    rpc_ready_min_peers: Option<usize>,
+    // Minimum fee increase in percent to replace a pending TX with the same nonce
+    // None if the replace-by-fee is disabled
+    replace_by_fee_min_increase: Option<u64>,
     // current network type on which one we're using/connected to
     network: Network,
     // this cache is used to avoid to recompute the common base for each block and is mandatory
@@ -203,9 +226,12 @@ pub struct Blockchain<S: Storage> {
     auto_prune_keep_n_blocks: Option<u64>,
     // Flush storage manually to the disk every N blocks (topoheight based)
     flush_db_every_n_blocks: Option<u64>,
+    // Genesis block hash configured to override the network one
+    genesis_hash: Option<Hash>,
     // Blocks hashes checkpoints
     // No rewind can be done below these blocks
-    checkpoints: HashSet<Hash>,
+    // Checkpoints added at runtime are persisted in the storage
+    checkpoints: RwLock<HashSet<Hash>>,
     // Threads count to use during a block verification
     // If more than one thread is used, it will use batch TXs
     // in differents groups and will verify them in parallel
@@ -216,7 +242,7 @@ pub struct Blockchain<S: Storage> {
 }
 
 impl<S: Storage> Blockchain<S> {
-    pub async fn new(mut config: Config, network: Network, storage: S) -> Result<Arc<Self>, Error> {
+    pub async fn new(mut config: Config, network: Network, mut storage: S) -> Result<Arc<Self>, Error> {
         // Do some checks on config params
         {
             if config.simulator.is_some() && network != Network::Devnet {
@@ -252,11 +278,33 @@ impl<S: Storage> Blockchain<S> {
                 return Err(BlockchainError::InvalidConfig.into())
             }
 
-            if config.p2p.proxy.kind.is_some() != config.p2p.proxy.address.is_some() {
+            if config.block_template_tips_limit == 0 || config.block_template_tips_limit > TIPS_LIMIT {
+                error!("Block template tips limit must be between 1 and {}", TIPS_LIMIT);
+                return Err(BlockchainError::InvalidConfig.into())
+            }
+
+            if config.p2p.proxy.kind.is_some() != (config.p2p.proxy.address.is_some() || !config.p2p.proxy.pool.is_empty()) {
                 error!("P2P Proxy must be specified with an address");
                 return Err(BlockchainError::InvalidConfig.into())
             }
 
+            for addr in config.p2p.proxy.address.iter().chain(config.p2p.proxy.pool.iter()) {
+                if let Err(e) = addr.parse::<SocketAddr>() {
+                    error!("Invalid P2P proxy address {}: {}", addr, e);
+                    return Err(BlockchainError::InvalidConfig.into())
+                }
+            }
+
+            if config.genesis_block_hash.is_some() && network.is_mainnet() {
+                error!("Genesis block hash override is not allowed on mainnet");
+                return Err(BlockchainError::InvalidConfig.into())
+            }
+
+            if config.rpc.admin_username.is_some() != config.rpc.admin_password.is_some() {
+                error!("RPC admin username and password must be set together");
+                return Err(BlockchainError::InvalidConfig.into())
+            }
+
             if config.p2p.proxy.username.is_some() != config.p2p.proxy.password.is_some() {
                 error!("P2P Proxy auth username/password mismatch");
                 return Err(BlockchainError::InvalidConfig.into())
@@ -274,6 +322,18 @@ impl<S: Storage> Blockchain<S> {
             }
         }
 
+        if !config.prune_retained_contracts.is_empty() {
+            info!("{} contracts will keep their full history when pruning", config.prune_retained_contracts.len());
+        }
+        storage.set_prune_retained_contracts(config.prune_retained_contracts.iter().cloned().collect())?;
+
+        let mut checkpoints: HashSet<Hash> = config.checkpoints.into_iter().collect();
+        let stored_checkpoints = storage.get_checkpoints().await?;
+        if !stored_checkpoints.is_empty() {
+            info!("Loaded {} checkpoints from storage", stored_checkpoints.len());
+            checkpoints.extend(stored_checkpoints);
+        }
+
         let on_disk = storage.has_blocks().await?;
         let (height, topoheight) = if on_disk {
             info!("Reading last metadata available...");
@@ -295,11 +355,13 @@ impl<S: Storage> Blockchain<S> {
             storage: RwLock::new(storage),
             add_block_semaphore: Semaphore::new(1),
             environment,
+            compaction_lock: Mutex::new(()),
             p2p: RwLock::new(None),
             rpc: RwLock::new(None),
             difficulty: Mutex::new(GENESIS_BLOCK_DIFFICULTY),
             skip_pow_verification: config.skip_pow_verification || config.simulator.is_some(),
             simulator: config.simulator,
+            simulator_lock: Mutex::new(()),
             network,
             tip_base_cache: Mutex::new(LruCache::new(NonZeroUsize::new(DEFAULT_CACHE_SIZE).expect("Default cache size for tip base must be above 0"))),
             tip_work_score_cache: Mutex::new(LruCache::new(NonZeroUsize::new(DEFAULT_CACHE_SIZE).expect("Default cache size for tip work score must be above 0"))),
@@ -307,12 +369,30 @@ impl<S: Storage> Blockchain<S> {
             full_order_cache: Mutex::new(LruCache::new(NonZeroUsize::new(DEFAULT_CACHE_SIZE).expect("Default cache size for full order must be above 0"))),
             auto_prune_keep_n_blocks: config.auto_prune_keep_n_blocks,
             skip_block_template_txs_verification: config.skip_block_template_txs_verification,
-            checkpoints: config.checkpoints.into_iter().collect(),
+            tx_selection_strategy: config.block_template_tx_selection_strategy,
+            block_template_tips_limit: config.block_template_tips_limit,
+            accept_transactions: AtomicBool::new(true),
+            replace_by_fee_min_increase: config.mempool_replace_by_fee_min_increase,
+            rpc_ready_min_peers: config.rpc.ready_min_peers,
+            genesis_hash: config.genesis_block_hash,
+            checkpoints: RwLock::new(checkpoints),
             txs_verification_threads_count: config.txs_verification_threads_count,
             flush_db_every_n_blocks: config.flush_db_every_n_blocks,
             disable_zkp_cache: config.disable_zkp_cache,
         };
 
+        // Verify that the stored genesis block is the expected one
+        if on_disk {
+            if let Some(expected_hash) = blockchain.get_genesis_hash() {
+                let storage = blockchain.get_storage().read().await;
+                let genesis_hash = storage.get_hash_at_topo_height(0).await?;
+                if genesis_hash != *expected_hash {
+                    error!("Stored genesis block hash is invalid! Expected: {}, got: {}", expected_hash, genesis_hash);
+                    return Err(BlockchainError::InvalidGenesisHash.into())
+                }
+            }
+        }
+
         // include genesis block
         if !on_disk {
             blockchain.create_genesis_block(config.genesis_block_hex.as_deref()).await?;
@@ -348,6 +428,8 @@ impl<S: Storage> Blockchain<S> {
             info!("Starting P2p server...");
             // setup exclusive nodes
             let mut exclusive_nodes: Vec<SocketAddr> = Vec::with_capacity(config.exclusive_nodes.len());
+            // hosts resolved to both an IPv4 and an IPv6 address
+            let mut dual_stack_nodes: HashMap<SocketAddr, SocketAddr> = HashMap::new();
             for peer in config.exclusive_nodes {
                 for peer in peer.split(",") {
                     match peer.parse() {
@@ -358,10 +440,22 @@ impl<S: Storage> Blockchain<S> {
                             match lookup_host(&peer).await {
                                 Ok(it) => {
                                     info!("Valid host found for {}", peer);
+                                    let (mut ipv4, mut ipv6) = (None, None);
                                     for addr in it {
                                         info!("IP from DNS resolution: {}", addr);
+                                        if addr.is_ipv4() {
+                                            ipv4.get_or_insert(addr);
+                                        } else {
+                                            ipv6.get_or_insert(addr);
+                                        }
                                         exclusive_nodes.push(addr);
                                     }
+
+                                    if let (Some(ipv4), Some(ipv6), true) = (ipv4, ipv6, config.happy_eyeballs) {
+                                        debug!("{} is dual stack: {} / {}", peer, ipv4, ipv6);
+                                        dual_stack_nodes.insert(ipv4, ipv6);
+                                        dual_stack_nodes.insert(ipv6, ipv4);
+                                    }
                                 },
                                 Err(e2) => {
                                     error!("Error while parsing {} as exclusive node address: {}, {}", peer, e, e2);
@@ -373,14 +467,34 @@ impl<S: Storage> Blockchain<S> {
                 }
             }
 
+            // setup the IPs exempt from fail count bans
+            let mut fail_count_whitelist: HashSet<IpAddr> = HashSet::with_capacity(config.fail_count_whitelist.len());
+            for ip in config.fail_count_whitelist {
+                for ip in ip.split(",") {
+                    match ip.parse() {
+                        Ok(ip) => {
+                            fail_count_whitelist.insert(ip);
+                        },
+                        Err(e) => {
+                            error!("Error while parsing {} as fail count whitelisted IP: {}", ip, e);
+                        }
+                    };
+                }
+            }
+
             let proxy_auth = if let (Some(username), Some(password)) = (config.proxy.username, config.proxy.password) {
                 Some((username, password))
             } else {
                 None
             };
 
-            let proxy = if let (Some(proxy), Some(addr)) = (config.proxy.kind, &config.proxy.address) {
-                Some((proxy, addr.parse()?, proxy_auth))
+            let proxy = if let Some(kind) = config.proxy.kind {
+                let addresses = config.proxy.address.iter()
+                    .chain(config.proxy.pool.iter())
+                    .map(|addr| addr.parse())
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                ProxyPool::new(kind, addresses, proxy_auth, config.proxy.selection)
             } else {
                 None
             };
@@ -409,6 +523,21 @@ impl<S: Storage> Blockchain<S> {
                 config.disable_fetching_txs_propagated,
                 config.handle_peer_packets_in_dedicated_task,
                 proxy,
+                config.peer_rate_limit,
+                config.handshake_timeout_ms,
+                config.peer_selection_strategy,
+                config.max_inbound_peers,
+                config.chain_sync_jitter_percent,
+                config.observer,
+                config.peer_id,
+                !config.disable_compression,
+                config.block_request_retries,
+                dual_stack_nodes,
+                fail_count_whitelist,
+                config.strict_chain_response_size,
+                config.send_queue_limit,
+                config.unsolicited_responses_limit,
+                config.object_request_timeout_ms,
             ) {
                 Ok(p2p) => {
                     *arc.p2p.write().await = Some(p2p.clone());
@@ -476,6 +605,12 @@ impl<S: Storage> Blockchain<S> {
         self.simulator.is_some()
     }
 
+    // Lock the simulated blocks production
+    // Blocks are only produced by the owner of the guard until it is dropped
+    pub async fn lock_simulator(&self) -> MutexGuard<'_, ()> {
+        self.simulator_lock.lock().await
+    }
+
     // Skip PoW verification flag
     pub fn skip_pow_verification(&self) -> bool {
         self.skip_pow_verification
@@ -615,7 +750,14 @@ impl<S: Storage> Blockchain<S> {
                 )
             ).await?;
     
-            let (genesis_block, genesis_hash) = if let Some(genesis_block) = get_hex_genesis_block(&self.network) {
+            // The hardcoded genesis block is ignored if an override hash is configured
+            let hardcoded_genesis = if self.genesis_hash.is_none() {
+                get_hex_genesis_block(&self.network)
+            } else {
+                None
+            };
+
+            let (genesis_block, genesis_hash) = if let Some(genesis_block) = hardcoded_genesis {
                 info!("De-serializing genesis block for network {}...", self.network);
                 let genesis = Block::from_hex(genesis_block)?;
                 let expected_hash = genesis.hash();
@@ -640,7 +782,7 @@ impl<S: Storage> Blockchain<S> {
                 return Err(BlockchainError::GenesisBlockMiner)
             }
     
-            if let Some(expected_hash) = get_genesis_block_hash(&self.network) {
+            if let Some(expected_hash) = self.get_genesis_hash() {
                 if genesis_hash != *expected_hash {
                     error!("Genesis block hash is invalid! Expected: {}, got: {}", expected_hash, genesis_hash);
                     return Err(BlockchainError::InvalidGenesisHash)
@@ -689,6 +831,64 @@ impl<S: Storage> Blockchain<S> {
         Ok(block)
     }
 
+    // Mine N blocks on top of the chain with the dev key, only available in simulator mode
+    // The given TXs are added to the mempool first to be included in the next blocks
+    // The simulator task is paused until all the blocks are added
+    // so the produced blocks only depend on the current chain state and the given TXs
+    // Returns the hashes of the blocks added, in order
+    pub async fn simulator_mine(&self, n_blocks: usize, txs: Vec<Transaction>) -> Result<Vec<Hash>, BlockchainError> {
+        // The constructor already prevents a simulator outside of the dev network
+        if !self.is_simulator_enabled() || self.network != Network::Devnet {
+            return Err(BlockchainError::SimulatorDisabled)
+        }
+
+        let _guard = self.lock_simulator().await;
+        for tx in txs {
+            self.add_tx_to_mempool(tx, false).await?;
+        }
+
+        let mut hashes = Vec::with_capacity(n_blocks);
+        for _ in 0..n_blocks {
+            let block = self.mine_block(&DEV_PUBLIC_KEY).await?;
+            let hash = block.hash();
+            debug!("Adding simulated block {}", hash);
+            self.add_new_block(block, Some(Immutable::Owned(hash.clone())), BroadcastOption::None, false).await?;
+            hashes.push(hash);
+        }
+
+        Ok(hashes)
+    }
+
+    // Manually compact the storage to reclaim space, returns the elapsed time
+    // Only one compaction can run at a time
+    pub async fn compact_storage(&self) -> Result<Duration, BlockchainError> {
+        let _guard = self.compaction_lock.try_lock()
+            .map_err(|_| BlockchainError::CompactionInProgress)?;
+
+        info!("Compacting storage");
+        let start = Instant::now();
+        {
+            let storage = self.storage.read().await;
+            storage.compact().await?;
+        }
+
+        let elapsed = start.elapsed();
+        info!("Storage compacted in {}ms", elapsed.as_millis());
+        Ok(elapsed)
+    }
+
+    // Export a snapshot of the current chain state to the path
+    // The storage read lock prevents any new block while the checkpoint is created
+    pub async fn create_snapshot(&self, path: &str) -> Result<TopoHeight, BlockchainError> {
+        let storage = self.storage.read().await;
+        let topoheight = storage.get_top_topoheight().await?;
+
+        info!("Exporting snapshot at topoheight {} to {}", topoheight, path);
+        storage.export_snapshot(topoheight, path).await?;
+
+        Ok(topoheight)
+    }
+
     // Prune the chain until topoheight
     // This will delete all blocks / versioned balances / txs until topoheight in param
     pub async fn prune_until_topoheight(&self, topoheight: TopoHeight) -> Result<TopoHeight, BlockchainError> {
@@ -792,6 +992,85 @@ impl<S: Storage> Blockchain<S> {
         self.stable_topoheight.load(Ordering::Acquire)
     }
 
+    // Add a checkpoint at runtime, no rewind can be done below it
+    // The block must be topologically ordered and at or below the stable topoheight
+    // Returns the topoheight of the checkpoint
+    pub async fn add_checkpoint(&self, hash: Hash) -> Result<TopoHeight, BlockchainError> {
+        debug!("add checkpoint {}", hash);
+        let mut storage = self.storage.write().await;
+        if !storage.is_block_topological_ordered(&hash).await? {
+            return Err(BlockchainError::BlockNotOrdered)
+        }
+
+        let topoheight = storage.get_topo_height_for_hash(&hash).await?;
+        let stable_topoheight = self.get_stable_topoheight();
+        if topoheight > stable_topoheight {
+            return Err(BlockchainError::CheckpointNotStable(hash, topoheight, stable_topoheight))
+        }
+
+        let mut checkpoints = self.checkpoints.write().await;
+        if checkpoints.insert(hash.clone()) {
+            // Only the checkpoints not coming from the config are stored
+            let mut stored = storage.get_checkpoints().await?;
+            stored.insert(hash.clone());
+            storage.set_checkpoints(&stored).await?;
+            info!("Added checkpoint {} at topoheight {}", hash, topoheight);
+        }
+
+        Ok(topoheight)
+    }
+
+    // Rewind the chain until the requested topoheight
+    // The add block semaphore is held during the whole rewind so no new block is processed,
+    // and the storage write lock prevents any transaction to be added to the mempool
+    // Returns the new topoheight and the new tips of the chain
+    pub async fn rewind_chain_to_topoheight(&self, topoheight: TopoHeight) -> Result<(TopoHeight, Vec<Hash>), BlockchainError> {
+        debug!("rewind chain to topoheight {}", topoheight);
+        let _permit = self.add_block_semaphore.acquire().await?;
+        let mut storage = self.storage.write().await;
+
+        let current_topoheight = self.get_topo_height();
+        if topoheight >= current_topoheight {
+            return Err(BlockchainError::InvalidRewindTopoHeight(topoheight, current_topoheight))
+        }
+
+        if let Some(pruned_topoheight) = storage.get_pruned_topoheight().await? {
+            if topoheight <= pruned_topoheight {
+                return Err(BlockchainError::RewindBelowPrunedTopoHeight(topoheight, pruned_topoheight))
+            }
+        }
+
+        for hash in self.checkpoints.read().await.iter() {
+            if storage.is_block_topological_ordered(hash).await? {
+                let checkpoint_topoheight = storage.get_topo_height_for_hash(hash).await?;
+                if topoheight < checkpoint_topoheight {
+                    return Err(BlockchainError::RewindBelowCheckpoint(hash.clone(), checkpoint_topoheight))
+                }
+            }
+        }
+
+        let (new_topoheight, txs) = self.rewind_chain_for_storage(&mut storage, current_topoheight - topoheight, false).await?;
+        info!("Chain has been rewinded until topoheight {}, {} rewinded txs", new_topoheight, txs.len());
+
+        let tips = storage.get_tips().await?
+            .into_iter()
+            .collect();
+
+        Ok((new_topoheight, tips))
+    }
+
+    // Get the expected genesis block hash
+    // The configured override has priority over the hardcoded one of the network
+    pub fn get_genesis_hash(&self) -> Option<&Hash> {
+        self.genesis_hash.as_ref()
+            .or_else(|| get_genesis_block_hash(&self.network))
+    }
+
+    // Minimum connected peers required before answering the balance and nonce queries
+    pub fn get_rpc_ready_min_peers(&self) -> Option<usize> {
+        self.rpc_ready_min_peers
+    }
+
     // Get the network on which this chain is running
     pub fn get_network(&self) -> &Network {
         &self.network
@@ -1501,6 +1780,17 @@ impl<S: Storage> Blockchain<S> {
         &self.rpc
     }
 
+    // Enable or disable the acceptance of new transactions in the mempool
+    // The node keeps syncing and serving reads while disabled
+    pub fn set_accept_transactions(&self, accept: bool) {
+        self.accept_transactions.store(accept, Ordering::SeqCst);
+    }
+
+    // Are new transactions accepted in the mempool
+    pub fn is_accepting_transactions(&self) -> bool {
+        self.accept_transactions.load(Ordering::SeqCst)
+    }
+
     // Returns the storage used for blockchain
     pub fn get_storage(&self) -> &RwLock<S> {
         &self.storage
@@ -1518,8 +1808,13 @@ impl<S: Storage> Blockchain<S> {
     }
 
     // Add a tx to the mempool with the given hash, it is not computed and the TX is transformed into an Arc
+    // New TXs are rejected if the mempool is closed
     pub async fn add_tx_to_mempool_with_hash(&self, tx: Arc<Transaction>, hash: Immutable<Hash>, broadcast: bool) -> Result<(), BlockchainError> {
         debug!("add tx to mempool with hash {}", hash);
+        if !self.is_accepting_transactions() {
+            return Err(BlockchainError::MempoolClosed)
+        }
+
         let storage = self.storage.read().await;
         debug!("storage read acquired to add tx to mempool with hash");
         self.add_tx_to_mempool_with_storage_and_hash(&storage, tx, hash, broadcast).await
@@ -1530,49 +1825,23 @@ impl<S: Storage> Blockchain<S> {
     pub async fn add_tx_to_mempool_with_storage_and_hash(&self, storage: &S, tx: Arc<Transaction>, hash: Immutable<Hash>, broadcast: bool) -> Result<(), BlockchainError> {
         debug!("add tx to mempool with storage and hash {} (broadcast = {})", hash, broadcast);
         let tx_size = tx.size();
-        if tx_size > MAX_TRANSACTION_SIZE {
-            return Err(BlockchainError::TxTooBig(tx_size, MAX_TRANSACTION_SIZE))
-        }
+        Self::pre_verify_tx(storage, &hash, tx_size)?;
 
-        // check that the TX is not already in blockchain
-        if storage.is_tx_executed_in_a_block(&hash)? {
-            return Err(BlockchainError::TxAlreadyInBlockchain(hash.into_owned()))
-        }
-
-        let hash = {
+        let (hash, replaced) = {
             debug!("locking mempool to add tx");
             let mut mempool = self.mempool.write().await;
             debug!("mempool locked to add tx");
 
-            if mempool.contains_tx(&hash) {
-                return Err(BlockchainError::TxAlreadyInMempool(hash.into_owned()))
-            }
-
             let stable_topoheight = self.get_stable_topoheight();
             let current_topoheight = self.get_topo_height();
-            // get the highest nonce available
-            // if presents, it means we have at least one tx from this owner in mempool
-            if let Some(cache) = mempool.get_cache_for(tx.get_source()) {
-                // we accept to delete a tx from mempool if the new one has a higher fee
-                if let Some(hash2) = cache.has_tx_with_same_nonce(tx.get_nonce()) {
-                    // A TX with the same nonce is already in mempool
-                    debug!("TX {} nonce is already used by TX {}", hash, hash2);
-                    return Err(BlockchainError::TxNonceAlreadyUsed(tx.get_nonce(), hash2.as_ref().clone()))
-                }
-
-                // check that the nonce is in the range
-                if !(tx.get_nonce() <= cache.get_max() + 1 && tx.get_nonce() >= cache.get_min()) {
-                    debug!("TX {} nonce is not in the range of the pending TXs for this owner, received: {}, expected between {} and {}", hash, tx.get_nonce(), cache.get_min(), cache.get_max());
-                    return Err(BlockchainError::InvalidTxNonceMempoolCache(tx.get_nonce(), cache.get_min(), cache.get_max()))
-                }
-            }
+            let replaced = self.pre_verify_tx_for_mempool(&mempool, &tx, &hash)?;
 
             // Put the hash behind an Arc to share it cheaply
             let hash = hash.into_arc();
 
             let start = Instant::now();
             let version = get_version_at_height(self.get_network(), self.get_height());
-            mempool.add_tx(storage, &self.environment, stable_topoheight, current_topoheight, hash.clone(), tx.clone(), tx_size, version).await?;
+            mempool.add_tx(storage, &self.environment, stable_topoheight, current_topoheight, hash.clone(), tx.clone(), tx_size, version, replaced.as_deref()).await?;
 
             debug!("TX {} has been added to the mempool", hash);
 
@@ -1580,9 +1849,23 @@ impl<S: Storage> Blockchain<S> {
             histogram!("terminos_mempool_tx_added_ms").record(start.elapsed().as_millis() as f64);
             counter!("terminos_txs_verified").increment(1u64);
 
-            hash
+            (hash, replaced)
         };
 
+        if let Some(replaced) = replaced {
+            counter!("terminos_mempool_txs_replaced").increment(1u64);
+            if let Some(rpc) = self.rpc.read().await.as_ref() {
+                if rpc.is_event_tracked(&NotifyEvent::MempoolTransactionEvicted).await {
+                    let data = MempoolTransactionEvictedEvent {
+                        hash: Cow::Owned(replaced.as_ref().clone()),
+                        source: tx.get_source().as_address(self.network.is_mainnet()),
+                        reason: MempoolEvictionReason::Replaced,
+                    };
+                    rpc.notify_clients_with(&NotifyEvent::MempoolTransactionEvicted, data).await;
+                }
+            }
+        }
+
         if broadcast {
             debug!("broadcast new tx {} added in mempool", hash);
             // P2p broadcast to others peers
@@ -1629,6 +1912,82 @@ impl<S: Storage> Blockchain<S> {
         Ok(())
     }
 
+    // Verify a TX as if it was added to the mempool, without adding it
+    // Returns the same error as add_tx_to_mempool would
+    pub async fn verify_tx(&self, tx: Arc<Transaction>, hash: &Hash) -> Result<(), BlockchainError> {
+        debug!("verify tx {}", hash);
+        let storage = self.storage.read().await;
+        debug!("storage read acquired to verify tx");
+        Self::pre_verify_tx(&*storage, hash, tx.size())?;
+
+        debug!("locking mempool to verify tx");
+        let mempool = self.mempool.read().await;
+        debug!("mempool locked to verify tx");
+        let replaced = self.pre_verify_tx_for_mempool(&mempool, &tx, hash)?;
+
+        let stable_topoheight = self.get_stable_topoheight();
+        let current_topoheight = self.get_topo_height();
+        let version = get_version_at_height(self.get_network(), self.get_height());
+        mempool.verify_tx(&*storage, &self.environment, stable_topoheight, current_topoheight, hash, &tx, version, replaced.is_some()).await
+    }
+
+    // Checks done before locking the mempool
+    fn pre_verify_tx(storage: &S, hash: &Hash, tx_size: usize) -> Result<(), BlockchainError> {
+        if tx_size > MAX_TRANSACTION_SIZE {
+            return Err(BlockchainError::TxTooBig(tx_size, MAX_TRANSACTION_SIZE))
+        }
+
+        // check that the TX is not already in blockchain
+        if storage.is_tx_executed_in_a_block(hash)? {
+            return Err(BlockchainError::TxAlreadyInBlockchain(hash.clone()))
+        }
+
+        Ok(())
+    }
+
+    // Checks against the TXs already pending in the mempool
+    // Returns the hash of the pending TX to replace if the replace-by-fee policy allows it
+    fn pre_verify_tx_for_mempool(&self, mempool: &Mempool, tx: &Transaction, hash: &Hash) -> Result<Option<Arc<Hash>>, BlockchainError> {
+        if mempool.contains_tx(hash) {
+            return Err(BlockchainError::TxAlreadyInMempool(hash.clone()))
+        }
+
+        // get the highest nonce available
+        // if presents, it means we have at least one tx from this owner in mempool
+        if let Some(cache) = mempool.get_cache_for(tx.get_source()) {
+            // we accept to delete a tx from mempool if the new one has a higher fee
+            if let Some(hash2) = cache.has_tx_with_same_nonce(tx.get_nonce()) {
+                // A TX with the same nonce is already in mempool
+                debug!("TX {} nonce is already used by TX {}", hash, hash2);
+                let Some(min_increase) = self.replace_by_fee_min_increase else {
+                    return Err(BlockchainError::TxNonceAlreadyUsed(tx.get_nonce(), hash2.as_ref().clone()))
+                };
+
+                // Replacing a TX followed by others would invalidate
+                // the expected balances used by them
+                if cache.get_txs().len() != 1 {
+                    return Err(BlockchainError::TxReplacementNotAllowed(hash2.as_ref().clone()))
+                }
+
+                let fee = mempool.get_sorted_tx(hash2)?.get_fee();
+                let required_fee = fee.saturating_add((fee.saturating_mul(min_increase) / 100).max(1));
+                if tx.get_fee() < required_fee {
+                    return Err(BlockchainError::TxReplacementFeeTooLow(hash2.as_ref().clone(), required_fee, tx.get_fee()))
+                }
+
+                return Ok(Some(hash2.clone()))
+            }
+
+            // check that the nonce is in the range
+            if !(tx.get_nonce() <= cache.get_max() + 1 && tx.get_nonce() >= cache.get_min()) {
+                debug!("TX {} nonce is not in the range of the pending TXs for this owner, received: {}, expected between {} and {}", hash, tx.get_nonce(), cache.get_min(), cache.get_max());
+                return Err(BlockchainError::InvalidTxNonceMempoolCache(tx.get_nonce(), cache.get_min(), cache.get_max()))
+            }
+        }
+
+        Ok(None)
+    }
+
     // Get a block template for the new block work (mining)
     pub async fn get_block_template(&self, address: PublicKey) -> Result<BlockHeader, BlockchainError> {
         debug!("get block template");
@@ -1779,9 +2138,9 @@ impl<S: Storage> Blockchain<S> {
         }
 
         let mut sorted_tips = blockdag::sort_tips(storage, tips.into_iter()).await?;
-        if sorted_tips.len() > TIPS_LIMIT {
-            let dropped_tips = sorted_tips.drain(TIPS_LIMIT..); // keep only first 3 heavier tips
-            warn!("Dropping tips {} because they are not in the first 3 heavier tips", dropped_tips.map(|h| h.to_string()).collect::<Vec<String>>().join(", "));
+        if sorted_tips.len() > self.block_template_tips_limit {
+            let dropped_tips = sorted_tips.drain(self.block_template_tips_limit..); // keep only the heavier tips
+            warn!("Dropping tips {} because they are not in the first {} heavier tips", dropped_tips.map(|h| h.to_string()).collect::<Vec<String>>().join(", "), self.block_template_tips_limit);
         }
 
         // find the newest timestamp
@@ -1813,19 +2172,66 @@ impl<S: Storage> Blockchain<S> {
     // This function is called when a miner request a new block template
     // We create a block candidate with selected TXs from mempool
     pub async fn get_block_template_for_storage(&self, storage: &S, address: PublicKey) -> Result<BlockHeader, BlockchainError> {
-        let mut block = self.get_block_header_template_for_storage(storage, address).await?;
-
         trace!("Locking mempool for building block template");
         let mempool = self.mempool.read().await;
         trace!("Mempool locked for building block template");
 
+        self.get_block_template_with_mempool(storage, &mempool, address).await
+    }
+
+    // Simulate the next block template using only the provided TXs
+    // An ephemeral mempool is built from them against the current chain state,
+    // the live mempool is never touched
+    // Returns the block template and the TXs rejected with their error
+    pub async fn simulate_block_template(&self, address: PublicKey, txs: impl IntoIterator<Item = Arc<Transaction>>) -> Result<(BlockHeader, Vec<(Hash, BlockchainError)>), BlockchainError> {
+        debug!("locking storage for block template simulation");
+        let storage = self.storage.read().await;
+        debug!("storage read acquired for block template simulation");
+
+        let stable_topoheight = self.get_stable_topoheight();
+        let current_topoheight = self.get_topo_height();
+        let version = get_version_at_height(self.get_network(), self.get_height());
+
+        // Add the TXs per source in nonce order
+        let mut txs: Vec<_> = txs.into_iter()
+            .map(|tx| (tx.hash(), tx))
+            .collect();
+        txs.sort_by(|(_, a), (_, b)| a.get_source().as_bytes().cmp(b.get_source().as_bytes()).then(a.get_nonce().cmp(&b.get_nonce())));
+
+        let mut mempool = Mempool::new(self.network, self.disable_zkp_cache);
+        let mut rejected = Vec::new();
+        for (hash, tx) in txs {
+            if mempool.contains_tx(&hash) {
+                continue;
+            }
+
+            let tx_size = tx.size();
+            let res = match Self::pre_verify_tx(&*storage, &hash, tx_size) {
+                Ok(()) => mempool.add_tx(&*storage, &self.environment, stable_topoheight, current_topoheight, Arc::new(hash.clone()), tx, tx_size, version, None).await,
+                Err(e) => Err(e)
+            };
+
+            if let Err(e) = res {
+                debug!("TX {} rejected from block template simulation: {}", hash, e);
+                rejected.push((hash, e));
+            }
+        }
+
+        let block = self.get_block_template_with_mempool(&*storage, &mempool, address).await?;
+        Ok((block, rejected))
+    }
+
+    // Build a block template selecting the TXs from the given mempool
+    pub async fn get_block_template_with_mempool(&self, storage: &S, mempool: &Mempool, address: PublicKey) -> Result<BlockHeader, BlockchainError> {
+        let mut block = self.get_block_header_template_for_storage(storage, address).await?;
+
         let start = Instant::now();
 
         // use the mempool cache to get all availables txs grouped by account
         let caches = mempool.get_caches();
 
         // Build the tx selector using the mempool
-        let mut tx_selector = TxSelector::with_capacity(caches.len());
+        let mut tx_selector = TxSelector::with_capacity(caches.len(), self.tx_selection_strategy);
         for cache in caches.values() {
             let cache_txs = cache.get_txs();
             // Map every tx hash to a TxSelectorEntry
@@ -1835,7 +2241,8 @@ impl<S: Storage> Blockchain<S> {
                     Ok(TxSelectorEntry {
                         size: sorted_tx.get_size(),
                         hash: tx_hash,
-                        tx: sorted_tx.get_tx()
+                        tx: sorted_tx.get_tx(),
+                        first_seen: sorted_tx.get_first_seen()
                     })
                 })
                 .collect::<Result<VecDeque<_>, BlockchainError>>()?;
@@ -1856,7 +2263,7 @@ impl<S: Storage> Blockchain<S> {
         let mut chain_state = ChainState::new(storage, &self.environment, stable_topoheight, topoheight, block.get_version());
 
         if !tx_selector.is_empty() {
-            let tx_cache = TxCache::new(storage, &mempool, self.disable_zkp_cache);
+            let tx_cache = TxCache::new(storage, mempool, self.disable_zkp_cache);
             let mut failed_sources = HashSet::new();
             // Search all txs that were processed in tips
             // This help us to determine if a TX was already included or not based on our DAG
@@ -1892,7 +2299,7 @@ impl<S: Storage> Blockchain<S> {
                 }
             }
 
-            while let Some(TxSelectorEntry { size, hash, tx }) = tx_selector.next() {
+            while let Some(TxSelectorEntry { size, hash, tx, .. }) = tx_selector.next() {
                 if block_size + total_txs_size + size >= MAX_BLOCK_SIZE || block.txs_hashes.len() >= u16::MAX as usize {
                     debug!("Stopping to include new TXs in this block, final size: {}, count: {}", human_bytes::human_bytes((block_size + total_txs_size) as f64), block.txs_hashes.len());
                     break;
@@ -2274,6 +2681,9 @@ impl<S: Storage> Blockchain<S> {
                 // Track how much time it takes to verify them all
                 let start = Instant::now();
                 let stable_topoheight = self.get_stable_topoheight();
+                // Sum of the time spent by each batch, in microseconds
+                // Compared to the wall time, it gives the effective parallelism
+                let busy_micros = AtomicU64::new(0);
                 // If multi thread is enabled and we have more than one source
                 // Otherwise its not worth-it to move it on another thread
                 if self.txs_verification_threads_count > 1 && txs_grouped.len() > 1 && is_multi_threads_supported() {
@@ -2296,28 +2706,52 @@ impl<S: Storage> Blockchain<S> {
                     let storage = &*storage;
                     let environment = &self.environment;
                     let cache = &tx_cache;
+                    let busy_micros = &busy_micros;
 
                     // We run the batches in concurrent tasks
                     // But, because Transaction#verify_batch is actually spawning a blocking thread
                     // it will be multi-threaded by N threads
                     stream::iter(batches.into_iter().map(Ok))
                         .try_for_each_concurrent(self.txs_verification_threads_count, async |txs| {
+                            let batch_start = Instant::now();
                             let mut chain_state = ChainState::new(storage, environment, stable_topoheight, current_topoheight, version);
-                            Transaction::verify_batch(txs.iter(), &mut chain_state, cache).await
+                            let res = Transaction::verify_batch(txs.iter(), &mut chain_state, cache).await;
+
+                            let elapsed = batch_start.elapsed();
+                            busy_micros.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+                            histogram!("terminos_txs_verification_batch_ms").record(elapsed.as_millis() as f64);
+                            histogram!("terminos_txs_verification_batch_size").record(txs.len() as f64);
+
+                            res
                         }).await?;
+
+                    histogram!("terminos_txs_verification_batches").record(batches_count as f64);
                 } else {
                     // Verify all valid transactions in one batch
                     let mut chain_state = ChainState::new(&*storage, &self.environment, stable_topoheight, current_topoheight, version);
                     let iter = txs_grouped.values()
                         .flatten();
                     Transaction::verify_batch(iter, &mut chain_state, &tx_cache).await?;
+
+                    busy_micros.store(start.elapsed().as_micros() as u64, Ordering::Relaxed);
+                    histogram!("terminos_txs_verification_batches").record(1f64);
                 }
 
-                debug!("Verified {} transactions in {}ms", total_txs, start.elapsed().as_millis());
+                let elapsed = start.elapsed();
+                debug!("Verified {} transactions in {}ms", total_txs, elapsed.as_millis());
 
                 // Record metrics
                 counter!("terminos_txs_verified").increment(total_txs as u64);
-                histogram!("terminos_txs_verification_ms").record(start.elapsed().as_millis() as f64);
+                histogram!("terminos_txs_verification_ms").record(elapsed.as_millis() as f64);
+                histogram!("terminos_block_txs_verified").record(total_txs as f64);
+
+                // Effective parallelism achieved: total time spent in all batches / wall time
+                let wall_micros = elapsed.as_micros();
+                if wall_micros > 0 {
+                    let parallelism = busy_micros.into_inner() as f64 / wall_micros as f64;
+                    trace!("Effective TXs verification parallelism: {:.2}", parallelism);
+                    histogram!("terminos_txs_verification_parallelism").record(parallelism);
+                }
             }
         }
 
@@ -2424,6 +2858,13 @@ impl<S: Storage> Blockchain<S> {
         // We keep in order all orphaned txs to try to re-add them in the mempool
         let mut orphaned_transactions = IndexSet::new();
 
+        // Track the reorg to notify it in one event
+        let reorg_event_tracked = should_track_events.contains(&NotifyEvent::ChainReorg);
+        // First topoheight that got cleaned
+        let mut reorg_topoheight = None;
+        let mut orphaned_blocks = Vec::new();
+        let mut applied_blocks = Vec::new();
+
         // order the DAG (up to TOP_HEIGHT - STABLE_LIMIT)
         let mut highest_topo = 0;
         // Tells if the new block added is ordered in DAG or not
@@ -2456,6 +2897,9 @@ impl<S: Storage> Blockchain<S> {
                     }
 
                     debug!("Cleaning transactions executions at topo height {} (block {})", topoheight, hash_at_topo);
+                    if reorg_topoheight.is_none() {
+                        reorg_topoheight = Some(topoheight);
+                    }
 
                     let block = storage.get_block_header_by_hash(&hash_at_topo).await?;
 
@@ -2470,6 +2914,10 @@ impl<S: Storage> Blockchain<S> {
                         events.entry(NotifyEvent::BlockOrphaned).or_insert_with(Vec::new).push(value);
                     }
 
+                    if is_orphaned && reorg_event_tracked {
+                        orphaned_blocks.push(hash_at_topo.clone());
+                    }
+
                     // mark txs as unexecuted if it was executed in this block
                     for tx_hash in block.get_txs_hashes() {
                         if storage.is_tx_executed_in_block(tx_hash, &hash_at_topo)? {
@@ -2496,6 +2944,17 @@ impl<S: Storage> Blockchain<S> {
                 }
             }
 
+            // Retrieve the common base before the new order overwrite the topoheights
+            // A reorg is only notified if at least one block got orphaned
+            let reorg_base = match reorg_topoheight.filter(|_| !orphaned_blocks.is_empty()) {
+                Some(topoheight) if topoheight > 0 => {
+                    let common_base_topoheight = topoheight - 1;
+                    let common_base_hash = storage.get_hash_at_topo_height(common_base_topoheight).await?;
+                    Some((common_base_hash, common_base_topoheight))
+                },
+                _ => None
+            };
+
             // This is used to verify that each nonce is used only one time
             let mut nonce_checker = NonceChecker::new();
             // Side blocks counter per height
@@ -2517,6 +2976,9 @@ impl<S: Storage> Blockchain<S> {
                 is_written = true;
 
                 trace!("Ordering block {} at topoheight {}", hash, highest_topo);
+                if reorg_event_tracked {
+                    applied_blocks.push(hash.clone());
+                }
 
                 storage.set_topo_height_for_block(&hash, highest_topo).await?;
                 let (past_emitted_supply, past_burned_supply) = if highest_topo == 0 {
@@ -2711,6 +3173,28 @@ impl<S: Storage> Blockchain<S> {
                         }
                     }
 
+                    if should_track_events.contains(&NotifyEvent::AssetUpdated) {
+                        let assets_changes = chain_state.get_assets_changes();
+                        let entry = events.entry(NotifyEvent::AssetUpdated)
+                            .or_insert_with(Vec::new);
+
+                        for asset in contract_tracker.assets_updated.iter() {
+                            let Some(Some(changes)) = assets_changes.get(asset) else {
+                                warn!("Asset {} was updated but no changes were found", asset);
+                                continue;
+                            };
+
+                            let value = json!(AssetUpdatedEvent {
+                                asset: Cow::Borrowed(asset),
+                                data: Cow::Borrowed(&changes.data.1),
+                                block_hash: Cow::Borrowed(&hash),
+                                topoheight: highest_topo,
+                            });
+
+                            entry.push(value);
+                        }
+                    }
+
                     for (key, assets) in contract_tracker.transfers.iter() {
                         let event = NotifyEvent::ContractTransfer { address: key.as_address(is_mainnet) };
                         if should_track_events.contains(&event) {
@@ -2771,6 +3255,17 @@ impl<S: Storage> Blockchain<S> {
                 }
             }
 
+            if let Some((common_base_hash, common_base_topoheight)) = reorg_base {
+                debug!("DAG reorg from {} at topoheight {}: {} blocks orphaned, {} blocks applied", common_base_hash, common_base_topoheight, orphaned_blocks.len(), applied_blocks.len());
+                let value = json!(ChainReorgEvent {
+                    common_base_hash: Cow::Owned(common_base_hash),
+                    common_base_topoheight,
+                    orphaned: orphaned_blocks,
+                    applied: applied_blocks,
+                });
+                events.entry(NotifyEvent::ChainReorg).or_insert_with(Vec::new).push(value);
+            }
+
             let elapsed = Duration::from_micros(total_txs_execution_time as _);
             debug!("Executed {} TXs in {:?}", total_txs_executed, elapsed);
 
@@ -2888,8 +3383,9 @@ impl<S: Storage> Blockchain<S> {
             self.set_difficulty(difficulty).await;
         }
 
-        // Check if the event is tracked
+        // Check if the events are tracked
         let orphan_event_tracked = should_track_events.contains(&NotifyEvent::TransactionOrphaned);
+        let evicted_event_tracked = should_track_events.contains(&NotifyEvent::MempoolTransactionEvicted);
 
         // Clean mempool from old txs if the DAG has been updated
         let mempool_deleted_txs = {
@@ -2904,12 +3400,12 @@ impl<S: Storage> Blockchain<S> {
             res
         };
 
-        if orphan_event_tracked {
-            for (tx_hash, sorted_tx) in mempool_deleted_txs {
+        if orphan_event_tracked || evicted_event_tracked {
+            for (tx_hash, sorted_tx, reason) in mempool_deleted_txs {
                 // Delete it from our orphaned transactions list
                 // This save some performances as it will not try to add it back and
                 // consume resources for verifying the ZK Proof if we already know the answer
-                if orphaned_transactions.shift_remove(tx_hash.as_ref()) {
+                if orphan_event_tracked && orphaned_transactions.shift_remove(tx_hash.as_ref()) {
                     debug!("Transaction {} was marked as orphaned, but got deleted from mempool. Prevent adding it back", tx_hash);
                 }
 
@@ -2919,6 +3415,19 @@ impl<S: Storage> Blockchain<S> {
                     continue;
                 }
 
+                if evicted_event_tracked {
+                    let data = MempoolTransactionEvictedEvent {
+                        hash: Cow::Borrowed(&tx_hash),
+                        source: sorted_tx.get_tx().get_source().as_address(storage.is_mainnet()),
+                        reason,
+                    };
+                    events.entry(NotifyEvent::MempoolTransactionEvicted).or_insert_with(Vec::new).push(json!(data));
+                }
+
+                if !orphan_event_tracked {
+                    continue;
+                }
+
                 let data = RPCTransaction::from_tx(&sorted_tx.get_tx(), &tx_hash, storage.is_mainnet());
                 let data = TransactionResponse {
                     blocks: None,
@@ -3205,7 +3714,7 @@ impl<S: Storage> Blockchain<S> {
             0
         };
 
-        for hash in self.checkpoints.iter() {
+        for hash in self.checkpoints.read().await.iter() {
             if storage.is_block_topological_ordered(hash).await? {
                 let topo = storage.get_topo_height_for_hash(hash).await?;
                 if until_topo_height <= topo {
@@ -2,6 +2,7 @@ use std::time::Duration;
 use humantime::Duration as HumanDuration;
 use serde::{Deserialize, Serialize};
 use terminos_common::{
+    config::TIPS_LIMIT,
     crypto::Hash,
     prompt::LogLevel,
     utils::detect_available_parallelism
@@ -12,7 +13,7 @@ use crate::{
     p2p::diffie_hellman::{KeyVerificationAction, WrappedSecret}
 };
 
-use super::{simulator::Simulator, storage::rocksdb::{CacheMode, CompressionMode}};
+use super::{simulator::Simulator, storage::rocksdb::{CacheMode, CompressionMode}, tx_selector::SelectionStrategy};
 
 // Functions helpers for serde default values
 fn default_p2p_bind_address() -> String {
@@ -27,6 +28,10 @@ const fn default_max_outgoing_peers() -> usize {
     P2P_DEFAULT_MAX_OUTGOING_PEERS
 }
 
+const fn default_max_inbound_peers() -> usize {
+    P2P_DEFAULT_MAX_INBOUND_PEERS
+}
+
 fn default_rpc_bind_address() -> String {
     DEFAULT_RPC_BIND_ADDRESS.to_owned()
 }
@@ -59,6 +64,34 @@ const fn default_p2p_fail_count_limit() -> u8 {
     PEER_FAIL_LIMIT
 }
 
+const fn default_p2p_handshake_timeout_ms() -> u64 {
+    PEER_TIMEOUT_INIT_CONNECTION
+}
+
+const fn default_p2p_send_queue_limit() -> usize {
+    PEER_PACKET_CHANNEL_SIZE
+}
+
+const fn default_p2p_unsolicited_responses_limit() -> u32 {
+    PEER_UNSOLICITED_RESPONSES_LIMIT
+}
+
+const fn default_p2p_object_request_timeout_ms() -> u64 {
+    PEER_TIMEOUT_REQUEST_OBJECT
+}
+
+const fn default_chain_sync_jitter_percent() -> u8 {
+    CHAIN_SYNC_DEFAULT_JITTER_PERCENT
+}
+
+const fn default_block_request_retries() -> u8 {
+    P2P_DEFAULT_BLOCK_REQUEST_RETRIES
+}
+
+const fn default_block_template_tips_limit() -> usize {
+    TIPS_LIMIT
+}
+
 const fn debug_log_level() -> LogLevel {
     LogLevel::Debug
 }
@@ -142,6 +175,22 @@ pub struct RPCConfig {
     #[clap(name = "rpc-notify-events-concurrency", long, default_value_t = detect_available_parallelism())]
     #[serde(default = "detect_available_parallelism")]
     pub notify_events_concurrency: usize,
+    /// Username required to access the admin RPC endpoint.
+    /// The admin endpoint is only enabled when both username and password are set.
+    #[clap(name = "rpc-admin-username", long)]
+    #[serde(default)]
+    pub admin_username: Option<String>,
+    /// Password required to access the admin RPC endpoint.
+    #[clap(name = "rpc-admin-password", long)]
+    #[serde(default)]
+    pub admin_password: Option<String>,
+    /// Minimum connected peers required before answering the balance and nonce queries.
+    /// When set, the node must also be synced with its peers.
+    /// This prevents the wallets from trusting the stale data of an unsynced node.
+    /// By default, the queries are always answered.
+    #[clap(name = "rpc-ready-min-peers", long)]
+    #[serde(default)]
+    pub ready_min_peers: Option<usize>,
 }
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum, Serialize, Deserialize, strum::Display)]
@@ -153,6 +202,36 @@ pub enum ProxyKind {
     Socks4,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxySelection {
+    /// Use each proxy in turn
+    #[default]
+    #[clap(name = "round-robin")]
+    RoundRobin,
+    /// Pick a random proxy for each connection
+    #[clap(name = "random")]
+    Random,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerSelectionStrategy {
+    /// Select a peer uniformly at random between all candidates
+    #[clap(name = "uniform")]
+    Uniform,
+    /// Prefer peers with a higher cumulative difficulty delta,
+    /// a better measured rate and a lower fail count
+    #[clap(name = "weighted-by-rate")]
+    WeightedByRate,
+}
+
+impl Default for PeerSelectionStrategy {
+    fn default() -> Self {
+        Self::Uniform
+    }
+}
+
 #[derive(Debug, Clone, clap::Args, Serialize, Deserialize)]
 pub struct ProxyConfig {
     /// Configure a proxy address to be used
@@ -161,6 +240,17 @@ pub struct ProxyConfig {
     #[clap(name = "p2p-proxy-address", long)]
     #[serde(default)]
     pub address: Option<String>,
+    /// Additional proxies addresses to use along the `proxy-address`.
+    /// Each outgoing connection is made through one proxy of the pool.
+    /// All proxies share the same protocol and credentials.
+    /// This is useful to spread the connections across multiple Tor circuits.
+    #[clap(name = "p2p-proxy-pool", long)]
+    #[serde(default)]
+    pub pool: Vec<String>,
+    /// How a proxy is selected from the pool for each outgoing connection
+    #[clap(name = "p2p-proxy-selection", long, value_enum, default_value_t)]
+    #[serde(default)]
+    pub selection: ProxySelection,
     /// Proxy protocol to use when connecting to a peer
     #[clap(name = "p2p-proxy", long)]
     pub kind: Option<ProxyKind>,
@@ -195,6 +285,14 @@ pub struct P2pConfig {
     #[clap(name = "p2p-max-outgoing-peers", long, default_value_t = default_max_outgoing_peers())]
     #[serde(default = "default_max_outgoing_peers")]
     pub max_outgoing_peers: usize,
+    /// Set a maximum of P2P inbound peers.
+    /// 
+    /// This prevents the node from being saturated by incoming connections
+    /// and keeps free slots for our outgoing peers.
+    /// It is capped by the maximum peers allowed.
+    #[clap(name = "p2p-max-inbound-peers", long, default_value_t = default_max_inbound_peers())]
+    #[serde(default = "default_max_inbound_peers")]
+    pub max_inbound_peers: usize,
     /// Add a priority node to connect when P2p is started.
     /// A priority node is connected only one time.
     #[clap(long)]
@@ -316,6 +414,103 @@ pub struct P2pConfig {
     #[clap(name = "p2p-handle-peer-packets-in-dedicated-task", long)]
     #[serde(default)]
     pub handle_peer_packets_in_dedicated_task: bool,
+    /// Limit the upload rate to each peer in bytes per second.
+    /// This is useful for nodes running on metered connections.
+    /// By default, it is set to 0 (no limit).
+    #[clap(name = "p2p-peer-rate-limit", long, default_value_t = 0)]
+    #[serde(default)]
+    pub peer_rate_limit: u64,
+    /// Timeout in milliseconds to wait on the handshake of a peer.
+    /// This is only applied to the handshake packet and is separated
+    /// from the outgoing connection timeout.
+    /// Increase it on high-latency networks (such as Tor through a proxy).
+    #[clap(name = "p2p-handshake-timeout-ms", long, default_value_t = default_p2p_handshake_timeout_ms())]
+    #[serde(default = "default_p2p_handshake_timeout_ms")]
+    pub handshake_timeout_ms: u64,
+    /// Strategy used to select the peer to sync the chain from.
+    /// By default, a peer is selected uniformly at random.
+    #[clap(name = "p2p-peer-selection-strategy", long, value_enum, default_value_t)]
+    #[serde(default)]
+    pub peer_selection_strategy: PeerSelectionStrategy,
+    /// Maximum random jitter applied to the chain sync delay, in percent.
+    /// 
+    /// This spreads the chain sync requests of nodes started at the same time.
+    /// It can't be above 30% to respect the minimum delay expected by our peers.
+    #[clap(name = "p2p-chain-sync-jitter-percent", long, default_value_t = default_chain_sync_jitter_percent())]
+    #[serde(default = "default_chain_sync_jitter_percent")]
+    pub chain_sync_jitter_percent: u8,
+    /// Connect to others peers in observer mode.
+    /// 
+    /// Peers will still propagate us blocks and transactions,
+    /// but we will never be asked to serve chain or object requests.
+    /// We are also not shared to others nodes.
+    #[clap(name = "p2p-observer", long)]
+    #[serde(default)]
+    pub observer: bool,
+    /// Use a fixed peer id instead of a random one.
+    /// 
+    /// This is mostly useful for tests to get reproducible logs.
+    /// It must be unique in the network and greater than 0.
+    #[clap(name = "p2p-peer-id", long)]
+    #[serde(default)]
+    pub peer_id: Option<u64>,
+    /// Disable the compression of P2P packets.
+    /// 
+    /// By default, large packets (blocks, chain responses...) are compressed
+    /// using zstd when the peer supports it too.
+    #[clap(name = "p2p-disable-compression", long)]
+    #[serde(default)]
+    pub disable_compression: bool,
+    /// How many times a failed propagated block request is retried.
+    /// 
+    /// Each retry is done with another peer that propagated the block to us.
+    #[clap(name = "p2p-block-request-retries", long, default_value_t = default_block_request_retries())]
+    #[serde(default = "default_block_request_retries")]
+    pub block_request_retries: u8,
+    /// Race the IPv4 and IPv6 addresses of dual stack exclusive nodes.
+    /// 
+    /// When an exclusive node host resolves to both families,
+    /// both are connected concurrently and the first one ready is kept.
+    /// This is not used when a proxy is configured.
+    #[clap(name = "p2p-happy-eyeballs", long)]
+    #[serde(default)]
+    pub happy_eyeballs: bool,
+    /// IP addresses that are never banned due to their fail count.
+    /// 
+    /// Unlike exclusive nodes, it doesn't restrict who can connect to us,
+    /// and unlike priority nodes, it doesn't change how we propagate to them.
+    /// Useful for monitoring nodes.
+    #[clap(name = "p2p-fail-count-whitelist", long)]
+    #[serde(default)]
+    pub fail_count_whitelist: Vec<String>,
+    /// Reject the chain requests asking for more blocks than our maximum chain response size.
+    /// 
+    /// By default, the response is clamped to our limit.
+    /// This is useful to detect peers with a misconfigured limit.
+    #[clap(name = "p2p-strict-chain-response-size", long)]
+    #[serde(default)]
+    pub strict_chain_response_size: bool,
+    /// Maximum packets waiting to be sent to a peer.
+    /// 
+    /// If a peer is too slow and its send queue reaches this limit,
+    /// it is considered unhealthy and disconnected instead of buffering more packets.
+    #[clap(name = "p2p-send-queue-limit", long, default_value_t = default_p2p_send_queue_limit())]
+    #[serde(default = "default_p2p_send_queue_limit")]
+    pub send_queue_limit: usize,
+    /// Maximum unsolicited responses accepted from a peer before temp banning it.
+    /// 
+    /// Object, chain and bootstrap chain responses that we never requested are counted.
+    /// This is tracked separately from the fail count.
+    #[clap(name = "p2p-unsolicited-responses-limit", long, default_value_t = default_p2p_unsolicited_responses_limit())]
+    #[serde(default = "default_p2p_unsolicited_responses_limit")]
+    pub unsolicited_responses_limit: u32,
+    /// Maximum time in milliseconds to wait for an object requested through the object tracker.
+    /// 
+    /// A timed out transaction requested for a propagated block
+    /// is requested again from another peer that propagated the block.
+    #[clap(name = "p2p-object-request-timeout-ms", long, default_value_t = default_p2p_object_request_timeout_ms())]
+    #[serde(default = "default_p2p_object_request_timeout_ms")]
+    pub object_request_timeout_ms: u64,
 }
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum, Serialize, Deserialize)]
@@ -424,6 +619,12 @@ pub struct Config {
     /// It must ends with a slash.
     #[clap(long)]
     pub dir_path: Option<String>,
+    /// Import a snapshot directory created by the create_snapshot admin RPC method at startup.
+    /// 
+    /// The snapshot network, genesis and checksum are verified before being adopted.
+    /// This is only supported by RocksDB and requires an empty DB directory.
+    #[clap(long)]
+    pub import_snapshot: Option<String>,
     /// Enable the simulator (skip PoW verification, generate a new block for every BLOCK_TIME).
     #[clap(long)]
     pub simulator: Option<Simulator>,
@@ -437,14 +638,49 @@ pub struct Config {
     /// before the top.
     #[clap(long)]
     pub auto_prune_keep_n_blocks: Option<u64>,
+    /// Contracts hashes for which the versioned data
+    /// (modules, storage and balances) is never pruned.
+    /// Each retained contract keeps its full history on disk,
+    /// so the disk usage grows with every change made by it.
+    /// Only supported by the RocksDB backend.
+    #[clap(long)]
+    #[serde(default)]
+    pub prune_retained_contracts: Vec<Hash>,
     /// Skip the TXs verification when building a block template.
     #[clap(long)]
     #[serde(default)]
     pub skip_block_template_txs_verification: bool,
+    /// Strategy used to select the TXs from the mempool when building a block template.
+    /// By default, the TXs with the highest fees are selected first.
+    #[clap(name = "block-template-tx-selection-strategy", long, value_enum, default_value_t)]
+    #[serde(default)]
+    pub block_template_tx_selection_strategy: SelectionStrategy,
+    /// Maximum tips referenced by a block template.
+    /// This allows to study the DAG behavior under different merge widths.
+    /// It can't be above the consensus tips limit.
+    #[clap(name = "block-template-tips-limit", long, default_value_t = default_block_template_tips_limit())]
+    #[serde(default = "default_block_template_tips_limit")]
+    pub block_template_tips_limit: usize,
+    /// Enable the replace-by-fee policy of the mempool.
+    /// A TX using the same nonce as a pending one is accepted
+    /// if its fee is higher by at least this percentage, evicting the pending TX.
+    /// Only the single pending TX of a sender can be replaced.
+    /// By default, a TX reusing a pending nonce is rejected.
+    #[clap(name = "mempool-replace-by-fee-min-increase", long)]
+    #[serde(default)]
+    pub mempool_replace_by_fee_min_increase: Option<u64>,
     /// Use the hexadecimal representation of the genesis block for the dev mode.
     /// This is useful for testing and development.
     #[clap(long)]
     pub genesis_block_hex: Option<String>,
+    /// Expected genesis block hash, overriding the hardcoded one of the network.
+    /// This allows to run an isolated private network.
+    /// It is verified against the stored genesis block at startup
+    /// and used to verify the peers handshakes.
+    /// Not available on mainnet.
+    #[clap(long)]
+    #[serde(default)]
+    pub genesis_block_hash: Option<Hash>,
     /// Blocks hashes checkpoints
     /// No rewind can go below any of those checkpoints
     #[serde(default)]
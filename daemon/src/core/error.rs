@@ -15,6 +15,7 @@ use terminos_common::{
     account::Nonce,
     block::TopoHeight,
     difficulty::DifficultyError,
+    network::Network,
     prompt::PromptError,
     rpc::InternalRpcError,
     serializer::ReaderError,
@@ -380,6 +381,34 @@ pub enum BlockchainError {
     UnknownAccount,
     #[error(transparent)]
     SemaphoreError(#[from] AcquireError),
+    #[error("A storage compaction is already in progress")]
+    CompactionInProgress,
+    #[error("Simulator is not enabled on this node")]
+    SimulatorDisabled,
+    #[error("Checkpoint {} at topoheight {} is above the stable topoheight {}", _0, _1, _2)]
+    CheckpointNotStable(Hash, TopoHeight, TopoHeight),
+    #[error("Cannot rewind to topoheight {}, it must be below the current topoheight {}", _0, _1)]
+    InvalidRewindTopoHeight(TopoHeight, TopoHeight),
+    #[error("Cannot rewind to topoheight {}, chain is pruned until topoheight {}", _0, _1)]
+    RewindBelowPrunedTopoHeight(TopoHeight, TopoHeight),
+    #[error("Cannot rewind below checkpoint {} at topoheight {}", _0, _1)]
+    RewindBelowCheckpoint(Hash, TopoHeight),
+    #[error("Tx {} can't be replaced, only the single pending tx of a sender can be replaced", _0)]
+    TxReplacementNotAllowed(Hash),
+    #[error("Replacement fee is too low to replace Tx {}, expected at least {} but got {}", _0, _1, _2)]
+    TxReplacementFeeTooLow(Hash, u64, u64),
+    #[error("Mempool is closed, new transactions are not accepted")]
+    MempoolClosed,
+    #[error("DB directory is not empty, a snapshot can only be imported on a fresh node")]
+    SnapshotTargetNotEmpty,
+    #[error("Snapshot is for network {}, expected {}", _0, _1)]
+    SnapshotNetworkMismatch(Network, Network),
+    #[error("Snapshot genesis hash mismatch, expected {} got {}", _0, _1)]
+    SnapshotGenesisMismatch(Hash, Hash),
+    #[error("Snapshot checksum mismatch, expected {} got {}", _0, _1)]
+    SnapshotChecksumMismatch(Hash, Hash),
+    #[error("Snapshot topoheight mismatch, expected {} got {}", _0, _1)]
+    SnapshotTopoHeightMismatch(TopoHeight, TopoHeight),
 }
 
 impl BlockchainError {
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use thiserror::Error;
 use terminos_common::{
     api::daemon::HardFork,
     block::{Algorithm, BlockVersion},
@@ -73,17 +74,95 @@ pub fn is_version_matching_requirement(version: &str, req: &str) -> Result<bool>
 }
 
 // This function checks if a version is allowed at a given height
+// An invalid version is returned as an error
 pub fn is_version_allowed_at_height(network: &Network, height: u64, version: &str) -> Result<bool> {
-    for hard_fork in get_hard_forks(network) {
-        if let Some(req) = hard_fork.version_requirement.filter(|_| hard_fork.height <= height) {
-            let matches = is_version_matching_requirement(version, req)?;
-            if !matches {
-                return Ok(false);
+    match check_peer_compatibility(network, height, version) {
+        Ok(()) => Ok(true),
+        Err(e @ IncompatibilityReason::InvalidVersion(_)) => Err(e.into()),
+        Err(_) => Ok(false)
+    }
+}
+
+// Reason for which a peer is not compatible with us
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IncompatibilityReason {
+    #[error("peer network {} doesn't match our network {}", peer, expected)]
+    NetworkMismatch {
+        expected: Network,
+        peer: Network
+    },
+    #[error("peer version {} is too old, requirement is {}", version, requirement)]
+    TooOld {
+        version: String,
+        requirement: &'static str
+    },
+    #[error("peer version {} is too new, requirement is {}", version, requirement)]
+    TooNew {
+        version: String,
+        requirement: &'static str
+    },
+    #[error("peer version {} is invalid", _0)]
+    InvalidVersion(String)
+}
+
+// Check if a peer running on `peer_network` can connect to our `network`
+pub fn check_peer_network(network: &Network, peer_network: &Network) -> Result<(), IncompatibilityReason> {
+    if network != peer_network {
+        return Err(IncompatibilityReason::NetworkMismatch {
+            expected: *network,
+            peer: *peer_network
+        });
+    }
+
+    Ok(())
+}
+
+// Check a parsed peer version against a hard fork requirement
+// and report in which direction it failed
+fn check_version_requirement(version: &str, v: &semver::Version, requirement: &'static str) -> Result<(), IncompatibilityReason> {
+    // Requirements are hardcoded in the config, they must always be valid
+    let req = semver::VersionReq::parse(requirement)
+        .expect("invalid hard fork version requirement");
+
+    // Find the first comparator rejecting us to know in which direction we failed
+    if let Some(comparator) = req.comparators.iter().find(|c| !c.matches(v)) {
+        let too_new = match comparator.op {
+            semver::Op::Less | semver::Op::LessEq => true,
+            semver::Op::Greater | semver::Op::GreaterEq => false,
+            // Exact, tilde, caret and wildcard requirements reject both sides
+            _ => {
+                let base = (comparator.major, comparator.minor.unwrap_or(0), comparator.patch.unwrap_or(0));
+                (v.major, v.minor, v.patch) > base
             }
+        };
+
+        return Err(if too_new {
+            IncompatibilityReason::TooNew { version: version.to_owned(), requirement }
+        } else {
+            IncompatibilityReason::TooOld { version: version.to_owned(), requirement }
+        });
+    }
+
+    Ok(())
+}
+
+// Check if a peer running `version` is compatible with our `network` at the given height
+// This is the same check done during the P2P handshake, after the network check
+pub fn check_peer_compatibility(network: &Network, height: u64, version: &str) -> Result<(), IncompatibilityReason> {
+    let str_version = match version.split_once('-') {
+        Some((v, _)) => v,
+        None => version
+    };
+    let v = semver::Version::parse(str_version)
+        .map_err(|_| IncompatibilityReason::InvalidVersion(version.to_owned()))?;
+
+    for hard_fork in get_hard_forks(network) {
+        if let Some(requirement) = hard_fork.version_requirement.filter(|_| hard_fork.height <= height) {
+            check_version_requirement(version, &v, requirement)?;
         }
     }
 
-    Ok(true)
+    Ok(())
 }
 
 // Verify if the BlockVersion is/was enabled at a given height
@@ -140,6 +219,44 @@ mod tests {
             assert!(allowed);
         }
 
+        // An invalid version is always rejected, like during the handshake
+        assert!(is_version_allowed_at_height(&Network::Mainnet, 0, "not a version").is_err());
+    }
+
+    #[test]
+    fn test_check_peer_compatibility() {
+        assert!(check_peer_compatibility(&Network::Mainnet, 0, "1.0.0-abcdef").is_ok());
+        assert!(matches!(
+            check_peer_compatibility(&Network::Mainnet, 0, "not a version"),
+            Err(IncompatibilityReason::InvalidVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_version_requirement_direction() {
+        let check = |version: &str, requirement: &'static str| {
+            let v = semver::Version::parse(version).unwrap();
+            check_version_requirement(version, &v, requirement)
+        };
+
+        assert!(check("1.2.0", ">=1.0.0").is_ok());
+        assert!(matches!(check("0.9.0", ">=1.0.0"), Err(IncompatibilityReason::TooOld { .. })));
+        assert!(matches!(check("1.0.0", "<1.0.0"), Err(IncompatibilityReason::TooNew { .. })));
+        assert!(matches!(check("2.0.0", "^1.2.0"), Err(IncompatibilityReason::TooNew { .. })));
+        assert!(matches!(check("1.1.0", "^1.2.0"), Err(IncompatibilityReason::TooOld { .. })));
+        assert!(matches!(check("1.3.0", "~1.2.0"), Err(IncompatibilityReason::TooNew { .. })));
+        assert!(matches!(check("1.2.1", "=1.2.0"), Err(IncompatibilityReason::TooNew { .. })));
+        assert!(matches!(check("1.1.9", "=1.2.0"), Err(IncompatibilityReason::TooOld { .. })));
+        assert!(matches!(check("1.0.0", ">=1.0.0, <1.0.0"), Err(IncompatibilityReason::TooNew { .. })));
+    }
+
+    #[test]
+    fn test_check_peer_network() {
+        assert!(check_peer_network(&Network::Mainnet, &Network::Mainnet).is_ok());
+        assert_eq!(
+            check_peer_network(&Network::Mainnet, &Network::Testnet),
+            Err(IncompatibilityReason::NetworkMismatch { expected: Network::Mainnet, peer: Network::Testnet })
+        );
     }
 
     #[test]
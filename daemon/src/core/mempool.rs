@@ -15,7 +15,7 @@ use indexmap::IndexSet;
 use log::{debug, info, trace, warn};
 use terminos_common::{
     account::Nonce,
-    api::daemon::FeeRatesEstimated,
+    api::daemon::{FeeRatesEstimated, MempoolEvictionReason},
     block::{BlockVersion, TopoHeight},
     config::{BYTES_PER_KB, FEE_PER_KB},
     crypto::{
@@ -138,11 +138,37 @@ impl Mempool {
         Ok(Self::internal_estimate_fee_rates(fee_rates))
     }
 
+    // Compute the fee per kB rate of a TX based on its size
+    pub fn compute_fee_rate_per_kb(fee: u64, size: usize) -> u64 {
+        fee.saturating_mul(BYTES_PER_KB as u64) / size.max(1) as u64
+    }
+
+    // Estimate the fee per kB rates from the distribution of the provided fee rates
+    // low is the 25th percentile, medium the median and high the 90th percentile
+    // No rate can be below the fee per kB required by the network
+    pub fn estimate_fee_rates_from_distribution(mut fee_rates: Vec<u64>) -> FeeRatesEstimated {
+        fee_rates.sort_unstable();
+
+        let len = fee_rates.len();
+        // Nearest-rank percentile
+        let percentile = |p: usize| if len == 0 {
+            FEE_PER_KB
+        } else {
+            fee_rates[(len * p).div_ceil(100).max(1) - 1].max(FEE_PER_KB)
+        };
+
+        FeeRatesEstimated {
+            low: percentile(25),
+            medium: percentile(50),
+            high: percentile(90),
+            default: FEE_PER_KB
+        }
+    }
+
     // All checks are made in Blockchain before calling this function
-    pub async fn add_tx<S: Storage>(&mut self, storage: &S, environment: &Environment, stable_topoheight: TopoHeight, topoheight: TopoHeight, hash: Arc<Hash>, tx: Arc<Transaction>, size: usize, block_version: BlockVersion) -> Result<(), BlockchainError> {
-        let mut state = MempoolState::new(&self, storage, environment, stable_topoheight, topoheight, block_version, self.mainnet);
-        let tx_cache = TxCache::new(storage, self, self.disable_zkp_cache);
-        tx.verify(&hash, &mut state, &tx_cache).await?;
+    // If a TX to replace is provided, it must be the only pending TX of the sender
+    pub async fn add_tx<S: Storage>(&mut self, storage: &S, environment: &Environment, stable_topoheight: TopoHeight, topoheight: TopoHeight, hash: Arc<Hash>, tx: Arc<Transaction>, size: usize, block_version: BlockVersion, replaced: Option<&Hash>) -> Result<(), BlockchainError> {
+        let mut state = self.verify_tx_with_state(storage, environment, stable_topoheight, topoheight, &hash, &tx, block_version, replaced.is_some()).await?;
 
         let (balances, multisig) = state.get_sender_cache(tx.get_source())
             .ok_or_else(|| BlockchainError::AccountNotFound(tx.get_source().as_address(self.mainnet)))?;
@@ -151,6 +177,12 @@ impl Mempool {
             .map(|(asset, ciphertext)| (asset.clone(), ciphertext))
             .collect();
 
+        // Remove the replaced TX, the sender cache is rebuilt from the new TX
+        if let Some(replaced) = replaced {
+            debug!("TX {} is replaced by TX {}", replaced, hash);
+            self.remove_tx(replaced)?;
+        }
+
         let nonce = tx.get_nonce();
         // update the cache for this owner
         if let Some(cache) = self.caches.get_mut(tx.get_source()) {
@@ -188,6 +220,25 @@ impl Mempool {
         Ok(())
     }
 
+    // Verify a TX against the chain state and the pending TXs without adding it
+    pub async fn verify_tx<S: Storage>(&self, storage: &S, environment: &Environment, stable_topoheight: TopoHeight, topoheight: TopoHeight, hash: &Hash, tx: &Arc<Transaction>, block_version: BlockVersion, replacement: bool) -> Result<(), BlockchainError> {
+        self.verify_tx_with_state(storage, environment, stable_topoheight, topoheight, hash, tx, block_version, replacement).await?;
+        Ok(())
+    }
+
+    // Verify a TX and returns the state containing the sender balances updated by it
+    // In case of a replacement, the sender pending TX is ignored and the TX is verified against the storage only
+    async fn verify_tx_with_state<'a, S: Storage>(&'a self, storage: &'a S, environment: &'a Environment, stable_topoheight: TopoHeight, topoheight: TopoHeight, hash: &'a Hash, tx: &'a Arc<Transaction>, block_version: BlockVersion, replacement: bool) -> Result<MempoolState<'a, S>, BlockchainError> {
+        let mut state = MempoolState::new(self, storage, environment, stable_topoheight, topoheight, block_version, self.mainnet);
+        if replacement {
+            state = state.with_ignored_cache(tx.get_source());
+        }
+
+        let tx_cache = TxCache::new(storage, self, self.disable_zkp_cache);
+        tx.verify(hash, &mut state, &tx_cache).await?;
+        Ok(state)
+    }
+
     // Remove a TX using its hash from mempool
     // This will recalculate the cache bounds
     pub fn remove_tx(&mut self, hash: &Hash) -> Result<(), BlockchainError> {
@@ -329,11 +380,12 @@ impl Mempool {
     // Because of DAG reorg, we can't only check updated keys from new block,
     // as a block could be orphaned and the nonce order would change
     // So we need to check all keys from mempool and compare it from storage
-    pub async fn clean_up<S: Storage>(&mut self, storage: &S, environment: &Environment, stable_topoheight: TopoHeight, topoheight: TopoHeight, block_version: BlockVersion) -> Vec<(Arc<Hash>, SortedTx)> {
+    // Deleted TXs are returned with the reason of their eviction
+    pub async fn clean_up<S: Storage>(&mut self, storage: &S, environment: &Environment, stable_topoheight: TopoHeight, topoheight: TopoHeight, block_version: BlockVersion) -> Vec<(Arc<Hash>, SortedTx, MempoolEvictionReason)> {
         trace!("Cleaning up mempool...");
 
         // All deleted sorted txs with their hashes
-        let mut deleted_transactions: Vec<(Arc<Hash>, SortedTx)> = Vec::new();
+        let mut deleted_transactions: Vec<(Arc<Hash>, SortedTx, MempoolEvictionReason)> = Vec::new();
 
         let mut caches = HashMap::new();
         // Swap the nonces_cache with cache, so we iterate over cache and reinject it in nonces_cache
@@ -352,7 +404,7 @@ impl Mempool {
                     // Delete all txs from this cache
                     for tx in cache.txs {
                         if let Some(sorted_tx) = self.txs.remove(&tx) {
-                            deleted_transactions.push((tx, sorted_tx));
+                            deleted_transactions.push((tx, sorted_tx, MempoolEvictionReason::NonceConflict));
                         } else {
                             warn!("TX {} not found in mempool while deleting due to nonce error", tx);
                         }
@@ -375,7 +427,7 @@ impl Mempool {
                 for tx in cache.txs.drain(..) {
                     if let Some(sorted_tx) = self.txs.remove(&tx) {
                         debug!("Deleting ghost TX {} with {} and nonce {}", tx, sorted_tx.get_tx().get_reference(), sorted_tx.get_tx().get_nonce());
-                        deleted_transactions.push((tx, sorted_tx));
+                        deleted_transactions.push((tx, sorted_tx, MempoolEvictionReason::NonceConflict));
                     } else {
                         warn!("Ghost TX {} not found in mempool (orphaned due to nonce)", tx);
                     }
@@ -465,6 +517,9 @@ impl Mempool {
                     }
                 }
 
+                // TXs deleted above are using an already used nonce
+                // the next ones are deleted because they are no longer valid
+                let nonce_conflicts = deleted_txs_hashes.len();
                 if delete_cache {
                     // We empty the cache, so we can delete all txs
                     let mut local_cache = IndexSet::new();
@@ -474,10 +529,15 @@ impl Mempool {
                 }
 
                 // now delete all necessary txs
-                for hash in deleted_txs_hashes {
+                for (i, hash) in deleted_txs_hashes.into_iter().enumerate() {
                     debug!("Deleting TX {} for source {}", hash, key.as_address(self.mainnet));
                     if let Some(sorted_tx) = self.txs.remove(&hash) {
-                        deleted_transactions.push((hash, sorted_tx));
+                        let reason = if i < nonce_conflicts {
+                            MempoolEvictionReason::NonceConflict
+                        } else {
+                            MempoolEvictionReason::Invalidated
+                        };
+                        deleted_transactions.push((hash, sorted_tx, reason));
                     } else {
                         // This should never happen, but better to put a warning here
                         // in case of a lurking bug
@@ -624,6 +684,33 @@ mod tests {
         assert_eq!(estimated.default, FEE_PER_KB);
     }
 
+    #[test]
+    fn test_estimated_fee_rates_from_distribution() {
+        let fee_rates = (1..=100).map(|i| FEE_PER_KB * i).collect();
+        let estimated = super::Mempool::estimate_fee_rates_from_distribution(fee_rates);
+        assert_eq!(estimated.low, FEE_PER_KB * 25);
+        assert_eq!(estimated.medium, FEE_PER_KB * 50);
+        assert_eq!(estimated.high, FEE_PER_KB * 90);
+        assert_eq!(estimated.default, FEE_PER_KB);
+
+        // Never below the network minimum
+        let estimated = super::Mempool::estimate_fee_rates_from_distribution(vec![1, 2, FEE_PER_KB * 2]);
+        assert_eq!(estimated.low, FEE_PER_KB);
+        assert_eq!(estimated.medium, FEE_PER_KB);
+        assert_eq!(estimated.high, FEE_PER_KB * 2);
+
+        let estimated = super::Mempool::estimate_fee_rates_from_distribution(Vec::new());
+        assert_eq!(estimated.low, FEE_PER_KB);
+        assert_eq!(estimated.high, FEE_PER_KB);
+    }
+
+    #[test]
+    fn test_compute_fee_rate_per_kb() {
+        assert_eq!(super::Mempool::compute_fee_rate_per_kb(FEE_PER_KB, BYTES_PER_KB), FEE_PER_KB);
+        assert_eq!(super::Mempool::compute_fee_rate_per_kb(FEE_PER_KB, BYTES_PER_KB / 2), FEE_PER_KB * 2);
+        assert_eq!(super::Mempool::compute_fee_rate_per_kb(FEE_PER_KB, 0), FEE_PER_KB * BYTES_PER_KB as u64);
+    }
+
     #[test]
     fn test_estimated_fee_rates_no_tx() {
         let estimated = super::Mempool::internal_estimate_fee_rates(Vec::new());
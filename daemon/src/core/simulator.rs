@@ -102,6 +102,9 @@ impl Simulator {
                 _ => 1
             };
 
+            // Prevent any manual mining while we produce our blocks
+            let _guard = blockchain.lock_simulator().await;
+
             // Generate blocks
             let blocks = self.generate_blocks(blocks_count, &mut rng, &keys, &blockchain).await;
 
@@ -22,7 +22,8 @@ use terminos_common::{
         verify::{BlockchainApplyState, BlockchainVerificationState, ContractEnvironment},
         ContractDeposit,
         MultiSigPayload,
-        Reference
+        Reference,
+        TransactionType
     },
     versioned_type::VersionedState
 };
@@ -394,6 +395,11 @@ impl<'a, S: Storage> ApplicableChainState<'a, S> {
     // Get the contract tracker
     pub fn get_contract_tracker(&self) -> &ContractEventTracker {
         &self.contract_manager.tracker
+    }
+
+    // Get the assets changes made by the contracts
+    pub fn get_assets_changes(&self) -> &HashMap<Hash, Option<AssetChanges>> {
+        &self.contract_manager.assets
     } 
 
     // Get the contract outputs for TX
@@ -590,7 +596,20 @@ impl<'a, S: Storage> ApplicableChainState<'a, S> {
         // Apply all the contract outputs
         debug!("storing contract outputs");
         for (key, outputs) in self.contract_manager.outputs {
-            self.inner.storage.set_contract_outputs_for_tx(&key, &outputs).await?;
+            // Find the contract called to index the outputs per contract
+            // A deployed contract has the hash of the TX that deployed it
+            let contract = self.block.get_txs_hashes()
+                .iter()
+                .zip(self.block.get_transactions())
+                .find(|(hash, _)| *hash == key)
+                .and_then(|(_, tx)| match tx.get_data() {
+                    TransactionType::InvokeContract(payload) => Some(&payload.contract),
+                    TransactionType::DeployContract(_) => Some(key),
+                    _ => None
+                })
+                .unwrap_or(key);
+
+            self.inner.storage.set_contract_outputs_for_tx(&key, contract, self.inner.topoheight, &outputs).await?;
         }
 
         // Apply all balances changes at topoheight
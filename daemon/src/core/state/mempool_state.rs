@@ -19,7 +19,7 @@ use terminos_environment::Environment;
 use terminos_vm::Module;
 use crate::core::{
     error::BlockchainError,
-    mempool::Mempool,
+    mempool::{AccountCache, Mempool},
     storage::Storage
 };
 
@@ -58,6 +58,9 @@ pub struct MempoolState<'a, S: Storage> {
     topoheight: TopoHeight,
     // Block header version
     block_version: BlockVersion,
+    // Sender for which the mempool cache is ignored
+    // This is used to verify a TX replacing the pending one of its sender
+    ignored_cache: Option<&'a PublicKey>,
 }
 
 impl<'a, S: Storage> MempoolState<'a, S> {
@@ -73,9 +76,26 @@ impl<'a, S: Storage> MempoolState<'a, S> {
             stable_topoheight,
             topoheight,
             block_version,
+            ignored_cache: None,
         }
     }
 
+    // Ignore the mempool cache of the sender
+    // Its nonce and balances are retrieved from the storage instead
+    pub fn with_ignored_cache(mut self, key: &'a PublicKey) -> Self {
+        self.ignored_cache = Some(key);
+        self
+    }
+
+    // Retrieve the mempool cache of a sender unless it is ignored
+    fn get_mempool_cache_for(&self, key: &PublicKey) -> Option<&'a AccountCache> {
+        if self.ignored_cache == Some(key) {
+            return None
+        }
+
+        self.mempool.get_cache_for(key)
+    }
+
     // Retrieve the sender cache (inclunding balances and multisig)
     pub fn get_sender_cache(&mut self, key: &PublicKey) -> Option<(HashMap<&Hash, Ciphertext>, Option<MultiSigPayload>)> {
         let account = self.accounts.remove(key)?;
@@ -104,8 +124,8 @@ impl<'a, S: Storage> MempoolState<'a, S> {
     }
 
     // Retrieve the nonce & the multisig state for a sender account
-    async fn create_sender_account(mempool: &Mempool, storage: &S, key: &'a PublicKey, topoheight: TopoHeight) -> Result<Account<'a>, BlockchainError> {
-        let (nonce, multisig) = if let Some(cache) = mempool.get_cache_for(key) {
+    async fn create_sender_account(cache: Option<&AccountCache>, storage: &S, key: &'a PublicKey, topoheight: TopoHeight) -> Result<Account<'a>, BlockchainError> {
+        let (nonce, multisig) = if let Some(cache) = cache {
             let nonce = cache.get_next_nonce();
             let multisig = if let Some(multisig) = cache.get_multisig() {
                 Some(multisig.clone())
@@ -138,12 +158,13 @@ impl<'a, S: Storage> MempoolState<'a, S> {
     // If still not present, we check in storage and determine using reference
     // Which version to use
     async fn internal_get_sender_balance<'b>(&'b mut self, key: &'a PublicKey, asset: &'a Hash, reference: &Reference) -> Result<&'b mut Ciphertext, BlockchainError> {
+        let cache = self.get_mempool_cache_for(key);
         match self.accounts.entry(key) {
             Entry::Occupied(o) => {
                 let account = o.into_mut();
                 match account.assets.entry(asset) {
                     Entry::Occupied(entry) => Ok(entry.into_mut()),
-                    Entry::Vacant(entry) => match self.mempool.get_cache_for(key) {
+                    Entry::Vacant(entry) => match cache {
                         Some(cache) => {
                             if let Some(version) = cache.get_balances().get(asset) {
                                 Ok(entry.insert(version.clone()))
@@ -160,7 +181,7 @@ impl<'a, S: Storage> MempoolState<'a, S> {
                 }
             },
             Entry::Vacant(e) => {
-                let account = e.insert(Self::create_sender_account(&self.mempool, &self.storage, key, self.topoheight).await?);
+                let account = e.insert(Self::create_sender_account(cache, &self.storage, key, self.topoheight).await?);
 
                 match account.assets.entry(asset) {
                     Entry::Occupied(entry) => Ok(entry.into_mut()),
@@ -180,10 +201,11 @@ impl<'a, S: Storage> MempoolState<'a, S> {
     // Retrieve the account nonce
     // Only sender accounts should be used here
     async fn internal_get_account_nonce(&mut self, key: &'a PublicKey) -> Result<Nonce, BlockchainError> {
+        let cache = self.get_mempool_cache_for(key);
         match self.accounts.entry(key) {
             Entry::Occupied(o) => Ok(o.get().nonce),
             Entry::Vacant(e) => {
-                let account = Self::create_sender_account(&self.mempool, &self.storage, key, self.topoheight).await?;
+                let account = Self::create_sender_account(cache, &self.storage, key, self.topoheight).await?;
                 Ok(e.insert(account).nonce)
             }
         }
@@ -35,7 +35,7 @@ pub trait Storage:
     + MerkleHashProvider + NetworkProvider + MultiSigProvider + TipsProvider
     + CommitPointProvider + ContractProvider + ContractDataProvider + ContractOutputsProvider
     + ContractInfoProvider + ContractBalanceProvider + VersionedProvider + SupplyProvider
-    + CacheProvider + StateProvider + EnergyProvider
+    + CacheProvider + StateProvider + EnergyProvider + CheckpointProvider
     + Sync + Send + 'static {
     // delete block at topoheight, and all pointers (hash_at_topo, topo_by_hash, reward, supply, diff, cumulative diff...)
     async fn delete_block_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(Hash, Immutable<BlockHeader>, Vec<(Hash, Immutable<Transaction>)>), BlockchainError>;
@@ -171,4 +171,16 @@ pub trait Storage:
 
     // Flush the inner DB after a block being written
     async fn flush(&mut self) -> Result<(), BlockchainError>;
+
+    // Set the contracts for which the versioned data is never pruned
+    // Backends that can't filter the contracts data must reject a non empty set
+    fn set_prune_retained_contracts(&mut self, contracts: HashSet<Hash>) -> Result<(), BlockchainError>;
+
+    // Manually compact the contracts data, balances and supply
+    // Used to reclaim disk space after a large prune
+    async fn compact(&self) -> Result<(), BlockchainError>;
+
+    // Export a consistent copy of the current DB state to the path
+    // The topoheight is recorded in the snapshot metadata
+    async fn export_snapshot(&self, topoheight: TopoHeight, path: &str) -> Result<(), BlockchainError>;
 }
\ No newline at end of file
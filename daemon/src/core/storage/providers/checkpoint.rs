@@ -0,0 +1,14 @@
+use std::collections::HashSet;
+use async_trait::async_trait;
+use terminos_common::crypto::Hash;
+use crate::core::error::BlockchainError;
+
+// This trait is used to persist the checkpoints added at runtime
+#[async_trait]
+pub trait CheckpointProvider {
+    // get all the checkpoints stored on disk
+    async fn get_checkpoints(&self) -> Result<HashSet<Hash>, BlockchainError>;
+
+    // set the checkpoints on disk
+    async fn set_checkpoints(&mut self, checkpoints: &HashSet<Hash>) -> Result<(), BlockchainError>;
+}
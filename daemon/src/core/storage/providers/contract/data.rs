@@ -39,4 +39,9 @@ pub trait ContractDataProvider {
 
     // Get all the contract data entries at a maximum topoheight
     async fn get_contract_data_entries_at_maximum_topoheight<'a>(&'a self, contract: &'a Hash, topoheight: TopoHeight) -> Result<impl Stream<Item = Result<(ValueCell, ValueCell), BlockchainError>> + Send + 'a, BlockchainError>;
+
+    // Get up to `limit` contract data entries at a maximum topoheight, starting from `start_key` (included)
+    // Entries are returned in a deterministic order that is not based on the keys values
+    // Deleted entries are skipped
+    async fn get_contract_data_entries_range_at_maximum_topoheight(&self, contract: &Hash, start_key: Option<&ValueCell>, limit: usize, topoheight: TopoHeight) -> Result<Vec<(ValueCell, ValueCell)>, BlockchainError>;
 }
\ No newline at end of file
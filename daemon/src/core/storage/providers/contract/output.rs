@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use terminos_common::{
+    block::TopoHeight,
     contract::ContractOutput,
     crypto::Hash
 };
@@ -14,8 +15,13 @@ pub trait ContractOutputsProvider {
     async fn get_contract_outputs_for_tx(&self, tx_hash: &Hash) -> Result<Vec<ContractOutput>, BlockchainError>;
 
     // Set the contract outputs for a transaction
-    async fn set_contract_outputs_for_tx(&mut self, tx_hash: &Hash, contract_output: &Vec<ContractOutput>) -> Result<(), BlockchainError>;
+    // The transaction is also indexed for the contract at the topoheight it got executed
+    async fn set_contract_outputs_for_tx(&mut self, tx_hash: &Hash, contract: &Hash, topoheight: TopoHeight, contract_output: &Vec<ContractOutput>) -> Result<(), BlockchainError>;
 
-    // Delete the contract outputs for a transaction
+    // Delete the contract outputs for a transaction and its contract index entry
     async fn delete_contract_outputs_for_tx(&mut self, tx_hash: &Hash) -> Result<(), BlockchainError>;
+
+    // Get up to `limit` transactions that produced outputs for a contract
+    // starting from `start_topoheight` (included), ordered by topoheight
+    async fn get_contract_transactions(&self, contract: &Hash, start_topoheight: TopoHeight, limit: usize) -> Result<Vec<(TopoHeight, Hash)>, BlockchainError>;
 }
\ No newline at end of file
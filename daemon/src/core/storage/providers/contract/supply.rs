@@ -21,4 +21,24 @@ pub trait SupplyProvider {
 
     // Set the latest supply pointer for this asset and store the versioned data
     async fn set_last_supply_for_asset(&mut self, asset: &Hash, topoheight: TopoHeight, supply: &VersionedSupply) -> Result<(), BlockchainError>;
+
+    // Sample the supply every `step` topoheights from start to end (both included)
+    // Points with no supply set yet are skipped
+    async fn get_asset_supply_history(&self, asset: &Hash, start_topoheight: TopoHeight, end_topoheight: TopoHeight, step: u64) -> Result<Vec<(TopoHeight, u64)>, BlockchainError> {
+        let step = step.max(1);
+        let mut points = Vec::new();
+        let mut topoheight = start_topoheight;
+        while topoheight <= end_topoheight {
+            if let Some((_, supply)) = self.get_asset_supply_at_maximum_topoheight(asset, topoheight).await? {
+                points.push((topoheight, supply.take()));
+            }
+
+            topoheight = match topoheight.checked_add(step) {
+                Some(v) => v,
+                None => break
+            };
+        }
+
+        Ok(points)
+    }
 }
\ No newline at end of file
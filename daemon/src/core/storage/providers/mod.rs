@@ -21,6 +21,7 @@ mod versioned;
 mod cache;
 mod state;
 mod energy;
+mod checkpoint;
 
 pub use asset::*;
 pub use blocks_at_height::*;
@@ -44,4 +45,5 @@ pub use contract::*;
 pub use versioned::*;
 pub use cache::*;
 pub use state::*;
-pub use energy::*;
\ No newline at end of file
+pub use energy::*;
+pub use checkpoint::*;
\ No newline at end of file
@@ -99,6 +99,13 @@ pub enum Column {
 
     // {topoheight}{asset_id} => {version}
     VersionedAssetsSupply,
+
+    // Transactions that produced outputs per contract
+    // {contract_id}{topoheight}{tx_hash} => {}
+    ContractTransactions,
+    // Reverse index of the contract transactions
+    // {tx_hash} => {contract_id}{topoheight}
+    TransactionContract,
     
     // Energy resources for each account
     // {account_key} => {topoheight}
@@ -3,12 +3,18 @@ mod types;
 mod providers;
 mod snapshot;
 
-use std::sync::Arc;
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc
+};
 
 use anyhow::Context;
 use async_trait::async_trait;
 use itertools::Either;
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
 use rocksdb::{
     BlockBasedOptions,
     Cache,
@@ -18,6 +24,7 @@ use rocksdb::{
     DBWithThreadMode,
     Direction,
     Env,
+    checkpoint::Checkpoint,
     IteratorMode as InternalIteratorMode,
     MultiThreaded,
     Options,
@@ -30,7 +37,7 @@ use strum::IntoEnumIterator;
 use terminos_common::{
     account::EnergyResource,
     block::{BlockHeader, TopoHeight},
-    crypto::{Hash, PublicKey},
+    crypto::{hash, hash_reader, Hash, PublicKey},
     immutable::Immutable,
     network::Network,
     serializer::{Count, Serializer},
@@ -40,7 +47,7 @@ use terminos_common::{
 use crate::core::{
     config::RocksDBConfig,
     error::{BlockchainError, DiskContext},
-    storage::{BlocksAtHeightProvider, ClientProtocolProvider, ContractOutputsProvider, Tips}
+    storage::{BlocksAtHeightProvider, ClientProtocolProvider, ContractOutputsProvider, DagOrderProvider, Tips}
 };
 
 pub use column::*;
@@ -59,6 +66,82 @@ macro_rules! cf_handle {
 
 type InnerDB = DBWithThreadMode<MultiThreaded>;
 
+// File written in an exported snapshot directory
+const SNAPSHOT_METADATA_FILE: &str = "snapshot_metadata.json";
+
+// Columns compacted on a manual compaction request
+// These are the ones growing the most and heavily deleted by a prune
+const COMPACTED_COLUMNS: [Column; 8] = [
+    Column::ContractsData,
+    Column::VersionedContractsData,
+    Column::ContractDataById,
+    Column::Balances,
+    Column::VersionedBalances,
+    Column::ContractsBalances,
+    Column::VersionedContractsBalances,
+    Column::VersionedAssetsSupply,
+];
+
+// Metadata stored alongside an exported snapshot
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotMetadata {
+    // Topoheight of the chain at the time of the export
+    pub topoheight: TopoHeight,
+    // Network of the exported chain
+    pub network: Network,
+    // Hash of the block at topoheight 0
+    pub genesis_hash: Hash,
+    // Checksum of all the checkpoint files
+    pub checksum: Hash
+}
+
+// Compute the checksum of a snapshot directory
+// Each file (except the metadata one) is hashed in name order
+// and the final checksum is the hash of all the names and file hashes
+fn compute_snapshot_checksum(path: &Path) -> Result<Hash, BlockchainError> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path).context("Error while reading snapshot directory")? {
+        let entry = entry.context("Error while reading snapshot entry")?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name != SNAPSHOT_METADATA_FILE {
+            files.push(name);
+        }
+    }
+    files.sort();
+
+    let mut buffer = Vec::new();
+    for name in files {
+        // Checkpoint files can be huge, stream them instead of loading them in memory
+        let file = File::open(path.join(&name))
+            .with_context(|| format!("Error while opening snapshot file {}", name))?;
+        let file_hash = hash_reader(BufReader::new(file))
+            .with_context(|| format!("Error while reading snapshot file {}", name))?;
+
+        buffer.extend_from_slice(name.as_bytes());
+        buffer.extend_from_slice(file_hash.as_bytes());
+    }
+
+    Ok(hash(&buffer))
+}
+
+// Copy all the snapshot files (except the metadata one) into the destination directory
+fn copy_snapshot_files(source: &Path, destination: &Path) -> Result<(), BlockchainError> {
+    fs::create_dir_all(destination).context("Error while creating snapshot import directory")?;
+    for entry in fs::read_dir(source).context("Error while reading snapshot directory")? {
+        let entry = entry.context("Error while reading snapshot entry")?;
+        let name = entry.file_name();
+        if name == SNAPSHOT_METADATA_FILE {
+            continue;
+        }
+
+        debug!("copying snapshot file {:?}", name);
+        fs::copy(entry.path(), destination.join(&name))
+            .with_context(|| format!("Error while copying snapshot file {:?}", name))?;
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Copy, Clone, clap::ValueEnum, Serialize, Deserialize)]
 #[clap(rename_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
@@ -137,7 +220,9 @@ impl<'a> IteratorMode<'a> {
 pub struct RocksStorage {
     db: Arc<InnerDB>,
     network: Network,
-    snapshot: Option<Snapshot> 
+    snapshot: Option<Snapshot>,
+    // Contracts for which the versioned data is never pruned
+    retained_contracts: HashSet<Hash>
 }
 
 impl RocksStorage {
@@ -199,8 +284,74 @@ impl RocksStorage {
         Self {
             db: Arc::new(db),
             network,
-            snapshot: None
+            snapshot: None,
+            retained_contracts: HashSet::new()
+        }
+    }
+
+    // Import a snapshot created by export_snapshot into the DB directory
+    // This must be called before opening the storage on a fresh node
+    // The snapshot is verified (network, genesis, checksum) before its files are copied
+    pub fn import_snapshot(snapshot_dir: &str, dir: &str, network: Network, expected_genesis: Option<&Hash>) -> Result<SnapshotMetadata, BlockchainError> {
+        trace!("import snapshot from {}", snapshot_dir);
+
+        let target = format!("{}{}", dir, network.to_string().to_lowercase());
+        let target = Path::new(&target);
+        if target.exists() && target.read_dir().context("Error while reading DB directory")?.next().is_some() {
+            return Err(BlockchainError::SnapshotTargetNotEmpty)
+        }
+
+        let source = Path::new(snapshot_dir);
+        let content = fs::read(source.join(SNAPSHOT_METADATA_FILE))
+            .context("Error while reading snapshot metadata")?;
+        let metadata: SnapshotMetadata = serde_json::from_slice(&content)
+            .context("Error while parsing snapshot metadata")?;
+
+        if metadata.network != network {
+            return Err(BlockchainError::SnapshotNetworkMismatch(metadata.network, network))
+        }
+
+        if let Some(expected) = expected_genesis {
+            if metadata.genesis_hash != *expected {
+                return Err(BlockchainError::SnapshotGenesisMismatch(expected.clone(), metadata.genesis_hash))
+            }
+        }
+
+        info!("verifying snapshot checksum");
+        let checksum = compute_snapshot_checksum(source)?;
+        if checksum != metadata.checksum {
+            return Err(BlockchainError::SnapshotChecksumMismatch(metadata.checksum, checksum))
+        }
+
+        // Files are copied in a temporary directory which is then renamed to the DB directory
+        // so a failure never leaves a half populated DB directory behind
+        let tmp = PathBuf::from(format!("{}.import", target.display()));
+        if tmp.exists() {
+            debug!("removing leftover import directory {}", tmp.display());
+            fs::remove_dir_all(&tmp).context("Error while removing leftover import directory")?;
+        }
+
+        let res = copy_snapshot_files(source, &tmp).and_then(|_| {
+            // The DB directory may exist but is empty
+            if target.exists() {
+                fs::remove_dir(target).context("Error while removing empty DB directory")?;
+            }
+
+            fs::rename(&tmp, target).context("Error while moving imported snapshot to the DB directory")?;
+            Ok(())
+        });
+
+        if let Err(e) = res {
+            if tmp.exists() {
+                if let Err(e) = fs::remove_dir_all(&tmp) {
+                    warn!("Error while removing import directory {}: {}", tmp.display(), e);
+                }
+            }
+
+            return Err(e)
         }
+
+        Ok(metadata)
     }
 
     pub fn load_cache_from_disk(&mut self) {
@@ -568,6 +719,63 @@ impl Storage for RocksStorage {
             Ok::<_, BlockchainError>(())
         }).await.context("Flushing DB")?
     }
+
+    fn set_prune_retained_contracts(&mut self, contracts: HashSet<Hash>) -> Result<(), BlockchainError> {
+        trace!("set {} prune retained contracts", contracts.len());
+        self.retained_contracts = contracts;
+        Ok(())
+    }
+
+    async fn compact(&self) -> Result<(), BlockchainError> {
+        trace!("compact DB");
+
+        let db = Arc::clone(&self.db);
+        tokio::task::spawn_blocking(move || {
+            for column in COMPACTED_COLUMNS {
+                info!("compacting {:?}", column);
+                let cf = cf_handle!(db, column);
+                db.compact_range_cf::<&[u8], &[u8]>(&cf, None, None);
+            }
+
+            debug!("wait for compact");
+            let options = WaitForCompactOptions::default();
+            db.wait_for_compact(&options)
+                .context("Error while waiting on compact")?;
+
+            Ok::<_, BlockchainError>(())
+        }).await.context("Compacting DB")?
+    }
+
+    async fn export_snapshot(&self, topoheight: TopoHeight, path: &str) -> Result<(), BlockchainError> {
+        trace!("export snapshot at topoheight {} to {}", topoheight, path);
+
+        // Pending changes would not be part of the checkpoint
+        if self.snapshot.is_some() {
+            return Err(BlockchainError::UnsupportedOperation)
+        }
+
+        let genesis_hash = self.get_hash_at_topo_height(0).await?;
+        let network = self.network;
+        let db = Arc::clone(&self.db);
+        let path = path.to_owned();
+        tokio::task::spawn_blocking(move || {
+            let checkpoint = Checkpoint::new(&db)
+                .context("Error while preparing checkpoint")?;
+
+            info!("creating checkpoint at {}", path);
+            checkpoint.create_checkpoint(&path)
+                .context("Error while creating checkpoint")?;
+
+            let checksum = compute_snapshot_checksum(Path::new(&path))?;
+            let metadata = SnapshotMetadata { topoheight, network, genesis_hash, checksum };
+            let content = serde_json::to_vec_pretty(&metadata)
+                .context("Error while serializing snapshot metadata")?;
+            fs::write(Path::new(&path).join(SNAPSHOT_METADATA_FILE), content)
+                .context("Error while writing snapshot metadata")?;
+
+            Ok::<_, BlockchainError>(())
+        }).await.context("Exporting snapshot")?
+    }
 }
 
 // EnergyProvider implementation for RocksStorage
@@ -614,6 +822,62 @@ mod tests {
     use itertools::Itertools;
     use rocksdb::{Direction, IteratorMode, Options, SliceTransform, DB};
     use tempdir::TempDir;
+    use super::*;
+
+    #[test]
+    fn test_import_snapshot_integrity() {
+        let snapshot_dir = TempDir::new("snapshot").unwrap();
+        fs::write(snapshot_dir.path().join("000001.sst"), b"data").unwrap();
+        fs::write(snapshot_dir.path().join("CURRENT"), b"MANIFEST-000001").unwrap();
+
+        let genesis_hash = Hash::zero();
+        let metadata = SnapshotMetadata {
+            topoheight: 10,
+            network: Network::Devnet,
+            genesis_hash: genesis_hash.clone(),
+            checksum: compute_snapshot_checksum(snapshot_dir.path()).unwrap()
+        };
+        fs::write(snapshot_dir.path().join(SNAPSHOT_METADATA_FILE), serde_json::to_vec(&metadata).unwrap()).unwrap();
+
+        let snapshot_path = snapshot_dir.path().to_str().unwrap();
+        let db_dir = TempDir::new("db").unwrap();
+        let dir = format!("{}/", db_dir.path().to_str().unwrap());
+
+        // Wrong network
+        assert!(matches!(
+            RocksStorage::import_snapshot(snapshot_path, &dir, Network::Testnet, None),
+            Err(BlockchainError::SnapshotNetworkMismatch(_, _))
+        ));
+
+        // Wrong genesis
+        assert!(matches!(
+            RocksStorage::import_snapshot(snapshot_path, &dir, Network::Devnet, Some(&Hash::max())),
+            Err(BlockchainError::SnapshotGenesisMismatch(_, _))
+        ));
+
+        // Tampered file
+        fs::write(snapshot_dir.path().join("000001.sst"), b"tampered").unwrap();
+        assert!(matches!(
+            RocksStorage::import_snapshot(snapshot_path, &dir, Network::Devnet, Some(&genesis_hash)),
+            Err(BlockchainError::SnapshotChecksumMismatch(_, _))
+        ));
+
+        // Restored file is imported
+        fs::write(snapshot_dir.path().join("000001.sst"), b"data").unwrap();
+        let imported = RocksStorage::import_snapshot(snapshot_path, &dir, Network::Devnet, Some(&genesis_hash)).unwrap();
+        assert_eq!(imported.topoheight, 10);
+
+        let target = db_dir.path().join("dev");
+        assert_eq!(fs::read(target.join("000001.sst")).unwrap(), b"data");
+        assert!(!target.join(SNAPSHOT_METADATA_FILE).exists());
+        assert!(!db_dir.path().join("dev.import").exists());
+
+        // Target isn't empty anymore
+        assert!(matches!(
+            RocksStorage::import_snapshot(snapshot_path, &dir, Network::Devnet, Some(&genesis_hash)),
+            Err(BlockchainError::SnapshotTargetNotEmpty)
+        ));
+    }
 
     #[test]
     fn test_rocks_db_iterator_behavior() {
@@ -0,0 +1,27 @@
+use std::collections::HashSet;
+use async_trait::async_trait;
+use log::trace;
+use terminos_common::crypto::Hash;
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        rocksdb::Column,
+        CheckpointProvider,
+        RocksStorage,
+    }
+};
+
+#[async_trait]
+impl CheckpointProvider for RocksStorage {
+    // get all the checkpoints stored on disk
+    async fn get_checkpoints(&self) -> Result<HashSet<Hash>, BlockchainError> {
+        trace!("get checkpoints");
+        Ok(self.load_optional_from_disk(Column::Common, b"checkpoints")?.unwrap_or_default())
+    }
+
+    // set the checkpoints on disk
+    async fn set_checkpoints(&mut self, checkpoints: &HashSet<Hash>) -> Result<(), BlockchainError> {
+        trace!("set {} checkpoints", checkpoints.len());
+        self.insert_into_disk(Column::Common, b"checkpoints", checkpoints)
+    }
+}
@@ -1,5 +1,7 @@
+use std::iter;
 use async_trait::async_trait;
 use futures::{stream, Stream, StreamExt};
+use itertools::Either;
 use log::trace;
 use rocksdb::Direction;
 use terminos_vm::ValueCell;
@@ -7,6 +9,7 @@ use terminos_common::{
     block::TopoHeight,
     crypto::Hash,
     serializer::Serializer,
+    tokio::try_block_on
 };
 use crate::core::{
     error::BlockchainError,
@@ -130,9 +133,69 @@ impl ContractDataProvider for RocksStorage {
             .filter_map(|res| async move { res.await.transpose() })
         )
     }
+
+    async fn get_contract_data_entries_range_at_maximum_topoheight(&self, contract: &Hash, start_key: Option<&ValueCell>, limit: usize, topoheight: TopoHeight) -> Result<Vec<(ValueCell, ValueCell)>, BlockchainError> {
+        trace!("get contract {} data entries range at maximum topoheight {}", contract, topoheight);
+        let Some(contract_id) = self.get_optional_contract_id(contract)? else {
+            return Ok(Vec::new())
+        };
+
+        // Keys are ordered by the contract id and then by the data id
+        let start = match start_key {
+            Some(key) => Self::get_contract_data_key(contract_id, key),
+            None => {
+                let mut buf = [0u8; 16];
+                buf[0..8].copy_from_slice(&contract_id.to_be_bytes());
+                buf
+            }
+        };
+
+        let iterator = self.iter_keys::<(ContractId, u64)>(Column::ContractsData, IteratorMode::From(&start, Direction::Forward))?;
+        let mut entries = Vec::new();
+        for res in iterator {
+            let (id, data_id) = res?;
+            // We went over all the contract entries
+            if id != contract_id || entries.len() >= limit {
+                break;
+            }
+
+            let key = self.load_from_disk(Column::ContractDataById, &data_id.to_be_bytes())?;
+            if let Some((_, version)) = self.get_contract_data_at_maximum_topoheight_for(contract, &key, topoheight).await? {
+                if let Some(value) = version.take() {
+                    entries.push((key, value));
+                }
+            }
+        }
+
+        Ok(entries)
+    }
 }
 
 impl RocksStorage {
+    // Lazily iterate over the contract entries at a maximum topoheight
+    // Keys are ordered by the contract id and then by the data id
+    pub(super) fn iter_contract_data_at_maximum_topoheight<'a>(&'a self, contract: &'a Hash, topoheight: TopoHeight) -> Result<impl Iterator<Item = Result<(ValueCell, ValueCell), anyhow::Error>> + 'a, BlockchainError> {
+        let contract_id = self.get_optional_contract_id(contract)?;
+        let start = contract_id.unwrap_or_default().to_be_bytes();
+        let iterator = match contract_id {
+            Some(_) => Either::Left(self.iter_keys::<(ContractId, u64)>(Column::ContractsData, IteratorMode::From(&start, Direction::Forward))?),
+            None => Either::Right(iter::empty())
+        };
+
+        // No prefix extractor is set on this column, so stop once we leave the contract
+        Ok(iterator.take_while(move |res| !matches!(res, Ok((id, _)) if Some(*id) != contract_id))
+            .filter_map(move |res| {
+                let load = || -> Result<Option<(ValueCell, ValueCell)>, anyhow::Error> {
+                    let (_, data_id) = res?;
+                    let key: ValueCell = self.load_from_disk(Column::ContractDataById, &data_id.to_be_bytes())?;
+                    let version = try_block_on(self.get_contract_data_at_maximum_topoheight_for(contract, &key, topoheight))??;
+                    Ok(version.and_then(|(_, v)| v.take().map(|v| (key, v))))
+                };
+
+                load().transpose()
+            }))
+    }
+
     fn generate_data_id_for(contract: ContractId, key: &ValueCell) -> u64 {
         xxhash_rust::xxh3::xxh3_64_with_seed(&key.to_bytes(), contract)
     }
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use async_trait::async_trait;
 use log::trace;
 use terminos_common::{
@@ -208,6 +209,25 @@ impl RocksStorage {
         self.load_optional_from_disk(Column::Contracts, contract)
     }
 
+    // Resolve the ids of the contracts retained from pruning
+    // Contracts not deployed yet have no data to retain
+    pub fn get_retained_contract_ids(&self) -> Result<HashSet<ContractId>, BlockchainError> {
+        trace!("get retained contract ids");
+        let mut ids = HashSet::with_capacity(self.retained_contracts.len());
+        for contract in self.retained_contracts.iter() {
+            if let Some(id) = self.get_optional_contract_id(contract)? {
+                ids.insert(id);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    // Check if the key is prefixed by one of the retained contract ids
+    pub fn is_retained_contract_key(ids: &HashSet<ContractId>, key: &[u8]) -> bool {
+        !ids.is_empty() && key.len() >= 8 && ids.contains(&ContractId::from_be_bytes(key[0..8].try_into().expect("8 bytes")))
+    }
+
     pub(super) fn get_contract_id(&self, contract: &Hash) -> Result<ContractId, BlockchainError> {
         trace!("get contract id");
         self.get_optional_contract_id(contract)?
@@ -89,4 +89,10 @@ impl ContractStorage for RocksStorage {
         let res = try_block_on(self.has_contract_at_maximum_topoheight(contract, topoheight))??;
         Ok(res)
     }
+
+    fn iter_data<'a>(&'a self, contract: &'a Hash, topoheight: TopoHeight) -> Result<Box<dyn Iterator<Item = Result<(ValueCell, ValueCell), anyhow::Error>> + 'a>, anyhow::Error> {
+        trace!("iter data for contract {} at topoheight {}", contract, topoheight);
+        let iterator = self.iter_contract_data_at_maximum_topoheight(contract, topoheight)?;
+        Ok(Box::new(iterator))
+    }
 }
\ No newline at end of file
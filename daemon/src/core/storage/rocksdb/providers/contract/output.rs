@@ -1,12 +1,18 @@
 use async_trait::async_trait;
 use log::trace;
+use rocksdb::Direction;
 use terminos_common::{
+    block::TopoHeight,
     contract::ContractOutput,
     crypto::Hash
 };
 use crate::core::{
     error::BlockchainError,
-    storage::{rocksdb::Column, ContractOutputsProvider, RocksStorage}
+    storage::{
+        rocksdb::{Column, ContractId, IteratorMode},
+        ContractOutputsProvider,
+        RocksStorage
+    }
 };
 
 #[async_trait]
@@ -24,14 +30,64 @@ impl ContractOutputsProvider for RocksStorage {
     }
 
     // Set the contract outputs for a transaction
-    async fn set_contract_outputs_for_tx(&mut self, tx_hash: &Hash, contract_output: &Vec<ContractOutput>) -> Result<(), BlockchainError> {
+    async fn set_contract_outputs_for_tx(&mut self, tx_hash: &Hash, contract: &Hash, topoheight: TopoHeight, contract_output: &Vec<ContractOutput>) -> Result<(), BlockchainError> {
         trace!("set contract outputs for tx {}", tx_hash);
-        self.insert_into_disk(Column::TransactionsOutputs, tx_hash, contract_output)
+        self.insert_into_disk(Column::TransactionsOutputs, tx_hash, contract_output)?;
+
+        // A contract that failed to be deployed has no id
+        if let Some(contract_id) = self.get_optional_contract_id(contract)? {
+            trace!("index tx {} for contract {} at topoheight {}", tx_hash, contract, topoheight);
+            self.insert_into_disk(Column::ContractTransactions, Self::get_contract_transaction_key(contract_id, topoheight, tx_hash), &())?;
+            self.insert_into_disk(Column::TransactionContract, tx_hash, &(contract_id, topoheight))?;
+        }
+
+        Ok(())
     }
 
     // Delete the contract outputs for a transaction
     async fn delete_contract_outputs_for_tx(&mut self, tx_hash: &Hash) -> Result<(), BlockchainError> {
         trace!("delete contract outputs for tx {}", tx_hash);
-        self.remove_from_disk(Column::TransactionsOutputs, tx_hash)
+        self.remove_from_disk(Column::TransactionsOutputs, tx_hash)?;
+
+        if let Some((contract_id, topoheight)) = self.load_optional_from_disk::<_, (ContractId, TopoHeight)>(Column::TransactionContract, tx_hash)? {
+            self.remove_from_disk(Column::ContractTransactions, Self::get_contract_transaction_key(contract_id, topoheight, tx_hash))?;
+            self.remove_from_disk(Column::TransactionContract, tx_hash)?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_contract_transactions(&self, contract: &Hash, start_topoheight: TopoHeight, limit: usize) -> Result<Vec<(TopoHeight, Hash)>, BlockchainError> {
+        trace!("get contract {} transactions from topoheight {}", contract, start_topoheight);
+        let Some(contract_id) = self.get_optional_contract_id(contract)? else {
+            return Ok(Vec::new())
+        };
+
+        let mut start = [0u8; 16];
+        start[0..8].copy_from_slice(&contract_id.to_be_bytes());
+        start[8..16].copy_from_slice(&start_topoheight.to_be_bytes());
+
+        let mut txs = Vec::new();
+        for res in self.iter_keys::<(ContractId, TopoHeight, Hash)>(Column::ContractTransactions, IteratorMode::From(&start, Direction::Forward))? {
+            let (id, topoheight, tx_hash) = res?;
+            // We went over all the contract transactions
+            if id != contract_id || txs.len() >= limit {
+                break;
+            }
+
+            txs.push((topoheight, tx_hash));
+        }
+
+        Ok(txs)
+    }
+}
+
+impl RocksStorage {
+    fn get_contract_transaction_key(contract: ContractId, topoheight: TopoHeight, tx_hash: &Hash) -> [u8; 48] {
+        let mut buf = [0u8; 48];
+        buf[0..8].copy_from_slice(&contract.to_be_bytes());
+        buf[8..16].copy_from_slice(&topoheight.to_be_bytes());
+        buf[16..48].copy_from_slice(tx_hash.as_bytes());
+        buf
     }
-}
\ No newline at end of file
+}
@@ -19,4 +19,5 @@ mod nonce;
 mod state;
 mod multisig;
 mod contract;
-mod versioned;
\ No newline at end of file
+mod versioned;
+mod checkpoint;
\ No newline at end of file
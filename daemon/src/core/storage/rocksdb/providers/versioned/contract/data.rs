@@ -24,6 +24,7 @@ impl VersionedContractDataProvider for RocksStorage {
 
     async fn delete_versioned_contract_data_below_topoheight(&mut self, topoheight: TopoHeight, keep_last: bool) -> Result<(), BlockchainError> {
         trace!("delete versioned contract data below topoheight {}", topoheight);
-        self.delete_versioned_below_topoheight(Column::ContractsData, Column::VersionedContractsData, topoheight, keep_last)
+        let retained = self.get_retained_contract_ids()?;
+        self.delete_versioned_below_topoheight_filtered(Column::ContractsData, Column::VersionedContractsData, topoheight, keep_last, |key| Self::is_retained_contract_key(&retained, key))
     }
 }
\ No newline at end of file
@@ -82,10 +82,14 @@ impl VersionedContractProvider for RocksStorage {
     // delete versioned contracts below topoheight
     async fn delete_versioned_contracts_below_topoheight(&mut self, topoheight: TopoHeight, keep_last: bool) -> Result<(), BlockchainError> {
         trace!("delete versioned contracts below topoheight {}", topoheight);
+        let retained = self.get_retained_contract_ids()?;
         let start = topoheight.to_be_bytes();
         if keep_last {
             for res in Self::iter_owned_internal::<(), Contract>(&self.db, self.snapshot.as_ref(), IteratorMode::Start, Column::Contracts)? {
                 let (_, contract) = res?;
+                if retained.contains(&contract.id) {
+                    continue;
+                }
 
                 if let Some(topo) = contract.module_pointer {
                     // We fetch the last version to take its previous topoheight
@@ -116,6 +120,11 @@ impl VersionedContractProvider for RocksStorage {
         } else {
             for res in Self::iter_owned_internal::<RawBytes, ()>(&self.db, self.snapshot.as_ref(), IteratorMode::From(&start, Direction::Forward), Column::VersionedContracts)? {
                 let (key, _) = res?;
+                // Versioned key is {topoheight}{contract_id}
+                if Self::is_retained_contract_key(&retained, &key[8..]) {
+                    continue;
+                }
+
                 Self::remove_from_disk_internal(&self.db, self.snapshot.as_mut(), Column::VersionedContracts, &key)?;
             }
         }
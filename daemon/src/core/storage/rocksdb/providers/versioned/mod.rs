@@ -71,10 +71,19 @@ impl RocksStorage {
     }
 
     pub fn delete_versioned_below_topoheight(&mut self, column_pointer: Column, column_versioned: Column, topoheight: TopoHeight, keep_last: bool) -> Result<(), BlockchainError> {
+        self.delete_versioned_below_topoheight_filtered(column_pointer, column_versioned, topoheight, keep_last, |_| false)
+    }
+
+    // Same as `delete_versioned_below_topoheight` but keep all the versions of the keys matched by `is_retained`
+    // The predicate receives the pointer key (without the topoheight prefix)
+    pub fn delete_versioned_below_topoheight_filtered<F: Fn(&[u8]) -> bool>(&mut self, column_pointer: Column, column_versioned: Column, topoheight: TopoHeight, keep_last: bool, is_retained: F) -> Result<(), BlockchainError> {
         let start = topoheight.to_be_bytes();
         if keep_last {
             for res in Self::iter_owned_internal::<RawBytes, TopoHeight>(&self.db, self.snapshot.as_ref(), IteratorMode::Start, column_pointer)? {
                 let (key, pointer) = res?;
+                if is_retained(&key) {
+                    continue;
+                }
 
                 // We fetch the last version to take its previous topoheight
                 // And we loop on it to delete them all until the end of the chained data
@@ -106,6 +115,10 @@ impl RocksStorage {
         } else {
             for res in Self::iter_owned_internal::<RawBytes, ()>(&self.db, self.snapshot.as_ref(), IteratorMode::From(&start, Direction::Forward), column_versioned)? {
                 let (key, _) = res?;
+                if is_retained(&key[8..]) {
+                    continue;
+                }
+
                 Self::remove_from_disk_internal(&self.db, self.snapshot.as_mut(), column_versioned, &key)?;
             }
         }
@@ -42,6 +42,7 @@ pub(super) const TOP_TOPO_HEIGHT: &[u8; 4] = b"TOPO";
 pub(super) const TOP_HEIGHT: &[u8; 4] = b"TOPH";
 pub(super) const NETWORK: &[u8; 3] = b"NET";
 pub(super) const PRUNED_TOPOHEIGHT: &[u8; 4] = b"PRUN";
+pub(super) const CHECKPOINTS: &[u8; 4] = b"CHKP";
 // Counters (prevent to perform a O(n))
 pub(super) const ACCOUNTS_COUNT: &[u8; 4] = b"CACC";
 pub(super) const TXS_COUNT: &[u8; 4] = b"CTXS";
@@ -143,6 +144,12 @@ pub struct SledStorage {
     // Contract outputs per TX
     // Key is the TX Hash that called the contract, value is a list of contract outputs
     pub(super) contracts_outputs: Tree,
+    // Transactions that produced outputs per contract
+    // Key is {contract}{topoheight}{tx_hash}, no value
+    pub(super) contracts_transactions: Tree,
+    // Reverse index of the contracts transactions
+    // Key is the TX Hash, value is {contract}{topoheight}
+    pub(super) transactions_contract: Tree,
     // Energy resources for each account
     // Key is the account public key, value is the energy resource
     pub(super) energy_resources: Tree,
@@ -253,6 +260,8 @@ impl SledStorage {
             contracts_balances: sled.open_tree("contracts_balances")?,
             versioned_contracts_balances: sled.open_tree("versioned_contracts_balances")?,
             contracts_outputs: sled.open_tree("contracts_outputs")?,
+            contracts_transactions: sled.open_tree("contracts_transactions")?,
+            transactions_contract: sled.open_tree("transactions_contract")?,
             assets_supply: sled.open_tree("assets_supply")?,
             versioned_assets_supply: sled.open_tree("versioned_assets_supply")?,
             energy_resources: sled.open_tree("energy_resources")?,
@@ -377,6 +386,14 @@ impl SledStorage {
         }
     }
 
+    // Iter over the keys in the inclusive range, in ascending order
+    pub(super) fn range_keys(snapshot: Option<&Snapshot>, tree: &Tree, start: &[u8], end: &[u8]) -> impl Iterator<Item = sled::Result<IVec>> {
+        match snapshot {
+            Some(snapshot) => Either::Left(snapshot.range_keys(tree, start, end)),
+            None => Either::Right(tree.range(start..=end).keys())
+        }
+    }
+
     // Iter over a tree entries
     pub(super) fn iter(snapshot: Option<&Snapshot>, tree: &Tree) -> impl Iterator<Item = sled::Result<(IVec, IVec)>> {
         match snapshot {
@@ -717,6 +734,25 @@ impl Storage for SledStorage {
         debug!("Flushed {} bytes", n);
         Ok(())
     }
+
+    // Contract data keys are hashed with the contract, we can't know to which contract they belong
+    fn set_prune_retained_contracts(&mut self, contracts: HashSet<Hash>) -> Result<(), BlockchainError> {
+        if !contracts.is_empty() {
+            return Err(BlockchainError::UnsupportedOperation)
+        }
+
+        Ok(())
+    }
+
+    // Sled doesn't expose any manual compaction
+    async fn compact(&self) -> Result<(), BlockchainError> {
+        Err(BlockchainError::UnsupportedOperation)
+    }
+
+    // Sled has no checkpoint support
+    async fn export_snapshot(&self, _: TopoHeight, _: &str) -> Result<(), BlockchainError> {
+        Err(BlockchainError::UnsupportedOperation)
+    }
 }
 
 // EnergyProvider implementation for SledStorage
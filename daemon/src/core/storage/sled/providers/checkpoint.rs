@@ -0,0 +1,22 @@
+use std::collections::HashSet;
+use async_trait::async_trait;
+use log::trace;
+use terminos_common::{crypto::Hash, serializer::Serializer};
+use crate::core::{
+    error::BlockchainError,
+    storage::{sled::CHECKPOINTS, CheckpointProvider, SledStorage},
+};
+
+#[async_trait]
+impl CheckpointProvider for SledStorage {
+    async fn get_checkpoints(&self) -> Result<HashSet<Hash>, BlockchainError> {
+        trace!("get checkpoints");
+        Ok(self.load_optional_from_disk(&self.extra, CHECKPOINTS)?.unwrap_or_default())
+    }
+
+    async fn set_checkpoints(&mut self, checkpoints: &HashSet<Hash>) -> Result<(), BlockchainError> {
+        trace!("set {} checkpoints", checkpoints.len());
+        Self::insert_into_disk(self.snapshot.as_mut(), &self.extra, CHECKPOINTS, checkpoints.to_bytes())?;
+        Ok(())
+    }
+}
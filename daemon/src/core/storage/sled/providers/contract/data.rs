@@ -99,6 +99,11 @@ impl ContractDataProvider for SledStorage {
             .filter_map(|res| async move { res.await.transpose() })
         )
     }
+
+    async fn get_contract_data_entries_range_at_maximum_topoheight(&self, _: &Hash, _: Option<&ValueCell>, _: usize, _: TopoHeight) -> Result<Vec<(ValueCell, ValueCell)>, BlockchainError> {
+        // Contract data keys are hashed with the contract, we can't iterate over them
+        Err(BlockchainError::UnsupportedOperation)
+    }
 }
 
 impl SledStorage {
@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use terminos_common::{
+    block::TopoHeight,
     contract::ContractOutput,
     crypto::Hash,
     serializer::Serializer
@@ -19,13 +20,56 @@ impl ContractOutputsProvider for SledStorage {
         self.load_from_disk(&self.contracts_outputs, tx_hash.as_bytes(), DiskContext::ContractOutputs)
     }
 
-    async fn set_contract_outputs_for_tx(&mut self, tx_hash: &Hash, contract_output: &Vec<ContractOutput>) -> Result<(), BlockchainError> {
+    async fn set_contract_outputs_for_tx(&mut self, tx_hash: &Hash, contract: &Hash, topoheight: TopoHeight, contract_output: &Vec<ContractOutput>) -> Result<(), BlockchainError> {
         Self::insert_into_disk(self.snapshot.as_mut(), &self.contracts_outputs, tx_hash.as_bytes(), contract_output.to_bytes())?;
+
+        // A contract that failed to be deployed is not indexed
+        if self.contains_data(&self.contracts, contract.as_bytes())? {
+            Self::insert_into_disk(self.snapshot.as_mut(), &self.contracts_transactions, Self::get_contract_transaction_key(contract, topoheight, tx_hash), &[])?;
+            Self::insert_into_disk(self.snapshot.as_mut(), &self.transactions_contract, tx_hash.as_bytes(), (contract.clone(), topoheight).to_bytes())?;
+        }
+
         Ok(())
     }
 
     async fn delete_contract_outputs_for_tx(&mut self, tx_hash: &Hash) -> Result<(), BlockchainError> {
         Self::remove_from_disk_without_reading(self.snapshot.as_mut(), &self.contracts_outputs, tx_hash.as_bytes())?;
+        if let Some((contract, topoheight)) = Self::remove_from_disk::<(Hash, TopoHeight)>(self.snapshot.as_mut(), &self.transactions_contract, tx_hash.as_bytes())? {
+            Self::remove_from_disk_without_reading(self.snapshot.as_mut(), &self.contracts_transactions, &Self::get_contract_transaction_key(&contract, topoheight, tx_hash))?;
+        }
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn get_contract_transactions(&self, contract: &Hash, start_topoheight: TopoHeight, limit: usize) -> Result<Vec<(TopoHeight, Hash)>, BlockchainError> {
+        if !self.contains_data(&self.contracts, contract.as_bytes())? {
+            return Ok(Vec::new())
+        }
+
+        // Keys are ordered by topoheight under the contract prefix
+        let start = Self::get_contract_transaction_key(contract, start_topoheight, &Hash::zero());
+        let end = Self::get_contract_transaction_key(contract, TopoHeight::MAX, &Hash::max());
+
+        let mut txs = Vec::new();
+        for key in Self::range_keys(self.snapshot.as_ref(), &self.contracts_transactions, &start, &end) {
+            if txs.len() >= limit {
+                break;
+            }
+
+            let key = key?;
+            let (_, topoheight, tx_hash) = <(Hash, TopoHeight, Hash)>::from_bytes(&key)?;
+            txs.push((topoheight, tx_hash));
+        }
+
+        Ok(txs)
+    }
+}
+
+impl SledStorage {
+    fn get_contract_transaction_key(contract: &Hash, topoheight: TopoHeight, tx_hash: &Hash) -> [u8; 72] {
+        let mut buf = [0u8; 72];
+        buf[0..32].copy_from_slice(contract.as_bytes());
+        buf[32..40].copy_from_slice(&topoheight.to_be_bytes());
+        buf[40..72].copy_from_slice(tx_hash.as_bytes());
+        buf
+    }
+}
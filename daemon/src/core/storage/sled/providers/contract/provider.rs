@@ -8,16 +8,19 @@ use terminos_common::{
     tokio::try_block_on
 };
 use terminos_vm::ValueCell;
-use crate::core::storage::{
-    AccountProvider,
-    AssetProvider,
-    BalanceProvider,
-    ContractBalanceProvider,
-    ContractDataProvider,
-    ContractProvider as _,
-    NetworkProvider,
-    SledStorage,
-    SupplyProvider
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        AccountProvider,
+        AssetProvider,
+        BalanceProvider,
+        ContractBalanceProvider,
+        ContractDataProvider,
+        ContractProvider as _,
+        NetworkProvider,
+        SledStorage,
+        SupplyProvider
+    }
 };
 
 impl ContractStorage for SledStorage {
@@ -51,6 +54,11 @@ impl ContractStorage for SledStorage {
         let res = try_block_on(self.has_contract_at_maximum_topoheight(contract, topoheight))??;
         Ok(res)
     }
+
+    fn iter_data<'a>(&'a self, _: &'a Hash, _: TopoHeight) -> Result<Box<dyn Iterator<Item = Result<(ValueCell, ValueCell), anyhow::Error>> + 'a>, anyhow::Error> {
+        // Contract data keys are hashed with the contract, we can't iterate over them
+        Err(BlockchainError::UnsupportedOperation.into())
+    }
 }
 
 impl ContractProvider for SledStorage {
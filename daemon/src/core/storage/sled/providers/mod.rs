@@ -19,4 +19,5 @@ mod tips_provider;
 mod contract;
 mod versioned;
 mod cache;
-mod state;
\ No newline at end of file
+mod state;
+mod checkpoint;
\ No newline at end of file
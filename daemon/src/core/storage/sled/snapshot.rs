@@ -185,6 +185,38 @@ impl Snapshot {
         }
     }
 
+    // Iterate over the keys in the inclusive range, in ascending order
+    pub fn range_keys(&self, tree: &Tree, start: &[u8], end: &[u8]) -> impl Iterator<Item = sled::Result<IVec>> {
+        match self.trees.get(&tree.name()) {
+            Some(Some(entries)) => {
+                let original = tree.range(start..=end)
+                    .keys()
+                    .filter_map_ok(|v| {
+                        if !entries.writes.contains_key(&v) {
+                            Some(v)
+                        } else {
+                            None
+                        }
+                    });
+
+                // Both are sorted, merge them to keep the order
+                let changes = entries.writes.range::<[u8], _>(start..=end)
+                    .filter(|(_, v)| v.is_some())
+                    .map(|(k, _)| Ok(k.clone()))
+                    .merge_by(original, |a, b| match (a, b) {
+                        (Ok(a), Ok(b)) => a <= b,
+                        // Return the error as soon as possible
+                        _ => false
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter();
+
+                Either::Left(changes)
+            },
+            _ => Either::Right(tree.range(start..=end).keys())
+        }
+    }
+
     pub fn iter(&self, tree: &Tree) -> impl Iterator<Item = sled::Result<(IVec, IVec)>> {
         match self.trees.get(&tree.name()) {
             Some(Some(entries)) => {
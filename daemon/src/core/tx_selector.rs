@@ -8,14 +8,65 @@ use std::{
     sync::Arc,
     cmp::Ordering
 };
+use serde::{Deserialize, Serialize};
 use terminos_common::{
     transaction::Transaction,
     crypto::{
         Hash,
         PublicKey
-    }
+    },
+    time::TimestampSeconds
 };
 
+// Strategy used to order the transactions senders in the block template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionStrategy {
+    /// Select first the TX with the highest fee
+    #[default]
+    #[clap(name = "fee")]
+    Fee,
+    /// Select first the TX with the highest fee per byte
+    #[clap(name = "fee-per-byte")]
+    FeePerByte,
+    /// Select first the TX that was seen first in the mempool
+    #[clap(name = "fifo")]
+    Fifo,
+}
+
+// Values of a TX used by the strategy to order the groups
+#[derive(Debug, Clone, Copy)]
+struct SelectionKey {
+    fee: u64,
+    size: usize,
+    first_seen: TimestampSeconds
+}
+
+impl SelectionKey {
+    fn from_entry(entry: &TxSelectorEntry) -> Self {
+        Self {
+            fee: entry.tx.get_fee(),
+            size: entry.size,
+            first_seen: entry.first_seen
+        }
+    }
+}
+
+impl SelectionStrategy {
+    // Compare two TXs, the greatest one is selected first
+    fn compare(&self, a: &SelectionKey, b: &SelectionKey) -> Ordering {
+        match self {
+            Self::Fee => a.fee.cmp(&b.fee),
+            // Cross multiply to compare the ratios without losing precision
+            Self::FeePerByte => (a.fee as u128 * b.size as u128).cmp(&(b.fee as u128 * a.size as u128))
+                .then_with(|| a.fee.cmp(&b.fee)),
+            // The oldest TX has the highest priority
+            Self::Fifo => b.first_seen.cmp(&a.first_seen)
+                .then_with(|| a.fee.cmp(&b.fee)),
+        }
+    }
+}
+
 // this struct is used to store transaction with its hash and its size in bytes
 pub struct TxSelectorEntry<'a> {
     // Hash of the transaction
@@ -23,7 +74,9 @@ pub struct TxSelectorEntry<'a> {
     // Current transaction
     pub tx: &'a Arc<Transaction>,
     // Size in bytes of the TX
-    pub size: usize
+    pub size: usize,
+    // Timestamp in seconds when the TX was added to the mempool
+    pub first_seen: TimestampSeconds
 }
 
 impl PartialEq for TxSelectorEntry<'_> {
@@ -35,66 +88,83 @@ impl PartialEq for TxSelectorEntry<'_> {
 impl Eq for TxSelectorEntry<'_> {}
 
 // this struct is used to store transactions in a queue
-// and to order them by fees
+// and to order them using the selection strategy
 // Each Transactions is for a specific sender
-#[derive(PartialEq, Eq)]
-struct Transactions<'a>(VecDeque<TxSelectorEntry<'a>>);
+struct Transactions<'a> {
+    strategy: SelectionStrategy,
+    entries: VecDeque<TxSelectorEntry<'a>>
+}
+
+impl PartialEq for Transactions<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Transactions<'_> {}
 
 impl PartialOrd for Transactions<'_> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.0.front().map(|e| e.tx.get_fee()).partial_cmp(&other.0.front().map(|e| e.tx.get_fee()))
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Transactions<'_> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.0.front().map(|e| e.tx.get_fee()).cmp(&other.0.front().map(|e| e.tx.get_fee()))
+        match (self.entries.front(), other.entries.front()) {
+            (Some(a), Some(b)) => self.strategy.compare(&SelectionKey::from_entry(a), &SelectionKey::from_entry(b)),
+            (a, b) => a.is_some().cmp(&b.is_some())
+        }
     }
 }
 
 // TX selector is used to select transactions from the mempool
 // It create sub groups of transactions by sender and order them by nonces
-// It joins all sub groups in a queue that is ordered by fees
+// It joins all sub groups in a queue that is ordered by the selection strategy
 pub struct TxSelector<'a> {
+    strategy: SelectionStrategy,
     queue: BinaryHeap<Transactions<'a>>
 }
 
 impl<'a> TxSelector<'a> {
     // Create a TxSelector from a list of groups
-    pub fn grouped<I>(groups: I) -> Self
+    pub fn grouped<I>(groups: I, strategy: SelectionStrategy) -> Self
     where
         I: Iterator<Item = Vec<TxSelectorEntry<'a>>> + ExactSizeIterator
     {
         let mut queue = BinaryHeap::with_capacity(groups.len());
 
         // push every group to the queue
-        queue.extend(groups.map(|v| Transactions(VecDeque::from(v))));
+        queue.extend(groups.map(|v| Transactions { strategy, entries: VecDeque::from(v) }));
 
         Self {
+            strategy,
             queue
         }
     }
 
     // Create a TxSelector with a given capacity
-    pub fn with_capacity(capacity: usize) -> Self {
+    pub fn with_capacity(capacity: usize, strategy: SelectionStrategy) -> Self {
         Self {
+            strategy,
             queue: BinaryHeap::with_capacity(capacity)
         }
     }
 
-    // Create a TxSelector from a list of transactions with their hash and size
-    pub fn new<I>(iter: I) -> Self
+    // Create a TxSelector from a list of transactions with their size, first seen timestamp and hash
+    pub fn new<I>(iter: I, strategy: SelectionStrategy) -> Self
     where
-        I: Iterator<Item = (usize, &'a Arc<Hash>, &'a Arc<Transaction>)>
+        I: Iterator<Item = (usize, TimestampSeconds, &'a Arc<Hash>, &'a Arc<Transaction>)>
     {
         let mut groups: HashMap<&PublicKey, Vec<TxSelectorEntry>> = HashMap::new();
 
         // Create groups of transactions
-        for (size, hash, tx) in iter {
+        for (size, first_seen, hash, tx) in iter {
             let entry = TxSelectorEntry {
                 hash,
                 tx,
-                size
+                size,
+                first_seen
             };
 
             match groups.entry(tx.get_source()) {
@@ -112,23 +182,23 @@ impl<'a> TxSelector<'a> {
             v.sort_by(|a, b| a.tx.get_nonce().cmp(&b.tx.get_nonce()));
             v
         });
-        Self::grouped(iter)
+        Self::grouped(iter, strategy)
     }
 
     // Add a new group
     pub fn push_group<V: Into<VecDeque<TxSelectorEntry<'a>>>>(&mut self, group: V) {
-        self.queue.push(Transactions(group.into()));
+        self.queue.push(Transactions { strategy: self.strategy, entries: group.into() });
     }
 
-    // Get the next transaction with the highest fee
+    // Get the next transaction with the highest priority
     pub fn next(&mut self) -> Option<TxSelectorEntry<'a>> {
-        // get the group with the highest fee
+        // get the group with the highest priority
         let mut group = self.queue.pop()?;
-        // get the entry with the highest fee from this group
-        let entry = group.0.pop_front()?;
+        // get the next entry by nonce from this group
+        let entry = group.entries.pop_front()?;
 
         // if its not empty, push it back to the queue
-        if !group.0.is_empty() {
+        if !group.entries.is_empty() {
             self.queue.push(group);
         }
 
@@ -139,4 +209,45 @@ impl<'a> TxSelector<'a> {
     pub fn is_empty(&self) -> bool {
         self.queue.is_empty()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(fee: u64, size: usize, first_seen: TimestampSeconds) -> SelectionKey {
+        SelectionKey {
+            fee,
+            size,
+            first_seen
+        }
+    }
+
+    #[test]
+    fn test_fee_strategy_prefers_high_fee() {
+        let low = key(1000, 100, 0);
+        let high = key(5000, 1000, 10);
+        assert_eq!(SelectionStrategy::Fee.compare(&high, &low), Ordering::Greater);
+        assert_eq!(SelectionStrategy::Fee.compare(&low, &high), Ordering::Less);
+    }
+
+    #[test]
+    fn test_fee_per_byte_strategy() {
+        // Lower total fee but a better rate
+        let small = key(1000, 100, 0);
+        let big = key(5000, 1000, 0);
+        assert_eq!(SelectionStrategy::FeePerByte.compare(&small, &big), Ordering::Greater);
+
+        // Same rate, highest fee wins
+        let double = key(2000, 200, 0);
+        assert_eq!(SelectionStrategy::FeePerByte.compare(&double, &small), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_fifo_strategy() {
+        let old = key(1000, 100, 5);
+        let new = key(5000, 100, 10);
+        assert_eq!(SelectionStrategy::Fifo.compare(&old, &new), Ordering::Greater);
+        assert_eq!(SelectionStrategy::Fifo.compare(&new, &old), Ordering::Less);
+    }
+}
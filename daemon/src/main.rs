@@ -3,7 +3,7 @@ pub mod p2p;
 pub mod core;
 pub mod config;
 
-use config::{DEV_PUBLIC_KEY, STABLE_LIMIT};
+use config::{get_genesis_block_hash, DEV_PUBLIC_KEY, STABLE_LIMIT};
 use human_bytes::human_bytes;
 use humantime::{format_duration, Duration as HumanDuration};
 use log::{debug, error, info, trace, warn};
@@ -58,6 +58,7 @@ use core::{
     },
     blockdag,
     config::{Config as InnerConfig, StorageBackend},
+    error::BlockchainError,
     hard_fork::{
         get_block_time_target_for_version,
         get_pow_algorithm_for_version,
@@ -66,6 +67,7 @@ use core::{
     storage::{
         RocksStorage,
         SledStorage,
+        StateProvider,
         Storage
     }
 };
@@ -263,11 +265,37 @@ async fn main() -> Result<()> {
                 None
             };
 
+            if blockchain_config.import_snapshot.is_some() {
+                return Err(anyhow::anyhow!("Importing a snapshot is only supported by RocksDB"));
+            }
+
             let storage = SledStorage::new(dir_path.to_owned(), use_cache, config.network, blockchain_config.sled.internal_cache_size, blockchain_config.sled.internal_db_mode)?;
             start_chain(prompt, storage, config).await
         },
         StorageBackend::RocksDB => {
+            let metadata = match blockchain_config.import_snapshot.as_ref() {
+                Some(snapshot_dir) => {
+                    info!("Importing snapshot from {}", snapshot_dir);
+                    let expected_genesis = blockchain_config.genesis_block_hash.as_ref()
+                        .or_else(|| get_genesis_block_hash(&config.network));
+
+                    let metadata = RocksStorage::import_snapshot(snapshot_dir, &dir_path, config.network, expected_genesis)?;
+                    Some(metadata)
+                },
+                None => None
+            };
+
             let storage = RocksStorage::new(&dir_path, config.network, &blockchain_config.rocksdb);
+            if let Some(metadata) = metadata {
+                // The checkpoint contains the chain state, make sure it matches the exported one
+                let topoheight = storage.get_top_topoheight().await?;
+                if topoheight != metadata.topoheight {
+                    return Err(BlockchainError::SnapshotTopoHeightMismatch(metadata.topoheight, topoheight).into());
+                }
+
+                info!("Snapshot imported at topoheight {}", topoheight);
+            }
+
             start_chain(prompt, storage, config).await
         }
     }
@@ -305,6 +333,7 @@ async fn run_prompt<S: Storage>(prompt: ShareablePrompt, blockchain: Arc<Blockch
     command_manager.add_command(Command::new("top_block", "Print top block", CommandHandler::Async(async_handler!(top_block::<S>))))?;
     command_manager.add_command(Command::with_required_arguments("pop_blocks", "Delete last N blocks", vec![Arg::new("amount", ArgType::Number)], CommandHandler::Async(async_handler!(pop_blocks::<S>))))?;
     command_manager.add_command(Command::new("clear_mempool", "Clear all transactions in mempool", CommandHandler::Async(async_handler!(clear_mempool::<S>))))?;
+    command_manager.add_command(Command::with_required_arguments("accept_transactions", "Enable or disable new transactions in mempool", vec![Arg::new("accept", ArgType::Bool)], CommandHandler::Async(async_handler!(accept_transactions::<S>))))?;
     command_manager.add_command(Command::with_arguments("add_tx", "Add a TX in hex format in mempool", vec![Arg::new("hex", ArgType::String)], vec![Arg::new("broadcast", ArgType::Bool)], CommandHandler::Async(async_handler!(add_tx::<S>))))?;
     command_manager.add_command(Command::with_required_arguments("prune_chain", "Prune the chain until the specified topoheight", vec![Arg::new("topoheight", ArgType::Number)], CommandHandler::Async(async_handler!(prune_chain::<S>))))?;
     command_manager.add_command(Command::new("status", "Current daemon status", CommandHandler::Async(async_handler!(status::<S>))))?;
@@ -1104,6 +1133,20 @@ async fn clear_mempool<S: Storage>(manager: &CommandManager, _: ArgumentManager)
     Ok(())
 }
 
+async fn accept_transactions<S: Storage>(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let accept = arguments.get_value("accept")?.to_bool()?;
+    let context = manager.get_context().lock()?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    blockchain.set_accept_transactions(accept);
+    if accept {
+        manager.message("Mempool is now accepting new transactions");
+    } else {
+        manager.message("Mempool is now rejecting new transactions");
+    }
+
+    Ok(())
+}
+
 async fn snapshot_mode<S: Storage>(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
@@ -0,0 +1,156 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration
+};
+use terminos_common::time::{get_current_time_in_millis, TimestampMillis};
+use crate::config::MILLIS_PER_SECOND;
+
+// Token bucket used to throttle the bytes sent to a peer
+// Capacity is equal to one second of the configured rate
+// so a peer can burst up to the rate limit before being throttled
+// The bucket starts full on its first use
+pub struct TokenBucket {
+    // Tokens (bytes) currently available
+    available: u64,
+    // Last time the bucket was refilled, None if it was never used
+    // This can be in the future if we are in debt
+    last_refill: Option<TimestampMillis>,
+}
+
+impl TokenBucket {
+    pub fn new() -> Self {
+        Self {
+            available: 0,
+            last_refill: None,
+        }
+    }
+
+    // Consume the requested amount of bytes at the given rate (bytes per second)
+    // Returns how long the caller must wait before sending them
+    pub fn consume(&mut self, amount: u64, rate: u64, now: TimestampMillis) -> Duration {
+        if rate == 0 {
+            return Duration::ZERO
+        }
+
+        // Refill the bucket based on the elapsed time
+        // or fill it completely if it's the first use
+        match self.last_refill {
+            Some(last_refill) => {
+                let elapsed = now.saturating_sub(last_refill);
+                if elapsed > 0 {
+                    let refill = elapsed.saturating_mul(rate) / MILLIS_PER_SECOND;
+                    self.available = self.available.saturating_add(refill).min(rate);
+                    self.last_refill = Some(now);
+                }
+            },
+            None => {
+                self.available = rate;
+                self.last_refill = Some(now);
+            }
+        }
+
+        if self.available >= amount {
+            self.available -= amount;
+            return Duration::ZERO
+        }
+
+        // Not enough tokens, compute the missing time
+        // and move the refill timestamp in the future
+        // so the waiting period isn't credited twice
+        let missing = amount - self.available;
+        self.available = 0;
+        let wait = missing.saturating_mul(MILLIS_PER_SECOND).div_ceil(rate);
+        self.last_refill = self.last_refill.map(|v| v.max(now) + wait);
+
+        Duration::from_millis(wait)
+    }
+}
+
+// Measure the bytes rate over a rolling window of one second
+pub struct RateMeter {
+    // Start of the current window
+    window_start: AtomicU64,
+    // Bytes counted in the current window
+    window_bytes: AtomicU64,
+    // Last rate computed in bytes per second
+    last_rate: AtomicU64,
+}
+
+impl RateMeter {
+    pub fn new() -> Self {
+        Self {
+            window_start: AtomicU64::new(get_current_time_in_millis()),
+            window_bytes: AtomicU64::new(0),
+            last_rate: AtomicU64::new(0),
+        }
+    }
+
+    // Record the bytes transferred
+    pub fn record(&self, bytes: u64) {
+        self.window_bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.update(get_current_time_in_millis());
+    }
+
+    // Close the current window if it's over
+    fn update(&self, now: TimestampMillis) {
+        let start = self.window_start.load(Ordering::Relaxed);
+        let elapsed = now.saturating_sub(start);
+        if elapsed >= MILLIS_PER_SECOND && self.window_start.compare_exchange(start, now, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+            let bytes = self.window_bytes.swap(0, Ordering::Relaxed);
+            self.last_rate.store(bytes * MILLIS_PER_SECOND / elapsed, Ordering::Relaxed);
+        }
+    }
+
+    // Get the current rate in bytes per second
+    // If nothing was recorded during the last window, the rate is considered as zero
+    pub fn get_rate(&self) -> u64 {
+        let now = get_current_time_in_millis();
+        self.update(now);
+
+        let start = self.window_start.load(Ordering::Relaxed);
+        if now.saturating_sub(start) >= 2 * MILLIS_PER_SECOND {
+            return 0
+        }
+
+        self.last_rate.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_no_limit() {
+        let mut bucket = TokenBucket::new();
+        assert_eq!(bucket.consume(1024, 0, get_current_time_in_millis()), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_token_bucket_first_use() {
+        let mut bucket = TokenBucket::new();
+        // A fresh bucket allows to burst up to the rate
+        assert_eq!(bucket.consume(1000, 1000, 5000), Duration::ZERO);
+        // Then it is empty
+        assert_eq!(bucket.consume(500, 1000, 5000), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_token_bucket_throttle() {
+        let mut bucket = TokenBucket { available: 0, last_refill: Some(0) };
+        // After one second, we have 1000 bytes available
+        assert_eq!(bucket.consume(1000, 1000, 1000), Duration::ZERO);
+        // Nothing left, we must wait half a second for 500 bytes
+        assert_eq!(bucket.consume(500, 1000, 1000), Duration::from_millis(500));
+        // The waiting period is not credited again
+        assert_eq!(bucket.consume(500, 1000, 1500), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_token_bucket_capacity() {
+        let mut bucket = TokenBucket { available: 0, last_refill: Some(0) };
+        // Bucket can't hold more than one second of tokens
+        assert_eq!(bucket.consume(0, 1000, 10_000), Duration::ZERO);
+        assert_eq!(bucket.available, 1000);
+    }
+}
@@ -6,6 +6,7 @@ use log::{debug, error, info, trace, warn};
 use tokio::try_join;
 use terminos_common::{
     account::{VersionedBalance, VersionedNonce},
+    api::daemon::{BootstrapProgressEvent, NotifyEvent},
     crypto::{Hash, PublicKey},
     immutable::Immutable,
     versioned_type::State,
@@ -31,6 +32,7 @@ use crate::{
         packet::{
             BlockMetadata,
             BootstrapChainResponse,
+            StepKind,
             StepRequest,
             StepResponse,
             ObjectRequest,
@@ -341,6 +343,21 @@ impl<S: Storage> P2pServer<S> {
         Ok(())
     }
 
+    // Notify the RPC clients about the fast sync progress
+    async fn notify_bootstrap_progress(&self, kind: StepKind, processed: u64, total: Option<u64>, completed: bool) {
+        if let Some(rpc) = self.blockchain.get_rpc().read().await.as_ref() {
+            if rpc.is_event_tracked(&NotifyEvent::BootstrapProgress).await {
+                let value = BootstrapProgressEvent {
+                    step: Cow::Borrowed(kind.name()),
+                    processed,
+                    total,
+                    percentage: kind.progress(completed)
+                };
+                rpc.notify_clients_with(&NotifyEvent::BootstrapProgress, value).await;
+            }
+        }
+    }
+
     // first, retrieve chain info of selected peer
     // We retrieve all assets through pagination,
     // then we fetch all keys with its nonces and its balances (also through pagination)
@@ -366,6 +383,10 @@ impl<S: Storage> P2pServer<S> {
         let mut top_height: u64 = 0;
         let mut top_block_hash: Option<Hash> = None;
 
+        // Items processed in the current step for the progress event
+        let mut current_kind = StepKind::ChainInfo;
+        let mut processed: u64 = 0;
+
         loop {
             let response = if let Some(step) = step.take() {
                 info!("Requesting step {:?}", step.kind());
@@ -375,7 +396,14 @@ impl<S: Storage> P2pServer<S> {
                 break;
             };
 
-            step = match response {
+            let kind = response.kind();
+            if kind != current_kind {
+                current_kind = kind;
+                processed = 0;
+            }
+            processed += response.items_count() as u64;
+
+            let next = match response {
                 StepResponse::ChainInfo(common_point, topoheight, height, hash) => {
                     // first, check the common point in case we deviated from the chain
                     if let Some(common_point) = common_point {
@@ -591,6 +619,13 @@ impl<S: Storage> P2pServer<S> {
                     return Err(P2pError::InvalidPacket.into());
                 }
             };
+
+            // Only the blocks metadata count is known in advance
+            let total = (kind == StepKind::BlocksMetadata).then(|| PRUNE_SAFETY_LIMIT + 1);
+            let completed = next.as_ref().map_or(true, |next| next.kind() != kind);
+            self.notify_bootstrap_progress(kind, processed, total, completed).await;
+
+            step = next;
         }
 
         info!("Reload caches from disk");
@@ -73,7 +73,7 @@ impl<S: Storage> P2pServer<S> {
             debug!("locked storage for sync chain request");
             let request = ChainRequest::new(self.build_list_of_blocks_id(&*storage).await?, requested_max_size as u16);
             trace!("Built a chain request with {} blocks", request.size());
-            let ping = self.build_generic_ping_packet_with_storage(&*storage).await?;
+            let ping = self.build_generic_ping_packet_with_storage(&*storage).await?.for_peer(peer);
             PacketWrapper::new(Cow::Owned(request), Cow::Owned(ping))
         };
 
@@ -91,6 +91,18 @@ impl<S: Storage> P2pServer<S> {
             return Err(P2pError::InvalidChainResponseSize(response.blocks_size(), requested_max_size).into())
         }
 
+        // The peer can't send us the blocks below its pruned topoheight
+        // so the normal chain sync can't bridge the gap
+        if let Some(pruned_topoheight) = peer.get_pruned_topoheight() {
+            let topoheight = response.get_common_point()
+                .map(|common_point| common_point.get_topoheight())
+                .unwrap_or_else(|| self.blockchain.get_topo_height());
+
+            if topoheight < pruned_topoheight {
+                return Err(P2pError::SyncGapTooLarge(topoheight, pruned_topoheight).into())
+            }
+        }
+
         // Update last chain sync time
         *last_chain_sync = get_current_time_in_millis();
 
@@ -1,13 +1,17 @@
 use crate::config::{
+    PEER_COMPRESSION_LEVEL,
+    PEER_COMPRESSION_THRESHOLD,
+    PEER_MAX_PACKET_SIZE,
     PEER_TIMEOUT_DISCONNECT,
     PEER_TIMEOUT_INIT_CONNECTION,
     PEER_SEND_BYTES_TIMEOUT
 };
 use super::{
+    bandwidth::{RateMeter, TokenBucket},
     diffie_hellman,
     encryption::{Encryption, CipherSide},
     error::P2pError,
-    packet::Packet,
+    packet::{Packet, KEY_EXCHANGE_ID},
     EncryptionKey
 };
 use std::{
@@ -17,6 +21,7 @@ use std::{
     net::SocketAddr,
     sync::atomic::{
         AtomicBool,
+        AtomicU64,
         AtomicUsize,
         Ordering
     },
@@ -34,9 +39,13 @@ use terminos_common::{
             TcpStream
         },
         sync::Mutex,
-        time::timeout
+        time::{sleep, timeout}
+    },
+    time::{
+        TimestampSeconds,
+        get_current_time_in_millis,
+        get_current_time_in_seconds
     },
-    time::{TimestampSeconds, get_current_time_in_seconds},
     serializer::{Reader, Serializer},
 };
 use log::{debug, error, trace, warn};
@@ -81,9 +90,26 @@ pub struct Connection {
     // How many key rotation we sent
     rotate_key_out: AtomicUsize,
     // Encryption state used for packets
-    encryption: Encryption
+    encryption: Encryption,
+    // Token bucket used to throttle the bytes sent
+    send_bucket: Mutex<TokenBucket>,
+    // Rate limit override for this connection in bytes per second
+    rate_limit: AtomicU64,
+    // Is the rate limit override set
+    has_rate_limit: AtomicBool,
+    // Measured rate of bytes sent
+    send_rate: RateMeter,
+    // Measured rate of bytes read
+    recv_rate: RateMeter,
+    // Compression negotiated during the handshake
+    // If enabled, each packet (except key exchange) has a flag byte after its id
+    compression: AtomicBool,
 }
 
+// Flags used in the compressed packet framing
+const COMPRESSION_FLAG_RAW: u8 = 0;
+const COMPRESSION_FLAG_ZSTD: u8 = 1;
+
 // We are rotating every 1GB sent
 const ROTATE_EVERY_N_BYTES: usize = 1024 * 1024 * 1024;
 
@@ -104,6 +130,12 @@ impl Connection {
             rotate_key_in: AtomicUsize::new(0),
             rotate_key_out: AtomicUsize::new(0),
             encryption: Encryption::new(),
+            send_bucket: Mutex::new(TokenBucket::new()),
+            rate_limit: AtomicU64::new(0),
+            has_rate_limit: AtomicBool::new(false),
+            send_rate: RateMeter::new(),
+            recv_rate: RateMeter::new(),
+            compression: AtomicBool::new(false),
         }
     }
 
@@ -128,8 +160,8 @@ impl Connection {
             Duration::from_millis(PEER_TIMEOUT_INIT_CONNECTION),
             self.read_packet(buffer, 256)
         ).await?? else {
-            error!("Expected KeyExchange packet");
-            return Err(P2pError::InvalidPacket);
+            error!("Expected KeyExchange packet from {}", self.addr);
+            return Err(self.key_exchange_failure());
         };
 
         // Now that we got the peer key, update our encryption state
@@ -181,8 +213,8 @@ impl Connection {
             Duration::from_millis(PEER_TIMEOUT_INIT_CONNECTION),
             self.read_packet(buffer, 256)
         ).await?? else {
-            error!("Expected KeyExchange packet");
-            return Err(P2pError::InvalidPacket);
+            error!("Expected KeyExchange packet from {}", self.addr);
+            return Err(self.key_exchange_failure());
         };
 
         trace!("Received DH key from {}", self.addr);
@@ -208,7 +240,10 @@ impl Connection {
         }
 
         // the secret generated is used to encrypt our newly generated encryption key
-        let secret = keypair.get_shared_secret(&peer_dh_key);
+        let Some(secret) = keypair.get_shared_secret(&peer_dh_key) else {
+            error!("Received a low order Diffie-Hellman key from {}", self.addr);
+            return Err(self.key_exchange_failure());
+        };
 
         // Send our newly generated key if we initiated the connection
         {
@@ -227,20 +262,29 @@ impl Connection {
             Duration::from_millis(PEER_TIMEOUT_INIT_CONNECTION),
             self.read_packet(buffer, 256)
         ).await?? else {
-            error!("Expected KeyExchange packet");
-            return Err(P2pError::InvalidPacket);
+            error!("Expected KeyExchange packet from {}", self.addr);
+            return Err(self.key_exchange_failure());
         };
 
         trace!("Received encryption key from {}", self.addr);
 
         // Now that we got the shared peer key, update our encryption state
-        self.encryption.rotate_key(peer_key.into_owned(), CipherSide::Peer).await?;
+        if let Err(e) = self.encryption.rotate_key(peer_key.into_owned(), CipherSide::Peer).await {
+            error!("Invalid encryption key received from {}: {}", self.addr, e);
+            return Err(self.key_exchange_failure());
+        }
 
         trace!("Key exchange with {} successful", self.addr);
 
         Ok(peer_dh_key)
     }
 
+    // Track a failed key exchange and build its error
+    fn key_exchange_failure(&self) -> P2pError {
+        counter!("terminos_p2p_key_exchange_failures_total").increment(1);
+        P2pError::InvalidKeyExchange(self.addr)
+    }
+
     // Verify if its a outgoing connection
     pub fn is_out(&self) -> bool {
         self.out
@@ -284,7 +328,10 @@ impl Connection {
     // as all next packets will be encrypted with the new key and we have updated it before
     pub async fn rotate_peer_key(&self, key: EncryptionKey) -> P2pResult<()> {
         trace!("Rotating encryption key of peer {}", self.get_address());
-        self.encryption.rotate_key(key, CipherSide::Peer).await?;
+        if let Err(e) = self.encryption.rotate_key(key, CipherSide::Peer).await {
+            error!("Invalid encryption key received from {}: {}", self.addr, e);
+            return Err(self.key_exchange_failure());
+        }
         // Increment the key rotation counter
         self.rotate_key_in.fetch_add(1, Ordering::Relaxed);
         Ok(())
@@ -320,14 +367,82 @@ impl Connection {
         }
     }
 
+    // Enable the compression framing for this connection
+    // This must be called only once the handshake has been exchanged
+    pub fn set_compression(&self, enabled: bool) {
+        self.compression.store(enabled, Ordering::SeqCst);
+    }
+
+    // Is the compression negotiated with the peer
+    pub fn is_compression_enabled(&self) -> bool {
+        self.compression.load(Ordering::SeqCst)
+    }
+
+    // Frame the packet with the compression flag
+    // The packet id is kept uncompressed so it can still be read directly
+    // Only the packets above the threshold are compressed
+    fn compress_packet(&self, packet: &mut impl Buffer) -> P2pResult<()> {
+        let (id, body) = match packet.as_ref().split_first() {
+            Some((id, body)) if *id != KEY_EXCHANGE_ID => (*id, body),
+            _ => return Ok(())
+        };
+
+        let mut framed = Vec::with_capacity(packet.len() + 1);
+        framed.push(id);
+        if body.len() >= PEER_COMPRESSION_THRESHOLD {
+            let compressed = zstd::bulk::compress(body, PEER_COMPRESSION_LEVEL)?;
+            trace!("Compressed packet {} from {} to {} bytes (ratio: {:.2}) for {}", id, body.len(), compressed.len(), body.len() as f64 / compressed.len() as f64, self.addr);
+            framed.push(COMPRESSION_FLAG_ZSTD);
+            framed.extend_from_slice(&compressed);
+        } else {
+            framed.push(COMPRESSION_FLAG_RAW);
+            framed.extend_from_slice(body);
+        }
+
+        packet.truncate(0);
+        packet.extend_from_slice(&framed)
+            .map_err(|_| P2pError::InvalidPacketSize)?;
+
+        Ok(())
+    }
+
+    // Remove the compression framing from the packet bytes
+    fn decompress_packet<'a>(&self, bytes: &'a [u8]) -> P2pResult<Cow<'a, [u8]>> {
+        let (id, flag, body) = match bytes {
+            [id, ..] if *id == KEY_EXCHANGE_ID => return Ok(Cow::Borrowed(bytes)),
+            [id, flag, body @ ..] => (*id, *flag, body),
+            _ => return Err(P2pError::InvalidPacket)
+        };
+
+        let mut packet = Vec::with_capacity(bytes.len());
+        packet.push(id);
+        match flag {
+            COMPRESSION_FLAG_RAW => packet.extend_from_slice(body),
+            COMPRESSION_FLAG_ZSTD => {
+                // Limit the decompressed size to prevent any decompression bomb
+                let decompressed = zstd::bulk::decompress(body, PEER_MAX_PACKET_SIZE as usize)?;
+                trace!("Decompressed packet {} from {} to {} bytes (ratio: {:.2}) from {}", id, body.len(), decompressed.len(), decompressed.len() as f64 / body.len().max(1) as f64, self.addr);
+                packet.extend_from_slice(&decompressed);
+            },
+            _ => return Err(P2pError::InvalidPacket)
+        }
+
+        Ok(Cow::Owned(packet))
+    }
+
     // Send bytes to the peer
     // Encrypt must be used all time starting handshake
     async fn send_bytes_internal(&self, packet: &mut impl Buffer) -> P2pResult<()> {
+        if self.is_compression_enabled() {
+            self.compress_packet(packet)?;
+        }
+
         trace!("Sending {} bytes to {}", packet.len(), self.get_address());
         let mut stream = self.write.lock().await;
 
         // Count the bytes sent
         self.bytes_out.fetch_add(packet.len(), Ordering::Relaxed);
+        self.send_rate.record(packet.len() as u64);
 
         // We check if the encryption is enabled to manage it ourself here
         if self.encryption.is_ready() {
@@ -357,6 +472,44 @@ impl Connection {
         Ok(())
     }
 
+    // Wait until the bytes can be sent based on the rate limit (bytes per second)
+    // If the rate is zero, nothing is throttled
+    pub async fn throttle_send(&self, bytes: usize, rate: u64) {
+        if rate == 0 {
+            return;
+        }
+
+        let wait = {
+            let mut bucket = self.send_bucket.lock().await;
+            bucket.consume(bytes as u64, rate, get_current_time_in_millis())
+        };
+
+        if !wait.is_zero() {
+            trace!("Throttling {} bytes to {} for {:?}", bytes, self.addr, wait);
+            sleep(wait).await;
+        }
+    }
+
+    // Set a rate limit override in bytes per second for this connection
+    // None means the server rate limit is used
+    pub fn set_rate_limit(&self, rate: Option<u64>) {
+        if let Some(rate) = rate {
+            self.rate_limit.store(rate, Ordering::SeqCst);
+            self.has_rate_limit.store(true, Ordering::SeqCst);
+        } else {
+            self.has_rate_limit.store(false, Ordering::SeqCst);
+        }
+    }
+
+    // Get the rate limit override of this connection if set
+    pub fn get_rate_limit(&self) -> Option<u64> {
+        if self.has_rate_limit.load(Ordering::SeqCst) {
+            Some(self.rate_limit.load(Ordering::SeqCst))
+        } else {
+            None
+        }
+    }
+
     // Read packet bytes from the stream
     pub async fn read_packet_bytes(&self, buf: &mut [u8], max_size: u32) -> P2pResult<Vec<u8>> {
         let mut stream = self.read.lock().await;
@@ -374,6 +527,12 @@ impl Connection {
 
     // Deserialize a packet from bytes and verify its integrity
     pub async fn read_packet_from_bytes(&self, bytes: &[u8]) -> P2pResult<Packet<'static>> {
+        let bytes = if self.is_compression_enabled() {
+            self.decompress_packet(bytes)?
+        } else {
+            Cow::Borrowed(bytes)
+        };
+
         let mut reader = Reader::new(&bytes);
         let packet = Packet::read(&mut reader)?;
         if reader.total_read() != bytes.len() {
@@ -459,6 +618,7 @@ impl Connection {
             }
         }
         self.bytes_in.fetch_add(read, Ordering::Relaxed);
+        self.recv_rate.record(read as u64);
 
         Ok(read)
     }
@@ -519,6 +679,16 @@ impl Connection {
         self.bytes_in.load(Ordering::Relaxed)
     }
 
+    // Get the measured rate of bytes sent per second
+    pub fn send_rate(&self) -> u64 {
+        self.send_rate.get_rate()
+    }
+
+    // Get the measured rate of bytes read per second
+    pub fn recv_rate(&self) -> u64 {
+        self.recv_rate.get_rate()
+    }
+
     // Get the key rotation in
     pub fn key_rotation_in(&self) -> usize {
         self.rotate_key_in.load(Ordering::Relaxed)
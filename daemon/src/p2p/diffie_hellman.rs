@@ -84,9 +84,12 @@ impl DHKeyPair {
     }
 
     /// Get the shared secret between this keypair and another public key
+    /// Returns None if the public key is a low order point,
+    /// which would make the shared secret predictable
     #[inline]
-    pub fn get_shared_secret(&self, pub_key: &PublicKey) -> [u8; 32] {
-        self.priv_key.diffie_hellman(pub_key).to_bytes()
+    pub fn get_shared_secret(&self, pub_key: &PublicKey) -> Option<[u8; 32]> {
+        let secret = self.priv_key.diffie_hellman(pub_key);
+        secret.was_contributory().then(|| secret.to_bytes())
     }
 }
 
@@ -24,10 +24,10 @@ use terminos_common::{
 };
 use crate::{
     core::error::BlockchainError,
-    config::{CHAIN_SYNC_RESPONSE_MAX_BLOCKS, CHAIN_SYNC_RESPONSE_MIN_BLOCKS}
+    config::{CHAIN_SYNC_MAX_JITTER_PERCENT, CHAIN_SYNC_RESPONSE_MAX_BLOCKS, CHAIN_SYNC_RESPONSE_MIN_BLOCKS}
 };
 use super::{
-    peer_list::DiskError,
+    peer_list::{DiskError, Subnet, SubnetError},
     encryption::EncryptionError,
     packet::{
         StepKind,
@@ -44,12 +44,32 @@ pub enum P2pError {
     InvalidTempBanTime,
     #[error("Invalid fail count, it must be greater than 0")]
     InvalidFailCount,
+    #[error("Invalid handshake timeout, it must be greater than 0")]
+    InvalidHandshakeTimeout,
+    #[error("Invalid send queue limit, it must be greater than 0")]
+    InvalidSendQueueLimit,
+    #[error("Send queue of peer is full")]
+    SendQueueFull,
+    #[error("Invalid unsolicited responses limit, it must be greater than 0")]
+    InvalidUnsolicitedResponsesLimit,
+    #[error("Invalid object request timeout, it must be greater than 0")]
+    InvalidObjectRequestTimeout,
+    #[error("Invalid chain sync jitter, it must be at most {}%", CHAIN_SYNC_MAX_JITTER_PERCENT)]
+    InvalidChainSyncJitter,
+    #[error("Invalid peer id, it must be greater than 0")]
+    InvalidPeerId,
     #[error("Invalid Diffie-Hellman key")]
     InvalidDHKey,
+    #[error("Invalid key exchange with {}", _0)]
+    InvalidKeyExchange(SocketAddr),
     #[error("Invalid local port, it must be greater than 0")]
     InvalidLocalPort,
     #[error("disk error: {0}")]
     DiskError(#[from] DiskError),
+    #[error("Invalid subnet: {0}")]
+    InvalidSubnet(#[from] SubnetError),
+    #[error("Invalid subnet {}, banning a /0 prefix would ban every address", _0)]
+    SubnetBanTooWide(Subnet),
     #[error("Invalid P2P version: {}", _0)]
     InvalidP2pVersion(String),
     #[error("Invalid tag, it must be greater than 0 and maximum 16 chars")]
@@ -66,6 +86,8 @@ pub enum P2pError {
     NotAllowed,
     #[error("Peer list is full")]
     PeerListFull,
+    #[error("Inbound peers limit reached")]
+    InboundPeersFull,
     #[error("Tracker request has expired, we didn't received a valid response in time")]
     TrackerRequestExpired,
     #[error("Peer not found by id {}", _0)]
@@ -102,20 +124,34 @@ pub enum P2pError {
     AlreadyTrackedTx(Hash, Direction),
     #[error("Malformed chain request, received {} blocks id", _0)]
     MalformedChainRequest(usize),
+    #[error("Chain response size exceeded, requested {} blocks but maximum allowed is {}", _0, _1)]
+    ChainResponseSizeExceeded(usize, usize),
     #[error("Received a unrequested chain response")]
     UnrequestedChainResponse,
     #[error("Invalid chain response size, got {} blocks while maximum set was {}", _0, _1)]
     InvalidChainResponseSize(usize, usize),
+    #[error("Sync gap too large, our chain is at topoheight {} but the peer is pruned until topoheight {}", _0, _1)]
+    SyncGapTooLarge(u64, u64),
     #[error("Received a unrequested bootstrap chain response")]
     UnrequestedBootstrapChainResponse,
     #[error("Invalid common point at topoheight {}", _0)]
     InvalidCommonPoint(u64),
     #[error("Peer disconnected")]
     Disconnected,
-    #[error("Invalid handshake")]
-    InvalidHandshake,
+    #[error("Invalid handshake, genesis block hash mismatch: expected {}, got {}", expected, peer)]
+    GenesisMismatch {
+        expected: Hash,
+        peer: Hash
+    },
+    #[error("Invalid handshake, pruned topoheight {} is above topoheight {}", pruned_topoheight, topoheight)]
+    InconsistentPrunedTopoheight {
+        pruned_topoheight: u64,
+        topoheight: u64
+    },
     #[error("Expected Handshake packet")]
     ExpectedHandshake,
+    #[error("Expected Handshake extensions packet")]
+    ExpectedHandshakeExtensions,
     #[error("Invalid peer address, {}", _0)]
     InvalidPeerAddress(String), // peer address from handshake
     #[error("Invalid network")]
@@ -3,32 +3,41 @@ pub mod error;
 pub mod packet;
 pub mod peer_list;
 pub mod diffie_hellman;
+pub mod proxy;
 
 mod tracker;
 mod encryption;
 mod chain_sync;
+mod bandwidth;
+mod propagation;
 
 use anyhow::Context;
 pub use encryption::EncryptionKey;
+use propagation::{compute_propagation_stats, BlockPropagationEntry};
 
 use log::{debug, error, info, log, trace, warn};
 use metrics::counter;
 use std::{
     borrow::Cow,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     io,
     net::{IpAddr, SocketAddr},
     num::NonZeroUsize,
     sync::{
-        atomic::{AtomicBool, AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
         Arc
     },
-    time::Duration
+    time::{Duration, Instant}
 };
 use tokio_socks::tcp::{Socks4Stream, Socks5Stream};
 use bytes::{Bytes, BytesMut};
-use rand::{seq::IteratorRandom, Rng};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    seq::IteratorRandom,
+    Rng
+};
 use futures::{
+    future::select_ok,
     stream::{self, FuturesOrdered},
     Stream,
     StreamExt,
@@ -41,6 +50,8 @@ use terminos_common::{
         Direction,
         NotifyEvent,
         PeerPeerDisconnectedEvent,
+        ProbeResult,
+        PropagationStats,
         TimedDirection
     },
     block::{
@@ -48,7 +59,7 @@ use terminos_common::{
         BlockHeader,
         TopoHeight,
     },
-    config::{TIPS_LIMIT, VERSION},
+    config::{MAX_BLOCK_SIZE, TIPS_LIMIT, VERSION},
     crypto::{Hash, Hashable},
     difficulty::CumulativeDifficulty,
     immutable::Immutable,
@@ -67,6 +78,8 @@ use terminos_common::{
             broadcast,
             mpsc,
             oneshot,
+            Mutex,
+            Notify,
             RwLock,
         },
         task::JoinHandle,
@@ -84,7 +97,7 @@ use crate::{
         error::BlockchainError,
         hard_fork,
         storage::Storage,
-        config::ProxyKind,
+        config::{PeerSelectionStrategy, ProxyKind},
     },
     p2p::{
         connection::{Connection, State},
@@ -92,11 +105,19 @@ use crate::{
         packet::{
             BlockId,
             Handshake,
+            HandshakeExtension,
+            HandshakeExtensions,
             ObjectRequest,
             ObjectResponse,
             Ping,
             Packet,
-            PacketWrapper
+            PacketWrapper,
+            ObjectBatchResponse,
+            OwnedObjectResponse,
+            MEMPOOL_SIZE_EXTENSION_ID,
+            OBJECT_BATCH_EXTENSION_ID,
+            OBSERVER_EXTENSION_ID,
+            COMPRESSION_EXTENSION_ID
         },
         peer_list::{
             PeerList,
@@ -106,6 +127,7 @@ use crate::{
             Rx
         },
         tracker::{ObjectTracker, SharedObjectTracker},
+        proxy::ProxyPool,
         packet::{
             CommonPoint,
             NotifyInventoryRequest,
@@ -141,8 +163,8 @@ pub struct P2pServer<S: Storage> {
     // Sender channel to pass a newly connected peer to the handler
     peer_sender: mpsc::Sender<(Peer, Rx)>,
     // Synced cache to prevent concurrent tasks adding the block
-    // Timestamp is None if block is not yet executed
-    blocks_propagation_queue: RwLock<LruCache<Arc<Hash>, Option<TimestampMillis>>>,
+    // It also keeps when the block got executed and accepted
+    blocks_propagation_queue: RwLock<LruCache<Arc<Hash>, BlockPropagationEntry>>,
     // Sender for the blocks processing task to have an ordered queue
     blocks_processor: mpsc::Sender<(Arc<Peer>, BlockHeader, Arc<Hash>)>,
     // Sender for the transactions propagated
@@ -202,12 +224,61 @@ pub struct P2pServer<S: Storage> {
     block_propagation_log_level: log::Level,
     // Disable fetching transactions
     disable_fetching_txs_propagated: bool,
+    // Are we propagating the transactions to our peers
+    // This can be toggled at runtime and is independent of the fetching
+    tx_propagation_enabled: AtomicBool,
     // Should we handle packets in task
     // Each packet will be handled in a dedicated task
     handle_peer_packets_in_dedicated_task: bool,
     // Proxy address to use in case we try to connect
     // to an outgoing peer
-    proxy: Option<(ProxyKind, SocketAddr, Option<(String, String)>)>,
+    proxy: Option<ProxyPool>,
+    // Rate limit in bytes per second applied to each peer
+    // when sending packets, 0 means no limit
+    // A peer connection can override it
+    peer_rate_limit: AtomicU64,
+    // Timeout in milliseconds to wait on the handshake packet
+    // This is separated from the outgoing connection timeout
+    // to support high-latency networks
+    handshake_timeout_ms: u64,
+    // Used to stop the listener without stopping the other tasks
+    listener_stop: Notify,
+    // Blocks currently being requested or executed
+    pending_blocks: AtomicUsize,
+    // Transactions currently being requested or added to the mempool
+    pending_txs: AtomicUsize,
+    // Strategy used to select the peer for chain sync
+    peer_selection_strategy: PeerSelectionStrategy,
+    // How many inbound peers we accept
+    // This is capped by the max peers
+    max_inbound_peers: usize,
+    // Maximum jitter in percent applied to the chain sync delay
+    chain_sync_jitter_percent: u8,
+    // Are we connecting to others as an observer
+    // If set, peers will not request any object or chain from us
+    observer: bool,
+    // Do we support the compression of packets
+    // It is used only if the peer supports it too
+    compression: bool,
+    // How many times we retry a failed block request with another peer
+    block_request_retries: u8,
+    // Nodes resolved to both an IPv4 and an IPv6 address
+    // Each address is mapped to its address in the other family
+    // If not empty, we race both addresses when connecting to one of them
+    dual_stack_nodes: HashMap<SocketAddr, SocketAddr>,
+    // Reject the chain requests asking for a bigger response than our limit
+    // instead of clamping it
+    strict_chain_response_size: bool,
+    // IPs that are never banned or penalized due to their fail count
+    // Unlike exclusive nodes, it doesn't restrict who can connect to us
+    fail_count_whitelist: HashSet<IpAddr>,
+    // Maximum packets waiting to be sent to a peer before disconnecting it
+    send_queue_limit: usize,
+    // Maximum unsolicited responses accepted from a peer before temp banning it
+    unsolicited_responses_limit: u32,
+    // Peer ID to use for the next chain sync cycle
+    // instead of the random selection
+    forced_sync_peer: Mutex<Option<u64>>,
 }
 
 impl<S: Storage> P2pServer<S> {
@@ -234,7 +305,22 @@ impl<S: Storage> P2pServer<S> {
         block_propagation_log_level: log::Level,
         disable_fetching_txs_propagated: bool,
         handle_peer_packets_in_dedicated_task: bool,
-        proxy: Option<(ProxyKind, SocketAddr, Option<(String, String)>)>,
+        proxy: Option<ProxyPool>,
+        peer_rate_limit: u64,
+        handshake_timeout_ms: u64,
+        peer_selection_strategy: PeerSelectionStrategy,
+        max_inbound_peers: usize,
+        chain_sync_jitter_percent: u8,
+        observer: bool,
+        peer_id: Option<u64>,
+        compression: bool,
+        block_request_retries: u8,
+        dual_stack_nodes: HashMap<SocketAddr, SocketAddr>,
+        fail_count_whitelist: HashSet<IpAddr>,
+        strict_chain_response_size: bool,
+        send_queue_limit: usize,
+        unsolicited_responses_limit: u32,
+        object_request_timeout_ms: u64,
     ) -> Result<Arc<Self>, P2pError> {
         if tag.as_ref().is_some_and(|tag| tag.len() == 0 || tag.len() > 16) {
             return Err(P2pError::InvalidTag);
@@ -256,10 +342,32 @@ impl<S: Storage> P2pServer<S> {
             return Err(P2pError::InvalidFailCount);
         }
 
-        // set channel to communicate with listener thread
-        let mut rng = rand::thread_rng();
-        // generate a random peer id for network
-        let peer_id: u64 = rng.gen();
+        if handshake_timeout_ms == 0 {
+            return Err(P2pError::InvalidHandshakeTimeout);
+        }
+
+        if send_queue_limit == 0 {
+            return Err(P2pError::InvalidSendQueueLimit);
+        }
+
+        if unsolicited_responses_limit == 0 {
+            return Err(P2pError::InvalidUnsolicitedResponsesLimit);
+        }
+
+        if object_request_timeout_ms == 0 {
+            return Err(P2pError::InvalidObjectRequestTimeout);
+        }
+
+        if chain_sync_jitter_percent > CHAIN_SYNC_MAX_JITTER_PERCENT {
+            return Err(P2pError::InvalidChainSyncJitter);
+        }
+
+        if peer_id == Some(0) {
+            return Err(P2pError::InvalidPeerId);
+        }
+
+        // use the provided peer id or generate a random one for network
+        let peer_id: u64 = peer_id.unwrap_or_else(|| rand::thread_rng().gen());
         // parse the bind address
         let bind_address: SocketAddr = bind_address.parse()?;
 
@@ -268,7 +376,7 @@ impl<S: Storage> P2pServer<S> {
 
         // Channel used to broadcast the stop message
         let (exit_sender, exit_receiver) = broadcast::channel(1);
-        let object_tracker = ObjectTracker::new(exit_receiver);
+        let object_tracker = ObjectTracker::new(exit_receiver, Duration::from_millis(object_request_timeout_ms));
 
         let (ping_sender, ping_receiver) = mpsc::channel(1);
 
@@ -277,7 +385,8 @@ impl<S: Storage> P2pServer<S> {
             max_peers,
             stream_concurrency,
             format!("{}peerlist-{}", dir_path.unwrap_or_default(), blockchain.get_network().to_string().to_lowercase()),
-            Some(sender)
+            Some(sender),
+            exclusive_nodes.iter().map(|addr| addr.ip()).collect()
         )?;
 
 
@@ -315,8 +424,26 @@ impl<S: Storage> P2pServer<S> {
             disable_reexecute_blocks_on_sync,
             block_propagation_log_level,
             disable_fetching_txs_propagated,
+            tx_propagation_enabled: AtomicBool::new(true),
             handle_peer_packets_in_dedicated_task,
-            proxy
+            proxy,
+            peer_rate_limit: AtomicU64::new(peer_rate_limit),
+            handshake_timeout_ms,
+            listener_stop: Notify::new(),
+            pending_blocks: AtomicUsize::new(0),
+            pending_txs: AtomicUsize::new(0),
+            peer_selection_strategy,
+            max_inbound_peers: max_inbound_peers.min(max_peers),
+            chain_sync_jitter_percent,
+            observer,
+            compression,
+            block_request_retries,
+            dual_stack_nodes,
+            fail_count_whitelist,
+            strict_chain_response_size,
+            send_queue_limit,
+            unsolicited_responses_limit,
+            forced_sync_peer: Mutex::new(None),
         };
 
         let arc = Arc::new(server);
@@ -353,6 +480,43 @@ impl<S: Storage> P2pServer<S> {
         info!("P2p Server is now stopped!");
     }
 
+    // Drain the p2p module before stopping it
+    // We stop accepting new connections and chain sync requests
+    // but we let the in-flight blocks and transactions being processed
+    // Returns how many items were still pending when the timeout expired
+    pub async fn drain(&self, timeout: Duration) -> usize {
+        info!("Draining P2p Server...");
+        self.is_running.store(false, Ordering::SeqCst);
+        self.listener_stop.notify_one();
+
+        let deadline = Instant::now() + timeout;
+        let pending = loop {
+            let pending = self.get_pending_processing_count();
+            if pending == 0 || Instant::now() >= deadline {
+                break pending;
+            }
+
+            debug!("Waiting on {} pending blocks/txs to be processed", pending);
+            sleep(Duration::from_millis(P2P_DRAIN_CHECK_INTERVAL)).await;
+        };
+
+        if pending > 0 {
+            warn!("{} blocks/txs were still pending after draining", pending);
+        }
+
+        self.stop().await;
+
+        pending
+    }
+
+    // Count of the blocks and transactions being processed or waiting in queue
+    pub fn get_pending_processing_count(&self) -> usize {
+        let queued_blocks = self.blocks_processor.max_capacity() - self.blocks_processor.capacity();
+        let queued_txs = self.txs_processor.max_capacity() - self.txs_processor.capacity();
+
+        queued_blocks + queued_txs + self.pending_blocks.load(Ordering::SeqCst) + self.pending_txs.load(Ordering::SeqCst)
+    }
+
     // Verify if we are still running
     pub fn is_running(&self) -> bool {
         self.is_running.load(Ordering::SeqCst)
@@ -371,8 +535,12 @@ impl<S: Storage> P2pServer<S> {
     ) -> Result<(), P2pError> {
         let listener = TcpListener::bind(self.get_bind_address()).await?;
         info!("P2p Server will listen on: {}", self.get_bind_address());
-        if let Some((proxy, addr, auth)) = self.proxy.as_ref() {
-            info!("Proxy to use: {} ({} with auth = {})", addr, proxy, auth.is_some());
+        if let Some(pool) = self.proxy.as_ref() {
+            let addresses = pool.addresses().iter()
+                .map(|addr| addr.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            info!("Proxies to use: {} ({} with auth = {})", addresses, pool.kind(), pool.auth().is_some());
         }
 
         // start a new task for chain sync
@@ -444,6 +612,7 @@ impl<S: Storage> P2pServer<S> {
         let reject = !self.is_compatible_with_exclusive_nodes(&addr)
             // check that this incoming peer isn't blacklisted
             || !self.accept_new_connections().await
+            || !self.accept_new_inbound_connections().await
             || !self.peer_list.is_allowed(&addr.ip()).await?
             || self.is_connected_to_addr(&addr).await;
 
@@ -466,7 +635,9 @@ impl<S: Storage> P2pServer<S> {
                 },
                 Err(e) => {
                     debug!("Error while handling incoming connection {}: {}", addr, e);
-                    if let Err(e) = zelf.peer_list.increase_fail_count_for_peerlist_entry(&addr.ip(), true).await {
+                    if zelf.is_fail_count_whitelisted(&addr.ip()) {
+                        debug!("{} is fail count whitelisted, not increasing its fail count", addr);
+                    } else if let Err(e) = zelf.peer_list.increase_fail_count_for_peerlist_entry(&addr.ip(), true, &e).await {
                         error!("Error while increasing fail count for incoming peer {} while verifying it: {}", addr, e);
                     }
                 }
@@ -489,6 +660,10 @@ impl<S: Storage> P2pServer<S> {
                     debug!("Received exit message, exiting incoming connections task");
                     break;
                 }
+                _ = self.listener_stop.notified() => {
+                    debug!("Received listener stop signal, exiting incoming connections task");
+                    break;
+                }
                 res = listener.accept() => {
                     trace!("New listener result received (is err: {})", res.is_err());
                     counter!("terminos_p2p_incoming_connections_total").increment(1u64);
@@ -513,9 +688,15 @@ impl<S: Storage> P2pServer<S> {
     // based on data size, network ID, peers address validity
     // block height and block top hash of this peer (to know if we are on the same chain)
     async fn verify_handshake(&self, connection: &mut Connection, handshake: &mut Handshake<'_>) -> Result<(), P2pError> {
-        if handshake.get_network() != self.blockchain.get_network() {
-            trace!("{} has an invalid network: {}", connection, handshake.get_network());
-            return Err(P2pError::InvalidNetwork)
+        // check if the network and the version of this peer are allowed
+        if let Err(reason) = hard_fork::check_peer_network(self.blockchain.get_network(), handshake.get_network()) {
+            trace!("{} is not compatible: {}", connection, reason);
+            return Err(P2pError::InvalidNetwork);
+        }
+
+        if let Err(reason) = hard_fork::check_peer_compatibility(self.blockchain.get_network(), self.blockchain.get_height(), handshake.get_version()) {
+            trace!("{} is not compatible: {}", connection, reason);
+            return Err(P2pError::InvalidP2pVersion(handshake.get_version().clone()));
         }
 
         if *handshake.get_network_id() != NETWORK_ID {
@@ -530,10 +711,10 @@ impl<S: Storage> P2pServer<S> {
             return Err(P2pError::PeerIdAlreadyUsed(handshake.get_peer_id()));
         }
 
-        let genesis_hash = match get_genesis_block_hash(self.blockchain.get_network()) {
+        let genesis_hash = match self.blockchain.get_genesis_hash() {
             Some(hash) => Cow::Borrowed(hash),
             None => {
-                debug!("no hardcoded genesis block hash found, using the one from the storage");
+                debug!("no genesis block hash configured or hardcoded, using the one from the storage");
                 let storage = self.blockchain.get_storage().read().await;
                 debug!("storage read acquired for genesis block hash");
                 let hash = storage.get_hash_at_topo_height(0).await?;
@@ -542,23 +723,24 @@ impl<S: Storage> P2pServer<S> {
         };
 
         if *handshake.get_block_genesis_hash() != *genesis_hash {
-            debug!("Invalid genesis block hash {}", handshake.get_block_genesis_hash());
-            return Err(P2pError::InvalidHandshake)
+            debug!("Invalid genesis block hash {} from {}", handshake.get_block_genesis_hash(), connection);
+            return Err(P2pError::GenesisMismatch {
+                expected: genesis_hash.into_owned(),
+                peer: handshake.get_block_genesis_hash().clone()
+            })
         }
 
         if let Some(pruned_topoheight) = handshake.get_pruned_topoheight() {
             let topoheight = handshake.get_topoheight();
             if *pruned_topoheight > topoheight {
                 debug!("Peer {} has a pruned topoheight {} higher than its topoheight {}", connection, pruned_topoheight, topoheight);
-                return Err(P2pError::InvalidHandshake)
+                return Err(P2pError::InconsistentPrunedTopoheight {
+                    pruned_topoheight: *pruned_topoheight,
+                    topoheight
+                })
             }
         }
 
-        // check if the version of this peer is allowed
-        if !hard_fork::is_version_allowed_at_height(self.blockchain.get_network(), self.blockchain.get_height(), handshake.get_version()).map_err(|e| P2pError::InvalidP2pVersion(e.to_string()))? {
-            return Err(P2pError::InvalidP2pVersion(handshake.get_version().clone()));
-        }
-
         Ok(())
     }
 
@@ -572,10 +754,10 @@ impl<S: Storage> P2pServer<S> {
         let topoheight = self.blockchain.get_topo_height();
         let pruned_topoheight = storage.get_pruned_topoheight().await?;
         let cumulative_difficulty = storage.get_cumulative_difficulty_for_block_hash(&top_hash).await?;
-        let genesis_block = match get_genesis_block_hash(self.blockchain.get_network()) {
+        let genesis_block = match self.blockchain.get_genesis_hash() {
             Some(hash) => Cow::Borrowed(hash),
             None => {
-                trace!("no hardcoded genesis block hash found, using the one from the storage");
+                trace!("no genesis block hash configured or hardcoded, using the one from the storage");
                 Cow::Owned(storage.get_hash_at_topo_height(0).await?)
             }
         };
@@ -583,6 +765,24 @@ impl<S: Storage> P2pServer<S> {
         Ok(Packet::Handshake(Cow::Owned(handshake)).to_bytes())
     }
 
+    // Build the extensions advertised in our handshake
+    fn build_handshake_extensions(&self) -> Vec<HandshakeExtension> {
+        let mut extensions = vec![
+            HandshakeExtension::new(MEMPOOL_SIZE_EXTENSION_ID, Vec::new()),
+            HandshakeExtension::new(OBJECT_BATCH_EXTENSION_ID, Vec::new())
+        ];
+
+        if self.observer {
+            extensions.push(HandshakeExtension::new(OBSERVER_EXTENSION_ID, Vec::new()));
+        }
+
+        if self.compression {
+            extensions.push(HandshakeExtension::new(COMPRESSION_EXTENSION_ID, Vec::new()));
+        }
+
+        extensions
+    }
+
     // Create a valid peer using the connection, if an error happen, it will close the stream and return the error
     async fn create_verified_peer(&self, buf: &mut [u8], mut connection: Connection, priority: bool) -> Result<(Peer, Rx), P2pError> {
         let handshake = match self.verify_connection(buf, &mut connection).await {
@@ -602,7 +802,7 @@ impl<S: Storage> P2pServer<S> {
             mempool.size() > 0
         };
 
-        let (peer, rx) = handshake.create_peer(connection, priority, self.peer_list.clone(), !has_any_tx);
+        let (peer, rx) = handshake.create_peer(connection, priority, self.peer_list.clone(), self.send_queue_limit, !has_any_tx);
         Ok((peer, rx))
     }
 
@@ -624,7 +824,7 @@ impl<S: Storage> P2pServer<S> {
         }
 
         // wait on the handshake packet
-        let mut handshake: Handshake<'_> = match timeout(Duration::from_millis(PEER_TIMEOUT_INIT_CONNECTION), connection.read_packet(buf, buf.len() as u32)).await?? {
+        let mut handshake: Handshake<'_> = match timeout(Duration::from_millis(self.handshake_timeout_ms), connection.read_packet(buf, buf.len() as u32)).await?? {
             // only allow handshake packet
             Packet::Handshake(h) => h.into_owned(),
             _ => return Err(P2pError::ExpectedHandshake)
@@ -641,6 +841,32 @@ impl<S: Storage> P2pServer<S> {
             self.send_handshake(&connection).await?;
         }
 
+        // Exchange the extensions only if the peer version supports them
+        // Both sides send theirs before reading the other one
+        if handshake.supports_extensions() {
+            trace!("Exchanging handshake extensions with {}", connection);
+            let mut packet = Packet::HandshakeExtensions(Cow::Owned(HandshakeExtensions::new(self.build_handshake_extensions()))).to_bytes();
+            connection.send_bytes(&mut packet).await?;
+
+            let extensions = match timeout(Duration::from_millis(self.handshake_timeout_ms), connection.read_packet(buf, buf.len() as u32)).await?? {
+                Packet::HandshakeExtensions(extensions) => extensions.into_owned().into_inner(),
+                _ => return Err(P2pError::ExpectedHandshakeExtensions)
+            };
+
+            // Unknown extensions are ignored for forward compatibility
+            for extension in extensions.iter() {
+                if !matches!(extension.get_id(), MEMPOOL_SIZE_EXTENSION_ID | OBJECT_BATCH_EXTENSION_ID | OBSERVER_EXTENSION_ID | COMPRESSION_EXTENSION_ID) {
+                    trace!("Ignoring unknown handshake extension {} from {}", extension.get_id(), connection);
+                }
+            }
+
+            handshake.set_extensions(extensions);
+        }
+
+        // Both handshakes have been exchanged
+        // we can now enable the compression if both sides support it
+        connection.set_compression(self.compression && handshake.supports_compression());
+
         // if we reach here, handshake is all good, we can start listening this new peer
         connection.set_state(State::Success);
 
@@ -654,7 +880,13 @@ impl<S: Storage> P2pServer<S> {
             return Err(P2pError::PeerIdAlreadyUsed(peer_id));
         }
 
+        // Inbound slots may have been filled while verifying the connection
+        if !peer.is_out() && !self.accept_new_inbound_connections().await {
+            return Err(P2pError::InboundPeersFull);
+        }
+
         self.peer_list.add_peer(peer, self.get_max_peers()).await?;
+        self.check_network_time_offset().await;
 
         if peer.sharable() {
             trace!("Locking RPC Server to notify PeerConnected event");
@@ -676,21 +908,97 @@ impl<S: Storage> P2pServer<S> {
         self.exclusive_nodes.is_empty() || self.exclusive_nodes.contains(addr)
     }
 
+    // Check if the IP is exempt from the fail count bans
+    pub fn is_fail_count_whitelisted(&self, ip: &IpAddr) -> bool {
+        self.fail_count_whitelist.contains(ip)
+    }
+
+    // Get all the IPs exempt from the fail count bans
+    pub fn get_fail_count_whitelist(&self) -> &HashSet<IpAddr> {
+        &self.fail_count_whitelist
+    }
+
     // Connect to a specific peer address
     // Buffer is passed in parameter to prevent the re-allocation each time
     // No check is done, this is done at the moment of the connection
     pub async fn try_to_connect_to_peer(&self, addr: SocketAddr, priority: bool) -> Result<(), P2pError> {
         debug!("try to connect to peer addr {}, priority: {}", addr, priority);
         counter!("terminos_p2p_outgoing_connections_total").increment(1u64);
+
+        // If the node is dual stack, race both address families and keep the first one ready
+        // A proxy is only used over one family, so we don't race through it
+        let dual_stack_addr = match self.dual_stack_nodes.get(&addr) {
+            Some(other) if self.proxy.is_none() && !self.is_connected_to_addr(other).await => Some(*other),
+            _ => None
+        };
+
+        let peer = match dual_stack_addr {
+            Some(other) => {
+                debug!("racing {} and {} for dual stack connection", addr, other);
+                // The loser is dropped, which closes its connection
+                let (peer, _) = select_ok([
+                    Box::pin(self.connect_and_verify_peer(addr, priority)),
+                    Box::pin(self.connect_and_verify_peer(other, priority))
+                ]).await?;
+                peer
+            },
+            None => self.connect_and_verify_peer(addr, priority).await?
+        };
+
+        debug!("sending newly connected peer to the task");
+        // Peer is valid, send it to connect
+        self.peer_sender.send(peer).await
+            .context("Error while sending peer to task")?;
+
+        Ok(())
+    }
+
+    // Connect to the address, exchange the handshakes and disconnect
+    // The node is never added to our peer list and no fail count is applied
+    // Used to check that a node is up and to retrieve its chain state
+    pub async fn probe(&self, addr: SocketAddr) -> Result<ProbeResult, P2pError> {
+        debug!("probing {}", addr);
+        let start = Instant::now();
+        let mut connection = self.connect_to_peer(addr).await?;
+
+        let mut buffer = [0; 512];
+        let res = self.verify_connection(&mut buffer, &mut connection).await;
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+        if let Err(e) = connection.close().await {
+            debug!("Error while closing probe connection with {}: {}", addr, e);
+        }
+
+        let handshake = res?;
+        Ok(ProbeResult {
+            addr,
+            id: handshake.get_peer_id(),
+            tag: handshake.get_node_tag().clone(),
+            version: handshake.get_version().clone(),
+            top_block_hash: handshake.get_block_top_hash().clone(),
+            topoheight: handshake.get_topoheight(),
+            height: handshake.get_block_height(),
+            pruned_topoheight: *handshake.get_pruned_topoheight(),
+            cumulative_difficulty: handshake.get_cumulative_difficulty().clone(),
+            elapsed_ms
+        })
+    }
+
+    // Connect to the address and verify the handshake
+    // On failure, the fail count or the reconnect backoff of the address is increased
+    async fn connect_and_verify_peer(&self, addr: SocketAddr, priority: bool) -> Result<(Peer, Rx), P2pError> {
         let connection = match self.connect_to_peer(addr).await {
             Ok(connection) => connection,
             Err(e) => {
                 debug!("Error while connecting to address {}: {}", addr, e);
 
                 if !priority {
-                    if let Err(e) = self.peer_list.increase_fail_count_for_peerlist_entry(&addr.ip(), false).await {
+                    if self.is_fail_count_whitelisted(&addr.ip()) {
+                        debug!("{} is fail count whitelisted, not increasing its fail count", addr);
+                    } else if let Err(e) = self.peer_list.increase_fail_count_for_peerlist_entry(&addr.ip(), false, &e).await {
                         error!("Error while increasing fail count for peer {} while connecting to it: {}", addr, e);
                     }
+                } else {
+                    self.peer_list.increase_reconnect_backoff(&addr).await;
                 }
                 return Err(e);
             }
@@ -702,21 +1010,22 @@ impl<S: Storage> P2pServer<S> {
             Err(e) => {
                 debug!("Error while verifying connection to address {}: {}", addr, e);
                 if !priority {
-                    if let Err(e) = self.peer_list.increase_fail_count_for_peerlist_entry(&addr.ip(), false).await {
+                    if self.is_fail_count_whitelisted(&addr.ip()) {
+                        debug!("{} is fail count whitelisted, not increasing its fail count", addr);
+                    } else if let Err(e) = self.peer_list.increase_fail_count_for_peerlist_entry(&addr.ip(), false, &e).await {
                         error!("Error while increasing fail count for peer {} while verifying it: {}", addr, e);
                     }
+                } else {
+                    self.peer_list.increase_reconnect_backoff(&addr).await;
                 }
 
                 return Err(e);
             }
         };
 
-        debug!("sending newly connected peer to the task");
-        // Peer is valid, send it to connect
-        self.peer_sender.send(peer).await
-            .context("Error while sending peer to task")?;
+        self.peer_list.reset_reconnect_backoff(&addr).await;
 
-        Ok(())
+        Ok(peer)
     }
 
     // Connect to a new peer using its socket address
@@ -743,9 +1052,11 @@ impl<S: Storage> P2pServer<S> {
         }
 
         let duration = Duration::from_millis(PEER_TIMEOUT_INIT_OUTGOING_CONNECTION);
-        let stream = if let Some((kind, proxy, auth)) = self.proxy.as_ref() {
-            match kind {
-                ProxyKind::Socks5 => if let Some((username, password)) = auth {
+        let stream = if let Some(pool) = self.proxy.as_ref() {
+            let proxy = pool.select();
+            debug!("Connecting to {} through proxy {}", addr, proxy);
+            match pool.kind() {
+                ProxyKind::Socks5 => if let Some((username, password)) = pool.auth() {
                         timeout(duration, Socks5Stream::connect_with_password(proxy, &addr, &username, &password)).await
                     } else {
                         timeout(duration, Socks5Stream::connect(proxy, &addr)).await
@@ -785,8 +1096,12 @@ impl<S: Storage> P2pServer<S> {
         };
         let highest_topo_height = self.blockchain.get_topo_height();
         let highest_height = self.blockchain.get_height();
+        let mempool_size = {
+            let mempool = self.blockchain.get_mempool().read().await;
+            mempool.size() as u64
+        };
         let new_peers = IndexSet::new();
-        Ok(Ping::new(Cow::Owned(block_top_hash), highest_topo_height, highest_height, pruned_topoheight, cumulative_difficulty, new_peers))
+        Ok(Ping::new(Cow::Owned(block_top_hash), highest_topo_height, highest_height, pruned_topoheight, cumulative_difficulty, new_peers, Some(mempool_size)))
     }
 
     // Build a generic ping packet
@@ -831,16 +1146,19 @@ impl<S: Storage> P2pServer<S> {
         let available_peers = self.peer_list.get_cloned_peers().await;
         debug!("{} peers available for selection", available_peers.len());
 
+        let our_cumulative_difficulty = &our_cumulative_difficulty;
         let mut peers = stream::iter(available_peers)
             .map(|p| async move {
                 // Avoid selecting peers that have a weaker cumulative difficulty than us
-                {
+                let difficulty_delta_bits = {
                     let cumulative_difficulty = p.get_cumulative_difficulty().lock().await;
-                    if *cumulative_difficulty <= our_cumulative_difficulty {
+                    if *cumulative_difficulty <= *our_cumulative_difficulty {
                         trace!("{} has a lower cumulative difficulty than us, skipping...", p);
                         return None;
                     }
-                }
+
+                    (*cumulative_difficulty.as_ref() - *our_cumulative_difficulty.as_ref()).bits() as u64
+                };
 
                 let peer_topoheight = p.get_topoheight();
                 if fast_sync {
@@ -878,7 +1196,8 @@ impl<S: Storage> P2pServer<S> {
                 // check if this peer may have a block we don't have
                 if p.get_height() > our_height || peer_topoheight > our_topoheight {
                     debug!("{} is a candidate for chain sync, our topoheight: {}, our height: {}", p, our_topoheight, our_height);
-                    Some(p)
+                    let score = compute_peer_selection_score(difficulty_delta_bits, p.get_connection().recv_rate(), p.get_latency(), p.get_fail_count());
+                    Some((p, score))
                 } else {
                     trace!("{} is not ahead of us, skipping it", p);
                     None
@@ -886,7 +1205,7 @@ impl<S: Storage> P2pServer<S> {
             })
             .buffer_unordered(self.stream_concurrency)
             .filter_map(|x| async move { x })
-            .collect::<IndexSet<_>>()
+            .collect::<Vec<_>>()
             .await;
 
         // Try to not reuse the same peer between each sync if we had an error
@@ -896,8 +1215,8 @@ impl<S: Storage> P2pServer<S> {
             if peers.len() > 1 && (err && !priority) {
                 debug!("removing previous peer {} from random selection, err: {}, priority: {}", previous_peer, err, priority);
                 // We don't need to preserve the order
-                if let Some(position) = peers.iter().position(|p| p.get_id() == previous_peer) {
-                    peers.swap_remove_index(position);
+                if let Some(position) = peers.iter().position(|(p, _)| p.get_id() == previous_peer) {
+                    peers.swap_remove(position);
                 }
             }
         }
@@ -908,9 +1227,17 @@ impl<S: Storage> P2pServer<S> {
             return Ok(None)
         }
 
-        let selected = rand::thread_rng().gen_range(0..count);
+        let selected = match self.peer_selection_strategy {
+            PeerSelectionStrategy::Uniform => rand::thread_rng().gen_range(0..count),
+            PeerSelectionStrategy::WeightedByRate => {
+                let weights = WeightedIndex::new(peers.iter().map(|(_, score)| *score))
+                    .context("Error while building weighted peer selection")?;
+                weights.sample(&mut rand::thread_rng())
+            }
+        };
+
         // clone the Arc to prevent the lock until the end of the sync request
-        Ok(peers.swap_remove_index(selected))
+        Ok(Some(peers.swap_remove(selected).0))
     }
 
     // Check if user has allowed fast sync mode
@@ -955,17 +1282,21 @@ impl<S: Storage> P2pServer<S> {
         // used to detect how much time we have to wait before next request
         let mut last_chain_sync = get_current_time_in_millis();
         let interval = Duration::from_secs(CHAIN_SYNC_DELAY);
+        let max_jitter = CHAIN_SYNC_DELAY * MILLIS_PER_SECOND * self.chain_sync_jitter_percent as u64 / 100;
         // Try to not reuse the same peer between each sync
         // Don't use it at all if its errored
         // the Peer ID, peer priority flag, error state
         let mut previous_peer: Option<(u64, bool, bool)> = None;
         let mut warned = false;
+        // Did we already recommend the fast sync for the current gap
+        let mut gap_warned = false;
         loop {
             // Detect exact time needed before next chain sync
             let current = get_current_time_in_millis();
             let diff = current - last_chain_sync;
-            if  diff < CHAIN_SYNC_DELAY * MILLIS_PER_SECOND {
-                let wait = CHAIN_SYNC_DELAY * MILLIS_PER_SECOND - diff;
+            let delay = compute_chain_sync_delay(max_jitter, &mut rand::thread_rng());
+            if  diff < delay {
+                let wait = delay - diff;
                 debug!("Waiting {} ms for chain sync delay...", wait);
                 sleep(Duration::from_millis(wait)).await;
             }
@@ -976,10 +1307,14 @@ impl<S: Storage> P2pServer<S> {
                 break;
             }
 
+            // The operator may have requested to sync from a specific peer for this cycle
+            let forced_peer = self.take_forced_sync_peer().await;
+            let forced = forced_peer.is_some();
+
             // first we have to check if we allow fast sync mode
             // and then we check if we have a potential peer above us to fast sync
             // otherwise we sync normally 
-            let fast_sync = if self.allow_fast_sync() {
+            let fast_sync = if !forced && self.allow_fast_sync() {
                 trace!("locking peer list for fast sync check");
                 let peerlist = self.peer_list.get_peers().read().await;
                 trace!("peer list locked for fast sync check");
@@ -993,11 +1328,15 @@ impl<S: Storage> P2pServer<S> {
                 false
             };
 
-            let peer_selected = match self.select_random_best_peer(fast_sync, previous_peer).await {
-                Ok(peer) => peer,
-                Err(e) => {
-                    error!("Error while selecting random best peer for chain sync: {}", e);
-                    None
+            let peer_selected = if forced {
+                forced_peer
+            } else {
+                match self.select_random_best_peer(fast_sync, previous_peer).await {
+                    Ok(peer) => peer,
+                    Err(e) => {
+                        error!("Error while selecting random best peer for chain sync: {}", e);
+                        None
+                    }
                 }
             };
 
@@ -1020,12 +1359,28 @@ impl<S: Storage> P2pServer<S> {
                         false
                     }
                 } else {
-                    let previous_err = previous_peer.map(|(_, _, err)| err).unwrap_or(false);
+                    // A forced sync never skips the stable height check
+                    // so we don't rewind below it automatically
+                    let previous_err = !forced && previous_peer.map(|(_, _, err)| err).unwrap_or(false);
                     if let Err(e) = self.request_sync_chain_for(&peer, &mut last_chain_sync, previous_err).await {
                         peer.clear_objects_requested().await;
-                        warn!("Error occured on chain sync with {}: {}", peer, e);
+                        if let BlockchainError::ErrorOnP2p(P2pError::SyncGapTooLarge(_, _)) = e {
+                            warn!("Cannot sync the chain with {}: {}. Please enable the fast sync mode (--allow-fast-sync) to bootstrap from a pruned peer", peer, e);
+                        } else {
+                            warn!("Error occured on chain sync with {}: {}", peer, e);
+                        }
                         true
                     } else {
+                        // Recommend the fast sync once if we are far behind the peer
+                        let gap = peer.get_topoheight().saturating_sub(self.blockchain.get_topo_height());
+                        if gap > (self.max_chain_response_size as u64).saturating_mul(CHAIN_SYNC_GAP_FAST_SYNC_FACTOR) {
+                            if !self.allow_fast_sync() && !gap_warned {
+                                gap_warned = true;
+                                warn!("We are {} blocks behind {}, the fast sync mode (--allow-fast-sync) is recommended to sync faster", gap, peer);
+                            }
+                        } else {
+                            gap_warned = false;
+                        }
                         false
                     }
                 };
@@ -1125,7 +1480,7 @@ impl<S: Storage> P2pServer<S> {
                     .for_each_concurrent(self.stream_concurrency, |peer| {
                         // Clone the ping packet for each peer
                         // We need to update the shared peers in it
-                        let mut ping = ping.clone();
+                        let mut ping = ping.clone().for_peer(peer);
                         let all_peers = &all_peers;
                         async move {
                             if peer.get_connection().is_closed() {
@@ -1143,7 +1498,7 @@ impl<S: Storage> P2pServer<S> {
                             for p in all_peers.iter() {
                                 // don't send him itself
                                 // and don't share a peer that don't want to be shared
-                                if p.get_id() == peer.get_id() || !p.sharable() {
+                                if p.get_id() == peer.get_id() || !p.sharable() || p.is_observer() {
                                     continue;
                                 }
         
@@ -1197,8 +1552,7 @@ impl<S: Storage> P2pServer<S> {
                 last_peerlist_update = get_current_time_in_seconds();
             } else {
                 trace!("Sending generic ping packet...");
-                let packet = Packet::Ping(Cow::Owned(ping));
-                let bytes = Bytes::from(packet.to_bytes());
+                let bytes = PingPacketBytes::new(&ping, |ping| Packet::Ping(Cow::Borrowed(ping)).to_bytes());
 
                 // broadcast directly the ping packet asap to all peers
                 stream::iter(all_peers)
@@ -1208,7 +1562,7 @@ impl<S: Storage> P2pServer<S> {
                         async move {
                             if current_time - peer.get_last_ping_sent() > P2P_PING_DELAY && !peer.get_connection().is_closed() {
                                 trace!("broadcast generic ping packet to {}", peer);
-                                if let Err(e) = peer.send_bytes(bytes.clone()).await {
+                                if let Err(e) = peer.send_bytes(bytes.for_peer(&peer).clone()).await {
                                     error!("Error while trying to send ping packet to {}: {}", peer, e);
                                 } else {
                                     peer.set_last_ping_sent(current_time);
@@ -1225,10 +1579,11 @@ impl<S: Storage> P2pServer<S> {
     }
 
     // Select a random socket address for our next outgoing peer to connect to
+    // Addresses still in their reconnect backoff are skipped
     async fn select_random_socket_address(&self, addresses: impl Iterator<Item = SocketAddr>) -> Option<SocketAddr> {
         let mut availables = Vec::new();
         for node in addresses {
-            if !self.peer_list.is_connected_to_addr(&node).await {
+            if !self.peer_list.is_connected_to_addr(&node).await && self.peer_list.can_reconnect_to(&node).await {
                 availables.push(node);
             }
         }
@@ -1286,7 +1641,12 @@ impl<S: Storage> P2pServer<S> {
 
             let duration = if should_wait {
                 debug!("Not accepting new connections or no potential peer found, waiting delay before next check");
-                Duration::from_secs(P2P_EXTEND_PEERLIST_DELAY)
+                let delay = Duration::from_secs(P2P_EXTEND_PEERLIST_DELAY);
+                // Don't wait longer than the next reconnect backoff
+                match self.peer_list.get_next_reconnect_in().await {
+                    Some(millis) => delay.min(Duration::from_millis(millis)),
+                    None => delay
+                }
             } else {
                 debug!("Waiting only for the init connection");
                 Duration::from_millis(PEER_TIMEOUT_INIT_CONNECTION)
@@ -1329,12 +1689,150 @@ impl<S: Storage> P2pServer<S> {
         debug!("Event loop task is stopped!");
     }
 
+    // Search the requested object in our chain or mempool
+    async fn find_object(&self, peer: &Arc<Peer>, request: ObjectRequest) -> ObjectResponse<'static> {
+        match &request {
+            ObjectRequest::Block(hash) => {
+                debug!("{} asked full block {}", peer, hash);
+                let block = {
+                    let storage = self.blockchain.get_storage().read().await;
+                    debug!("storage read acquired for full block request");
+                    storage.get_block_by_hash(hash).await
+                };
+
+                match block {
+                    Ok(block) => {
+                        debug!("block {} found, sending it", hash);
+                        ObjectResponse::Block(Cow::Owned(block))
+                    },
+                    Err(e) => {
+                        debug!("{} asked block '{}' but not present in our chain: {}", peer, hash, e);
+                        ObjectResponse::NotFound(request)
+                    }
+                }
+            },
+            ObjectRequest::BlockHeader(hash) => {
+                debug!("{} asked block header {}", peer, hash);
+                let block = {
+                    let storage = self.blockchain.get_storage().read().await;
+                    debug!("storage read acquired for block header request");
+                    storage.get_block_header_by_hash(hash).await
+                };
+
+                match block {
+                    Ok(block) => {
+                        debug!("block header {} found, sending it", hash);
+                        ObjectResponse::BlockHeader(Cow::Owned(block.into_owned()))
+                    },
+                    Err(e) => {
+                        debug!("{} asked block header '{}' but not present in our chain: {}", peer, hash, e);
+                        ObjectResponse::NotFound(request)
+                    }
+                }
+            },
+            ObjectRequest::Transaction(hash) => {
+                debug!("{} asked tx {}", peer, hash);
+                match self.blockchain.get_tx(hash).await {
+                    Ok(tx) => {
+                        debug!("tx {} found, sending it", hash);
+                        ObjectResponse::Transaction(Cow::Owned(tx.into_owned()))
+                    },
+                    Err(e) => {
+                        debug!("{} asked tx '{}' but not present in our chain: {}", peer, hash, e);
+                        ObjectResponse::NotFound(request)
+                    }
+                }
+            }
+        }
+    }
+
+    // Track a response sent by the peer that we never requested
+    // Once the limit is reached, the peer is temp banned to prevent it from wasting our resources
+    async fn handle_unsolicited_response(&self, peer: &Arc<Peer>) {
+        let count = peer.increment_unsolicited_responses();
+        counter!("terminos_p2p_unsolicited_responses").increment(1);
+        debug!("{} sent us an unsolicited response ({}/{})", peer, count, self.unsolicited_responses_limit);
+
+        if count >= self.unsolicited_responses_limit
+            && !peer.is_priority()
+            && !self.is_fail_count_whitelisted(&peer.get_connection().get_address().ip()) {
+            warn!("Too many unsolicited responses ({}) from {}! Closing connection...", count, peer);
+            if let Err(e) = peer.close_and_temp_ban(self.temp_ban_time).await {
+                error!("Error while trying to close connection with {} due to unsolicited responses: {}", peer, e);
+            }
+        }
+    }
+
+    // Dispatch an object response to the one waiting for it
+    async fn handle_object_response(&self, peer: &Arc<Peer>, response: ObjectResponse<'_>) -> Result<(), P2pError> {
+        let response = response.to_owned();
+        trace!("Object response received is {}", response.get_hash());
+
+        // check if we requested it from this peer directly
+        // or that we requested it through the object tracker
+        let request = response.get_request();
+        if let Some(sender) = peer.remove_object_request(&request).await {
+            // handle the response
+            sender.send(response)
+                .with_context(|| format!("Cannot notify listener for {}", request))?;
+        } else if !self.object_tracker.handle_object_response(response).await? {
+            self.handle_unsolicited_response(peer).await;
+            return Err(P2pError::ObjectNotRequested(request))
+        }
+
+        Ok(())
+    }
+
+    // Fetch in batches the TXs missing from our chain directly from the peer
+    async fn request_missing_txs(&self, peer: &Arc<Peer>, block_hash: &Hash, hashes: Vec<Hash>) -> Result<HashMap<Hash, Arc<Transaction>>, BlockchainError> {
+        let mut txs = HashMap::with_capacity(hashes.len());
+        for chunk in hashes.chunks(PEER_OBJECTS_BATCH_MAX_LEN) {
+            debug!("Requesting {} TXs in batch for block propagation {}", chunk.len(), block_hash);
+            let requests = chunk.iter()
+                .map(|hash| ObjectRequest::Transaction(Immutable::Owned(hash.clone())))
+                .collect();
+
+            for response in peer.request_blocking_objects(requests).await? {
+                if let OwnedObjectResponse::NotFound(request) = response {
+                    return Err(P2pError::ObjectNotFound(request).into())
+                }
+
+                let (tx, hash) = response.into_transaction()?;
+                txs.insert(hash, Arc::new(tx));
+            }
+        }
+
+        Ok(txs)
+    }
+
     async fn request_block(&self, peer: &Arc<Peer>, block_hash: &Hash, header: BlockHeader) -> Result<Block, BlockchainError> {
+        // Fetch all the missing TXs at once if the peer supports it
+        let mut fetched_txs = if peer.supports_object_batch() {
+            let mut missing = Vec::new();
+            for hash in header.get_txs_hashes() {
+                if !self.blockchain.has_tx(hash).await? {
+                    missing.push(hash.clone());
+                }
+            }
+
+            if missing.is_empty() {
+                HashMap::new()
+            } else {
+                self.request_missing_txs(peer, block_hash, missing).await?
+            }
+        } else {
+            HashMap::new()
+        };
+
         // All futures containing the TXs requested
         let mut txs_futures = FuturesOrdered::new();
         for hash in header.get_txs_hashes().iter().cloned() {
+            let fetched = fetched_txs.remove(&hash);
             let future = async {
-                if let Ok(tx) = self.blockchain.get_tx(&hash).await {
+                if let Some(tx) = fetched {
+                    debug!("tx {} fetched in batch", hash);
+                    Ok(tx)
+                } else if let Ok(tx) = self.blockchain.get_tx(&hash).await {
                     debug!("tx {} found in chain", hash);
                     Ok(tx.into_arc())
                 } else {
@@ -1344,13 +1842,34 @@ impl<S: Storage> P2pServer<S> {
                     // TODO: rework object tracker
                     let mut listener = self.object_tracker.request_object_from_peer_with_or_get_notified(
                         Arc::clone(&peer),
-                        ObjectRequest::Transaction(Immutable::Owned(hash)),
+                        ObjectRequest::Transaction(Immutable::Owned(hash.clone())),
                         None
                     ).await?;
 
-                    listener.recv().await
-                        .context("Error while reading transaction for block")?
-                        .into_transaction()
+                    let response = match listener.recv().await {
+                        Ok(response) => response,
+                        Err(e) => {
+                            // The request was dropped by the tracker (timed out or peer failure)
+                            // try once with another peer that propagated us the block
+                            let Some(next_peer) = self.find_peer_for_block_retry(block_hash, &[peer.get_id()]).await else {
+                                return Err(e).context("Error while reading transaction for block")
+                                    .map_err(BlockchainError::from);
+                            };
+
+                            debug!("Request of TX {} for block {} failed from {}, retrying with {}", hash, block_hash, peer, next_peer);
+                            counter!("terminos_p2p_object_tracker_retries_total").increment(1u64);
+                            let mut listener = self.object_tracker.request_object_from_peer_with_or_get_notified(
+                                next_peer,
+                                ObjectRequest::Transaction(Immutable::Owned(hash)),
+                                None
+                            ).await?;
+
+                            listener.recv().await
+                                .context("Error while reading transaction for block")?
+                        }
+                    };
+
+                    response.into_transaction()
                         .map(|(tx, _)| Arc::new(tx))
                 }
             };
@@ -1378,6 +1897,16 @@ impl<S: Storage> P2pServer<S> {
         // Sequential blocks executor
         let mut blocks_executor = Executor::new();
 
+        // Request the block from the peer
+        // We keep the ids of the peers already tried in case we have to retry it
+        let request_block = |peer: Arc<Peer>, header: BlockHeader, block_hash: Arc<Hash>, tried: Vec<u64>| {
+            let zelf = &self;
+            async move {
+                let res = zelf.request_block(&peer, &block_hash, header.clone()).await;
+                (res, block_hash, peer, header, tried)
+            }
+        };
+
         'main: loop {
             select! {
                 biased;
@@ -1399,19 +1928,14 @@ impl<S: Storage> P2pServer<S> {
 
                     counter!("terminos_p2p_incoming_blocks_propagated_total").increment(1u64);
 
-                    let future = async {
-                       let res = self.request_block(&peer, &block_hash, header).await;
-
-                       (res, block_hash, peer)
-                    };
-
-                    scheduler.push_back(future);
+                    let tried = vec![peer.get_id()];
+                    scheduler.push_back(request_block(peer, header, block_hash, tried));
                 },
                 Some(block_hash) = blocks_executor.next() => {
                     pending_requests.remove(&block_hash);
                     scheduler.increment_n();
                 },
-                Some((res, block_hash, peer)) = scheduler.next() => {
+                Some((res, block_hash, peer, header, mut tried)) = scheduler.next() => {
                     // Mark the timestamp of when its being added
                     match res {
                         Ok(block) => {
@@ -1421,8 +1945,8 @@ impl<S: Storage> P2pServer<S> {
                                     debug!("Locking blocks propagation queue to mark the execution timestamp for {}", block_hash);
                                     let mut blocks_propagation_queue = zelf.blocks_propagation_queue.write().await;
                                     match blocks_propagation_queue.peek_mut(&block_hash) {
-                                        Some(v) => {
-                                            *v = Some(get_current_time_in_millis());
+                                        Some(entry) => {
+                                            entry.set_processing_at(get_current_time_in_millis());
                                         },
                                         None => {
                                             warn!("Block propagation {} not found in queue, are we overloaded?", block_hash);
@@ -1431,9 +1955,17 @@ impl<S: Storage> P2pServer<S> {
                                 }
     
                                 debug!("Adding received block {} from {} to chain", block_hash, peer);
-                                if let Err(e) = zelf.blockchain.add_new_block(block, Some(Immutable::Arc(block_hash.clone())), BroadcastOption::All, false).await {
-                                    warn!("Error while adding new block {} from {}: {}", block_hash, peer, e);
-                                    peer.increment_fail_count();
+                                match zelf.blockchain.add_new_block(block, Some(Immutable::Arc(block_hash.clone())), BroadcastOption::All, false).await {
+                                    Ok(()) => {
+                                        let mut blocks_propagation_queue = zelf.blocks_propagation_queue.write().await;
+                                        if let Some(entry) = blocks_propagation_queue.peek_mut(&block_hash) {
+                                            entry.set_accepted_at(get_current_time_in_millis());
+                                        }
+                                    },
+                                    Err(e) => {
+                                        warn!("Error while adding new block {} from {}: {}", block_hash, peer, e);
+                                        peer.increment_fail_count();
+                                    }
                                 }
 
                                 block_hash
@@ -1443,17 +1975,52 @@ impl<S: Storage> P2pServer<S> {
                             blocks_executor.push_back(future);
                         },
                         Err(e) => {
-                            pending_requests.remove(&block_hash);
-                            warn!("Error on blocks processing task: {}", e);
+                            // Retry with another peer that propagated it to us
+                            // The block is kept in the pending requests to prevent any duplicate
+                            let next_peer = if tried.len() <= self.block_request_retries as usize {
+                                self.find_peer_for_block_retry(&block_hash, &tried).await
+                            } else {
+                                None
+                            };
+
+                            if let Some(next_peer) = next_peer {
+                                debug!("Error while requesting block {} from {}: {}, retrying with {}", block_hash, peer, e, next_peer);
+                                tried.push(next_peer.get_id());
+                                scheduler.push_back(request_block(next_peer, header, block_hash, tried));
+                            } else {
+                                pending_requests.remove(&block_hash);
+                                warn!("Error on blocks processing task: {}", e);
+                            }
                         }
                     }
                 }
             }
+
+            self.pending_blocks.store(pending_requests.len(), Ordering::SeqCst);
         }
 
+        self.pending_blocks.store(0, Ordering::SeqCst);
         debug!("Blocks processing task ended");
     }
 
+    // Find a peer that propagated us the block and that we didn't try yet
+    async fn find_peer_for_block_retry(&self, block_hash: &Hash, tried: &[u64]) -> Option<Arc<Peer>> {
+        for peer in self.peer_list.get_cloned_peers().await {
+            if tried.contains(&peer.get_id()) || peer.get_connection().is_closed() {
+                continue;
+            }
+
+            let blocks_propagation = peer.get_blocks_propagation().lock().await;
+            if let Some((direction, is_common)) = blocks_propagation.peek(block_hash) {
+                if direction.contains_in() && !is_common {
+                    return Some(peer.clone());
+                }
+            }
+        }
+
+        None
+    }
+
     async fn request_transaction(
         &self,
         peer: &Arc<Peer>,
@@ -1558,8 +2125,12 @@ impl<S: Storage> P2pServer<S> {
                     };
                 }
             }
+
+            self.pending_txs.store(pending_requests.len() + txs_executor.len(), Ordering::SeqCst);
         }
 
+        self.pending_txs.store(0, Ordering::SeqCst);
+
         debug!("Blocks processing task ended");
     }
 
@@ -1598,6 +2169,13 @@ impl<S: Storage> P2pServer<S> {
                     // there is a overhead of 4 for each packet (packet size u32 4 bytes, packet id u8 is counted in the packet size)
                     trace!("Sending packet with real length: {}", bytes.len());
                     trace!("Packet id #{} : {:?}", bytes[0], bytes);
+
+                    // Connection override has the priority over the server limit
+                    let rate_limit = peer.get_connection()
+                        .get_rate_limit()
+                        .unwrap_or_else(|| self.get_peer_rate_limit());
+                    peer.get_connection().throttle_send(bytes.len() + 4, rate_limit).await;
+
                     let mut buffer = BytesMut::from(bytes);
                     peer.get_connection().send_bytes(&mut buffer).await?;
                     trace!("data sucessfully sent!");
@@ -1762,7 +2340,7 @@ impl<S: Storage> P2pServer<S> {
     // Each packet is a enum variant
     async fn handle_incoming_packet(self: &Arc<Self>, peer: &Arc<Peer>, packet: Packet<'_>) -> Result<(), P2pError> {
         match packet {
-            Packet::Handshake(_) => {
+            Packet::Handshake(_) | Packet::HandshakeExtensions(_) => {
                 error!("{} sent us handshake packet (not valid!)", peer);
                 peer.close().await?;
                 return Err(P2pError::InvalidPacket)
@@ -1770,7 +2348,11 @@ impl<S: Storage> P2pServer<S> {
             Packet::KeyExchange(key) => {
                 trace!("{}: Rotate key packet", peer);
                 let key = key.into_owned();
-                peer.get_connection().rotate_peer_key(key).await?;
+                if let Err(e) = peer.get_connection().rotate_peer_key(key).await {
+                    peer.increment_fail_count();
+                    peer.close().await?;
+                    return Err(e)
+                }
             },
             Packet::TransactionPropagation(packet_wrapper) => {
                 trace!("{}: Transaction Propagation packet", peer);
@@ -1786,6 +2368,11 @@ impl<S: Storage> P2pServer<S> {
                     return Ok(())
                 }
 
+                if !self.blockchain.is_accepting_transactions() {
+                    debug!("skipping TX {} because the mempool is closed", hash);
+                    return Ok(())
+                }
+
                 {
                     let mut txs_cache = peer.get_txs_cache().lock().await;
 
@@ -1803,19 +2390,22 @@ impl<S: Storage> P2pServer<S> {
                 // Avoid sending the TX propagated to a common peer
                 // because we track peerlist of each peers, we can try to determinate it
                 // iterate over all common peers of this peer broadcaster
-                self.get_common_peers_for(&peer).await
-                    .for_each_concurrent(self.stream_concurrency, |common_peer| {
-                        let hash = &hash;
-                        async move {
-                            trace!("{} is a common peer with {}, adding TX {} to its cache", common_peer, peer, hash);
-                            let mut txs_cache = common_peer.get_txs_cache().lock().await;
-                            if !txs_cache.contains(hash) {
-                                debug!("Adding TX {} to common {} cache", hash, common_peer);
-                                // Set it as Out so we don't send it anymore but we can get it one time in case of bad common peer prediction
-                                txs_cache.put(hash.clone(), (Direction::In, true));
+                // This is only needed if we are going to propagate it
+                if self.is_tx_propagation_enabled() {
+                    self.get_common_peers_for(&peer).await
+                        .for_each_concurrent(self.stream_concurrency, |common_peer| {
+                            let hash = &hash;
+                            async move {
+                                trace!("{} is a common peer with {}, adding TX {} to its cache", common_peer, peer, hash);
+                                let mut txs_cache = common_peer.get_txs_cache().lock().await;
+                                if !txs_cache.contains(hash) {
+                                    debug!("Adding TX {} to common {} cache", hash, common_peer);
+                                    // Set it as Out so we don't send it anymore but we can get it one time in case of bad common peer prediction
+                                    txs_cache.put(hash.clone(), (Direction::In, true));
+                                }
                             }
-                        }
-                    }).await;
+                        }).await;
+                }
 
                 // Check that the tx is not in mempool or on disk already
                 debug!("checking if TX {} is already in chain", hash);
@@ -1932,7 +2522,7 @@ impl<S: Storage> P2pServer<S> {
                 {
                     debug!("adding block {} in propagation queue", block_hash);
                     let mut blocks_propagation_queue = self.blocks_propagation_queue.write().await;
-                    blocks_propagation_queue.put(block_hash.clone(), None);
+                    blocks_propagation_queue.put(block_hash.clone(), BlockPropagationEntry::new(get_current_time_in_millis()));
                 }
 
                 debug!("Received block at height {} from {}", header.get_height(), peer);
@@ -1975,9 +2565,19 @@ impl<S: Storage> P2pServer<S> {
             },
             Packet::ChainRequest(packet_wrapper) => {
                 trace!("Received a chain request from {}", peer);
+                if peer.is_observer() {
+                    debug!("Ignoring chain request from observer {}", peer);
+                    return Ok(())
+                }
+
                 let (request, ping) = packet_wrapper.consume();
                 ping.into_owned().update_peer(peer, &self.blockchain).await?;
                 let request = request.into_owned();
+                if !self.is_running() {
+                    debug!("Ignoring chain request from {} as we are stopping", peer);
+                    return Ok(())
+                }
+
                 let last_request = peer.get_last_chain_sync();
                 let time = get_current_time_in_seconds();
                 // Node is trying to ask too fast our chain
@@ -2000,6 +2600,12 @@ impl<S: Storage> P2pServer<S> {
                 // This can be configured by node operators
                 // Verify that the requested size is not bigger than our limit
                 if accepted_response_size > self.max_chain_response_size {
+                    if self.strict_chain_response_size {
+                        debug!("{} requested a chain response of {} blocks but our limit is {}, rejecting", peer, accepted_response_size, self.max_chain_response_size);
+                        return Err(P2pError::ChainResponseSizeExceeded(accepted_response_size, self.max_chain_response_size))
+                    }
+
+                    debug!("{} requested a chain response of {} blocks but our limit is {}, clamping", peer, accepted_response_size, self.max_chain_response_size);
                     accepted_response_size = self.max_chain_response_size;
                 }
 
@@ -2010,8 +2616,12 @@ impl<S: Storage> P2pServer<S> {
                 trace!("Received a chain response from {}", peer);
                 let sender = peer.get_sync_chain_channel()
                     .lock().await
-                    .take()
-                    .ok_or(P2pError::UnrequestedChainResponse)?;
+                    .take();
+
+                let Some(sender) = sender else {
+                    self.handle_unsolicited_response(peer).await;
+                    return Err(P2pError::UnrequestedChainResponse)
+                };
 
                 if sender.send(response).is_err() {
                     error!("Error while sending chain response to channel of {}", peer);
@@ -2055,75 +2665,48 @@ impl<S: Storage> P2pServer<S> {
             },
             Packet::ObjectRequest(request) => {
                 trace!("Received a object request from {}", peer);
-                let request = request.into_owned();
-                match &request {
-                    ObjectRequest::Block(hash) => {
-                        debug!("{} asked full block {}", peer, hash);
-                        let block = {
-                            let storage = self.blockchain.get_storage().read().await;
-                            debug!("storage read acquired for full block request");
-                            storage.get_block_by_hash(hash).await
-                        };
-
-                        match block {
-                            Ok(block) => {
-                                debug!("block {} found, sending it", hash);
-                                peer.send_packet(Packet::ObjectResponse(ObjectResponse::Block(Cow::Borrowed(&block)))).await?;
-                            },
-                            Err(e) => {
-                                debug!("{} asked block '{}' but not present in our chain: {}", peer, hash, e);
-                                peer.send_packet(Packet::ObjectResponse(ObjectResponse::NotFound(request))).await?;
-                            }
-                        };
-                    },
-                    ObjectRequest::BlockHeader(hash) => {
-                        debug!("{} asked block header {}", peer, hash);
-                        let block = {
-                            let storage = self.blockchain.get_storage().read().await;
-                            debug!("storage read acquired for block header request");
-                            storage.get_block_header_by_hash(hash).await
-                        };
-
-                        match block {
-                            Ok(block) => {
-                                debug!("block header {} found, sending it", hash);
-                                peer.send_packet(Packet::ObjectResponse(ObjectResponse::BlockHeader(Cow::Borrowed(&block)))).await?;
-                            },
-                            Err(e) => {
-                                debug!("{} asked block header '{}' but not present in our chain: {}", peer, hash, e);
-                                peer.send_packet(Packet::ObjectResponse(ObjectResponse::NotFound(request))).await?;
-                            }
-                        };
-                    },
-                    ObjectRequest::Transaction(hash) => {
-                        debug!("{} asked tx {}", peer, hash);
-                        match self.blockchain.get_tx(hash).await {
-                            Ok(tx) => {
-                                debug!("tx {} found, sending it", hash);
-                                peer.send_packet(Packet::ObjectResponse(ObjectResponse::Transaction(Cow::Borrowed(&tx)))).await?;
-                            },
-                            Err(e) => {
-                                debug!("{} asked tx '{}' but not present in our chain: {}", peer, hash, e);
-                                peer.send_packet(Packet::ObjectResponse(ObjectResponse::NotFound(request))).await?;
-                            }
-                        }
+                if peer.is_observer() {
+                    debug!("Ignoring object request from observer {}", peer);
+                    return Ok(())
+                }
+
+                let response = self.find_object(peer, request.into_owned()).await;
+                peer.send_packet(Packet::ObjectResponse(response)).await?;
+            },
+            Packet::ObjectBatchRequest(request) => {
+                trace!("Received a object batch request from {}", peer);
+                if peer.is_observer() {
+                    debug!("Ignoring object batch request from observer {}", peer);
+                    return Ok(())
+                }
+
+                // Objects that would make the response bigger than a block are replied as not found
+                // The first one is always sent as a single object is already bounded
+                let requests = request.into_owned().into_requests();
+                let mut responses = Vec::with_capacity(requests.len());
+                let mut total_size = 0;
+                for request in requests {
+                    let response = self.find_object(peer, request.clone()).await;
+                    let size = response.size();
+                    if total_size > 0 && total_size + size > MAX_BLOCK_SIZE {
+                        debug!("Object batch response for {} is too big, replying {} as not found", peer, request);
+                        responses.push(ObjectResponse::NotFound(request));
+                    } else {
+                        total_size += size;
+                        responses.push(response);
                     }
                 }
+
+                peer.send_packet(Packet::ObjectBatchResponse(ObjectBatchResponse::new(responses))).await?;
             },
             Packet::ObjectResponse(response) => {
                 trace!("Received a object response from {}", peer);
-                let response = response.to_owned();
-                trace!("Object response received is {}", response.get_hash());
-
-                // check if we requested it from this peer directly
-                // or that we requested it through the object tracker
-                let request = response.get_request();
-                if let Some(sender) = peer.remove_object_request(&request).await {
-                    // handle the response
-                    sender.send(response)
-                        .with_context(|| format!("Cannot notify listener for {}", request))?;
-                } else if !self.object_tracker.handle_object_response(response).await? {
-                    return Err(P2pError::ObjectNotRequested(request))
+                self.handle_object_response(peer, response).await?;
+            },
+            Packet::ObjectBatchResponse(response) => {
+                trace!("Received a object batch response from {}", peer);
+                for response in response.into_responses() {
+                    self.handle_object_response(peer, response).await?;
                 }
             },
             Packet::NotifyInventoryRequest(packet_wrapper) => {
@@ -2212,7 +2795,7 @@ impl<S: Storage> P2pServer<S> {
                 if next_page.is_some() {
                     trace!("Requesting next page of inventory from {}", peer);
                     let packet = Cow::Owned(NotifyInventoryRequest::new(next_page));
-                    let ping = Cow::Owned(self.build_generic_ping_packet().await?);
+                    let ping = Cow::Owned(self.build_generic_ping_packet().await?.for_peer(peer));
                     peer.set_requested_inventory(true);
                     peer.send_packet(Packet::NotifyInventoryRequest(PacketWrapper::new(packet, ping))).await?;
                 } else {
@@ -2223,6 +2806,11 @@ impl<S: Storage> P2pServer<S> {
                 }
             },
             Packet::BootstrapChainRequest(request) => {
+                if peer.is_observer() {
+                    debug!("Ignoring bootstrap chain request from observer {}", peer);
+                    return Ok(())
+                }
+
                 self.handle_bootstrap_chain_request(peer, request.step()).await?;
             },
             Packet::BootstrapChainResponse(response) => {
@@ -2235,6 +2823,7 @@ impl<S: Storage> P2pServer<S> {
                     }
                 } else {
                     debug!("{} send us a bootstrap chain response of step {:?} but we didn't asked it", peer, response.kind());
+                    self.handle_unsolicited_response(peer).await;
                     return Err(P2pError::UnrequestedBootstrapChainResponse)
                 }
             },
@@ -2287,8 +2876,10 @@ impl<S: Storage> P2pServer<S> {
                             error!("Error while handling packet #{} from {}: {}", packet_id, peer, e);
                             // check that we don't have too many fails
                             // otherwise disconnect peer
-                            // Priority nodes are not disconnected
-                            if peer.get_fail_count() >= zelf.fail_count_limit && !peer.is_priority() {
+                            // Priority and whitelisted nodes are not disconnected
+                            if peer.get_fail_count() >= zelf.fail_count_limit
+                                && !peer.is_priority()
+                                && !zelf.is_fail_count_whitelisted(&peer.get_connection().get_address().ip()) {
                                 warn!("High fail count detected for {}! Closing connection...", peer);
                                 if let Err(e) = peer.close_and_temp_ban(zelf.temp_ban_time).await {
                                     error!("Error while trying to close connection with {} due to high fail count: {}", peer, e);
@@ -2343,8 +2934,11 @@ impl<S: Storage> P2pServer<S> {
 
         // Verify we have the same genesis block hash
         if let Some(genesis_id) = blocks.last() {
-            let our_genesis_hash = storage.get_hash_at_topo_height(0).await?;
-            if *genesis_id.get_hash() != our_genesis_hash || genesis_id.get_topoheight() > start_topoheight {
+            let our_genesis_hash = match self.blockchain.get_genesis_hash() {
+                Some(hash) => Cow::Borrowed(hash),
+                None => Cow::Owned(storage.get_hash_at_topo_height(0).await?)
+            };
+            if *genesis_id.get_hash() != *our_genesis_hash || genesis_id.get_topoheight() > start_topoheight {
                 warn!("Block id list has incorrect block genesis hash! Got {} at {}", genesis_id.get_hash(), genesis_id.get_topoheight());
                 return Err(P2pError::InvalidBlockIdList)
             }
@@ -2393,6 +2987,17 @@ impl<S: Storage> P2pServer<S> {
         false
     }
 
+    // Set the rate limit in bytes per second applied to each peer
+    // Set to 0 to disable it
+    pub fn set_peer_rate_limit(&self, bytes_per_sec: u64) {
+        self.peer_rate_limit.store(bytes_per_sec, Ordering::SeqCst);
+    }
+
+    // Get the rate limit in bytes per second applied to each peer
+    pub fn get_peer_rate_limit(&self) -> u64 {
+        self.peer_rate_limit.load(Ordering::SeqCst)
+    }
+
     // Get the optional tag set 
     pub fn get_tag(&self) -> &Option<String> {
         &self.tag
@@ -2418,6 +3023,26 @@ impl<S: Storage> P2pServer<S> {
         self.peer_list.get_outgoing_peers_count() < self.max_outgoing_peers 
     }
 
+    // Check if we are accepting new connections by verifying if we have free inbound slots available
+    pub async fn accept_new_inbound_connections(&self) -> bool {
+        self.get_inbound_peers_count().await < self.max_inbound_peers
+    }
+
+    // Get the maximum inbound peers count allowed to be connected
+    pub fn get_max_inbound_peers(&self) -> usize {
+        self.max_inbound_peers
+    }
+
+    // Returns the count of inbound peers connected
+    pub async fn get_inbound_peers_count(&self) -> usize {
+        self.peer_list.get_inbound_peers_count().await
+    }
+
+    // Returns the count of outbound peers connected
+    pub fn get_outbound_peers_count(&self) -> usize {
+        self.peer_list.get_outgoing_peers_count()
+    }
+
     // Returns the count of peers connected
     pub async fn get_peer_count(&self) -> usize {
         self.peer_list.size().await
@@ -2458,23 +3083,76 @@ impl<S: Storage> P2pServer<S> {
         &self.bind_address
     }
 
+    // Pause or resume the propagation of transactions to our peers
+    // Fetching of the transactions propagated by our peers (and inventory) stays independent
+    // so we can keep receiving transactions while not rebroadcasting them
+    pub fn set_tx_propagation_enabled(&self, enabled: bool) {
+        self.tx_propagation_enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    // Are we propagating the transactions to our peers
+    pub fn is_tx_propagation_enabled(&self) -> bool {
+        self.tx_propagation_enabled.load(Ordering::SeqCst)
+    }
+
+    // Are we connected to others as an observer
+    pub fn is_observer(&self) -> bool {
+        self.observer
+    }
+
+    // Get the object tracker
+    pub fn get_object_tracker(&self) -> &SharedObjectTracker {
+        &self.object_tracker
+    }
+
     // Get our peerlist
     pub fn get_peer_list(&self) -> &SharedPeerList {
         &self.peer_list
     }
 
+    // Disconnect a peer using its id without banning it or increasing its fail count
+    // Returns true if the peer was found and closed
+    pub async fn disconnect_peer(&self, peer_id: u64) -> bool {
+        let peer = {
+            let peers = self.peer_list.get_peers().read().await;
+            peers.get(&peer_id).cloned()
+        };
+
+        match peer {
+            Some(peer) => {
+                info!("Disconnecting {} manually", peer);
+                if let Err(e) = peer.close().await {
+                    debug!("Error while disconnecting {}: {}", peer, e);
+                }
+                true
+            },
+            None => false
+        }
+    }
+
     // Retrieve at which timestamp the block got finally started to be finally executed
     pub async fn get_block_propagation_timestamp(&self, hash: &Hash) -> Option<TimestampMillis> {
         let blocks_propagation_queue = self.blocks_propagation_queue.read().await;
         blocks_propagation_queue.peek(hash)
-            .copied()
-            .flatten()
+            .and_then(BlockPropagationEntry::get_processing_at)
+    }
+
+    // Compute the propagation stats over the last N accepted blocks
+    // Only blocks still present in the propagation queue are used
+    pub async fn get_propagation_stats(&self, limit: usize) -> PropagationStats {
+        let blocks_propagation_queue = self.blocks_propagation_queue.read().await;
+        compute_propagation_stats(blocks_propagation_queue.iter().map(|(_, entry)| entry), limit)
     }
 
     // Broadcast a new transaction hash using propagation packet
     // This is used so we don't overload the network during spam or high transactions count
     // We simply share its hash to nodes and others nodes can check if they have it already or not
     pub async fn broadcast_tx_hash(&self, tx: Arc<Hash>) {
+        if !self.is_tx_propagation_enabled() {
+            debug!("skipping broadcast of tx hash {} due to propagation paused", tx);
+            return
+        }
+
         debug!("Broadcasting tx hash {}", tx);
         counter!("terminos_p2p_broadcast_tx").increment(1u64);
 
@@ -2487,9 +3165,8 @@ impl<S: Storage> P2pServer<S> {
         };
         debug!("Ping packet has been generated for tx broadcast");
         let current_topoheight = ping.get_topoheight();
-        let packet = Packet::TransactionPropagation(PacketWrapper::new(Cow::Borrowed(&tx), Cow::Owned(ping)));
         // transform packet to bytes (so we don't need to transform it for each peer)
-        let bytes = Bytes::from(packet.to_bytes());
+        let bytes = PingPacketBytes::new(&ping, |ping| Packet::TransactionPropagation(PacketWrapper::new(Cow::Borrowed(&tx), Cow::Borrowed(ping))).to_bytes());
         trace!("Locking peer list for tx broadcast");
         let peers = self.peer_list.get_cloned_peers().await;
         trace!("Lock acquired for tx broadcast");
@@ -2525,7 +3202,7 @@ impl<S: Storage> P2pServer<S> {
 
                     if send {
                         trace!("Broadcasting tx hash {} to {}", tx, peer);
-                        if let Err(e) = peer.send_bytes(bytes.clone()).await {
+                        if let Err(e) = peer.send_bytes(bytes.for_peer(&peer).clone()).await {
                             error!("Error while broadcasting tx hash {} to {}: {}", tx, peer, e);
                         }
                     }
@@ -2543,7 +3220,8 @@ impl<S: Storage> P2pServer<S> {
         debug!("Building the ping packet for broadcast block {}", hash);
         // we build the ping packet ourself this time (we have enough data for it)
         // because this function can be call from Blockchain, which would lead to a deadlock
-        let ping = Ping::new(Cow::Borrowed(&hash), our_topoheight, our_height, pruned_topoheight, cumulative_difficulty, IndexSet::new());
+        // For the same reason, the mempool size is not included
+        let ping = Ping::new(Cow::Borrowed(&hash), our_topoheight, our_height, pruned_topoheight, cumulative_difficulty, IndexSet::new(), None);
         self.broadcast_block_with_ping(block, ping, &hash, is_from_mining, true).await;
     }
 
@@ -2553,15 +3231,14 @@ impl<S: Storage> P2pServer<S> {
         counter!("terminos_p2p_broadcast_block").increment(1u64);
 
         // Build the block propagation packet
-        let block_packet = Packet::BlockPropagation(PacketWrapper::new(Cow::Borrowed(block), Cow::Borrowed(&ping)));
-        let packet_block_bytes = Bytes::from(block_packet.to_bytes());
-        let packet_ping_bytes = Bytes::from(Packet::Ping(Cow::Owned(ping)).to_bytes());
+        let packet_block_bytes = PingPacketBytes::new(&ping, |ping| Packet::BlockPropagation(PacketWrapper::new(Cow::Borrowed(block), Cow::Borrowed(ping))).to_bytes());
+        let packet_ping_bytes = PingPacketBytes::new(&ping, |ping| Packet::Ping(Cow::Borrowed(ping)).to_bytes());
 
         // Lock the block from being handled again as we are broadcasting it
         if is_from_mining {
             debug!("Locking block propagation {}", hash);
             let mut blocks_propagation_queue = self.blocks_propagation_queue.write().await;
-            blocks_propagation_queue.put(hash.clone(), Some(get_current_time_in_millis()));
+            blocks_propagation_queue.put(hash.clone(), BlockPropagationEntry::local(get_current_time_in_millis()));
         }
 
         trace!("start broadcasting block {} to all peers", hash);
@@ -2621,14 +3298,14 @@ impl<S: Storage> P2pServer<S> {
                         // As we expect that the peer will accept this block
                         peer.set_height(block.get_height().max(peer.get_height()));
 
-                        if let Err(e) = peer.send_bytes(packet_block_bytes.clone()).await {
+                        if let Err(e) = peer.send_bytes(packet_block_bytes.for_peer(&peer).clone()).await {
                             debug!("Error on broadcast block {} to {}: {}", hash, peer, e);
                         }
                         trace!("{} has been broadcasted to {}", hash, peer);
                     } else if send_ping {
                         log!(self.block_propagation_log_level, "{} contains {}, don't broadcast block to him", peer, hash);
                         // But we can notify him with a ping packet that we got the block
-                        if let Err(e) = peer.send_bytes(packet_ping_bytes.clone()).await {
+                        if let Err(e) = peer.send_bytes(packet_ping_bytes.for_peer(&peer).clone()).await {
                             debug!("Error on sending ping for notifying that we accepted the block {} to {}: {}", hash, peer, e);
                         } else {
                             trace!("{} has been notified that we have the block {}", peer, hash);
@@ -2638,7 +3315,7 @@ impl<S: Storage> P2pServer<S> {
                 } else if send_ping && peer_height >= block.get_height().saturating_sub(STABLE_LIMIT) {
                     // Peer is above us, send him a ping packet to inform him we got a block propagated
                     log!(self.block_propagation_log_level, "send ping (block {}) for propagation to {}", hash, peer);
-                    if let Err(e) = peer.send_bytes(packet_ping_bytes.clone()).await {
+                    if let Err(e) = peer.send_bytes(packet_ping_bytes.for_peer(&peer).clone()).await {
                         debug!("Error on sending ping to peer for notifying that we got the block {} to {}: {}", hash, peer, e);
                     } else {
                         trace!("{} has been notified that we received the block {}", peer, hash);
@@ -2695,18 +3372,182 @@ impl<S: Storage> P2pServer<S> {
         counter!("terminos_p2p_request_inventory").increment(1u64);
 
         let packet = Cow::Owned(NotifyInventoryRequest::new(None));
-        let ping = Cow::Owned(self.build_generic_ping_packet().await?);
+        let ping = Cow::Owned(self.build_generic_ping_packet().await?.for_peer(peer));
         peer.set_requested_inventory(true);
         peer.send_packet(Packet::NotifyInventoryRequest(PacketWrapper::new(packet, ping))).await?;
         Ok(())
     }
 
+    // Restart the inventory sync of a peer from the first page
+    // Returns false if an inventory request is already in progress with this peer
+    // or if it advertised an empty mempool
+    pub async fn resync_mempool_of(&self, peer_id: u64) -> Result<bool, P2pError> {
+        let peer = {
+            let peers = self.peer_list.get_peers().read().await;
+            peers.get(&peer_id).cloned()
+        }.ok_or(P2pError::PeerNotFoundById(peer_id))?;
+
+        if peer.has_requested_inventory() {
+            debug!("Inventory of {} is already being requested, skipping resync", peer);
+            return Ok(false)
+        }
+
+        // Nothing to fetch, the peer advertised an empty mempool
+        if peer.get_mempool_size() == Some(0) {
+            debug!("{} has an empty mempool, skipping resync", peer);
+            return Ok(false)
+        }
+
+        info!("Resyncing mempool of {}", peer);
+        self.request_inventory_of(&peer).await?;
+        Ok(true)
+    }
+
+    // Force the next chain sync cycle to target the requested peer
+    // regardless of its cumulative difficulty
+    // Blocks received are still verified and checkpoints are respected
+    pub async fn sync_from_peer(&self, peer_id: u64) -> Result<(), P2pError> {
+        if !self.peer_list.has_peer(&peer_id).await {
+            return Err(P2pError::PeerNotFoundById(peer_id))
+        }
+
+        info!("Next chain sync will be done with peer {}", peer_id);
+        *self.forced_sync_peer.lock().await = Some(peer_id);
+        Ok(())
+    }
+
+    // Take the peer requested for the next chain sync cycle if still connected
+    async fn take_forced_sync_peer(&self) -> Option<Arc<Peer>> {
+        let peer_id = self.forced_sync_peer.lock().await.take()?;
+        let peer = {
+            let peers = self.peer_list.get_peers().read().await;
+            peers.get(&peer_id).cloned()
+        };
+
+        if peer.is_none() {
+            warn!("Peer {} requested for chain sync is not connected anymore", peer_id);
+        }
+
+        peer
+    }
+
+    // Compute the median clock offset in seconds between our connected peers and us
+    // Positive if the network clock is ahead of ours
+    // Returns the offset if any peer is connected, and the count of peers used
+    pub async fn get_network_time_offset(&self) -> (Option<i64>, usize) {
+        let offsets: Vec<i64> = {
+            let peers = self.peer_list.get_peers().read().await;
+            peers.values().map(|peer| peer.get_time_offset()).collect()
+        };
+
+        let count = offsets.len();
+        (median_time_offset(offsets), count)
+    }
+
+    // Compare our topoheight with the median topoheight of our connected peers
+    // Returns if we consider our chain as synced, the median topoheight if any peer is connected, and the count of peers
+    pub async fn get_sync_status(&self) -> (bool, Option<TopoHeight>, usize) {
+        let count = self.get_peer_count().await;
+        if count == 0 {
+            return (false, None, 0)
+        }
+
+        let median = self.peer_list.get_median_topoheight(None).await;
+        let synced = !self.is_syncing_chain()
+            && self.blockchain.get_topo_height() + P2P_SYNCED_TOPOHEIGHT_TOLERANCE >= median;
+
+        (synced, Some(median), count)
+    }
+
+    // Warn if our clock deviates too much from the network one
+    // This is diagnostic only, blocks validation is not impacted
+    async fn check_network_time_offset(&self) {
+        let (offset, count) = self.get_network_time_offset().await;
+        if count < P2P_TIME_OFFSET_MIN_PEERS {
+            return;
+        }
+
+        if let Some(offset) = offset.filter(|offset| offset.abs() > P2P_TIME_OFFSET_WARN_THRESHOLD) {
+            warn!("Your system clock deviates by {}s from the median time of {} peers, blocks may be rejected, please synchronize your clock", offset, count);
+        }
+    }
+
     // Clear all p2p connections by kicking peers
     pub async fn clear_connections(&self) {
         self.peer_list.close_all().await;
     }
 }
 
+// Compute the median of the peers clock offsets
+fn median_time_offset(mut offsets: Vec<i64>) -> Option<i64> {
+    if offsets.is_empty() {
+        return None
+    }
+
+    offsets.sort_unstable();
+    let mid = offsets.len() / 2;
+    Some(if offsets.len() % 2 == 0 {
+        (offsets[mid - 1] + offsets[mid]) / 2
+    } else {
+        offsets[mid]
+    })
+}
+
+// Compute the delay in milliseconds before the next chain sync request
+// A random jitter in range [-max_jitter, max_jitter] is applied to the chain sync delay
+// The delay never goes under 2/3 of the chain sync delay enforced by our peers
+fn compute_chain_sync_delay<R: Rng>(max_jitter: u64, rng: &mut R) -> u64 {
+    let delay = CHAIN_SYNC_DELAY * MILLIS_PER_SECOND;
+    if max_jitter == 0 {
+        return delay
+    }
+
+    let jitter = rng.gen_range(0..=max_jitter * 2);
+    let min_delay = delay * 2 / 3;
+    (delay + jitter).saturating_sub(max_jitter).max(min_delay)
+}
+
+// Bytes of a packet carrying a ping, serialized once for all the peers
+// The mempool size is only sent to peers that advertised its support
+struct PingPacketBytes {
+    with_mempool_size: Bytes,
+    without_mempool_size: Bytes
+}
+
+impl PingPacketBytes {
+    fn new<F: Fn(&Ping<'_>) -> Vec<u8>>(ping: &Ping<'_>, serialize: F) -> Self {
+        let with_mempool_size = Bytes::from(serialize(ping));
+        let without_mempool_size = if ping.get_mempool_size().is_some() {
+            Bytes::from(serialize(&ping.clone().without_mempool_size()))
+        } else {
+            with_mempool_size.clone()
+        };
+
+        Self {
+            with_mempool_size,
+            without_mempool_size
+        }
+    }
+
+    fn for_peer(&self, peer: &Peer) -> &Bytes {
+        if peer.supports_mempool_size() {
+            &self.with_mempool_size
+        } else {
+            &self.without_mempool_size
+        }
+    }
+}
+
+// Compute the score of a peer candidate for chain sync
+// A higher cumulative difficulty delta (in bits) and a higher measured rate increase it,
+// while each recent fail and each 100 ms of latency reduce it
+// Score is never zero so every candidate keeps a chance to be selected
+fn compute_peer_selection_score(difficulty_delta_bits: u64, rate: u64, latency: Option<u64>, fail_count: u8) -> u64 {
+    let base = (1 + difficulty_delta_bits).saturating_mul(1 + rate / 1024);
+    let penalty = (1 + fail_count as u64).saturating_mul(1 + latency.unwrap_or(0) / 100);
+    (base / penalty).max(1)
+}
+
 // Check if a socket address is a local address
 pub fn is_local_address(socket_addr: &SocketAddr) -> bool {
     match socket_addr.ip() {
@@ -2741,6 +3582,41 @@ mod tests {
     use std::str::FromStr;
     use super::*;
 
+    #[test]
+    fn test_median_time_offset() {
+        assert_eq!(median_time_offset(Vec::new()), None);
+        assert_eq!(median_time_offset(vec![5]), Some(5));
+        assert_eq!(median_time_offset(vec![10, -3, 1]), Some(1));
+        assert_eq!(median_time_offset(vec![4, -2, 0, 100]), Some(2));
+    }
+
+    #[test]
+    fn test_peer_selection_score() {
+        // Never zero
+        assert_eq!(compute_peer_selection_score(0, 0, Some(u64::MAX), u8::MAX), 1);
+        // Faster peer is preferred
+        assert!(compute_peer_selection_score(8, 64 * 1024, None, 0) > compute_peer_selection_score(8, 1024, None, 0));
+        // Failing peer is penalized
+        assert!(compute_peer_selection_score(8, 1024, None, 5) < compute_peer_selection_score(8, 1024, None, 0));
+        // Slow peer is penalized
+        assert!(compute_peer_selection_score(8, 1024, Some(500), 0) < compute_peer_selection_score(8, 1024, Some(20), 0));
+    }
+
+    #[test]
+    fn test_chain_sync_delay_jitter() {
+        let delay = CHAIN_SYNC_DELAY * MILLIS_PER_SECOND;
+        let mut rng = rand::thread_rng();
+        assert_eq!(compute_chain_sync_delay(0, &mut rng), delay);
+
+        let max_jitter = delay * CHAIN_SYNC_MAX_JITTER_PERCENT as u64 / 100;
+        for _ in 0..1000 {
+            let value = compute_chain_sync_delay(max_jitter, &mut rng);
+            assert!(value >= delay - max_jitter && value <= delay + max_jitter);
+            // Never under the minimum delay enforced by peers
+            assert!(value >= delay * 2 / 3);
+        }
+    }
+
     #[test]
     fn test_is_local_address() {
         assert!(is_local_address(&SocketAddr::from_str("172.20.0.1:2125").unwrap()));
@@ -70,6 +70,26 @@ impl StepKind {
             Self::BlocksMetadata => return None
         })
     }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ChainInfo => "chain_info",
+            Self::Assets => "assets",
+            Self::Keys => "keys",
+            Self::KeyBalances => "key_balances",
+            Self::Accounts => "accounts",
+            Self::MultiSigs => "multisigs",
+            Self::Contracts => "contracts",
+            Self::BlocksMetadata => "blocks_metadata"
+        }
+    }
+
+    // Rough progress of the whole bootstrap based on the steps order
+    pub fn progress(&self, completed: bool) -> f64 {
+        let steps = Self::BlocksMetadata as u8 + 1;
+        let done = *self as u8 + completed as u8;
+        done as f64 * 100.0 / steps as f64
+    }
 }
 
 #[derive(Debug)]
@@ -420,6 +440,23 @@ impl StepResponse {
             Self::BlocksMetadata(_) => StepKind::BlocksMetadata
         }
     }
+
+    // Number of items contained in this response
+    pub fn items_count(&self) -> usize {
+        match self {
+            Self::ChainInfo(_, _, _, _) => 1,
+            Self::Assets(assets, _) => assets.len(),
+            Self::Keys(keys, _) => keys.len(),
+            Self::KeyBalances(balances, _) => balances.len(),
+            Self::SpendableBalances(balances, _) => balances.len(),
+            Self::Accounts(accounts) => accounts.len(),
+            Self::Contracts(contracts, _) => contracts.len(),
+            Self::ContractModule(_) => 1,
+            Self::ContractBalances(balances, _) => balances.len(),
+            Self::ContractStores(stores, _) => stores.len(),
+            Self::BlocksMetadata(blocks) => blocks.len()
+        }
+    }
 }
 
 impl Serializer for StepResponse {
@@ -4,21 +4,149 @@ use terminos_common::{
     difficulty::CumulativeDifficulty,
     network::Network,
     serializer::{Reader, ReaderError, Serializer, Writer},
-    time::TimestampSeconds
+    time::{get_current_time_in_seconds, TimestampSeconds}
 };
-use crate::p2p::{
-    connection::Connection,
-    peer_list::{
-        SharedPeerList,
-        Peer,
-        Rx,
+use crate::{
+    config::P2P_EXTENSIONS_VERSION_REQUIREMENT,
+    core::hard_fork::is_version_matching_requirement,
+    p2p::{
+        connection::Connection,
+        peer_list::{
+            SharedPeerList,
+            Peer,
+            Rx,
+        }
     }
 };
 use std::{
     borrow::Cow,
+    collections::HashSet,
     fmt::{Display, Error, Formatter}
 };
 
+// Handshake extensions
+// The handshake packet keeps its original layout as older nodes reject any trailing byte.
+// Once both handshakes are exchanged, each side sends its extensions in a dedicated
+// HandshakeExtensions packet, but only if the peer version matches P2P_EXTENSIONS_VERSION_REQUIREMENT.
+// Both sides know the version of the other, so they agree on whether the packet is exchanged.
+
+// Extension advertising that our ping packets carry our mempool size
+pub const MEMPOOL_SIZE_EXTENSION_ID: u8 = 0;
+// Extension advertising that we support the object batch packets
+pub const OBJECT_BATCH_EXTENSION_ID: u8 = 1;
+// Extension advertising that we only want to receive the propagated blocks/txs
+// and will never serve chain or object requests
+pub const OBSERVER_EXTENSION_ID: u8 = 2;
+// Extension advertising that we support the compression of packets
+// Compression is only enabled if both peers advertise it
+pub const COMPRESSION_EXTENSION_ID: u8 = 3;
+
+// Optional feature advertised by a peer
+// Each extension is length-prefixed so a node can skip the ones it doesn't know
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HandshakeExtension {
+    id: u8,
+    data: Vec<u8>
+}
+
+impl HandshakeExtension {
+    // Maximum number of extensions in a handshake
+    pub const MAX_COUNT: usize = 32;
+    // Maximum size of the data of a single extension
+    pub const MAX_DATA_SIZE: usize = 1024;
+
+    pub fn new(id: u8, data: Vec<u8>) -> Self {
+        debug_assert!(data.len() <= Self::MAX_DATA_SIZE);
+        Self { id, data }
+    }
+
+    pub fn get_id(&self) -> u8 {
+        self.id
+    }
+
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl Serializer for HandshakeExtension {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u8(self.id);
+        writer.write_u16(self.data.len() as u16);
+        writer.write_bytes(&self.data);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let id = reader.read_u8()?;
+        let len = reader.read_u16()? as usize;
+        if len > Self::MAX_DATA_SIZE {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        let data = reader.read_bytes_ref(len)?.to_vec();
+        Ok(Self { id, data })
+    }
+
+    fn size(&self) -> usize {
+        1 + 2 + self.data.len()
+    }
+}
+
+// Check if a peer running the given version supports the handshake extensions packet
+pub fn is_extensions_supported(version: &str) -> bool {
+    is_version_matching_requirement(version, P2P_EXTENSIONS_VERSION_REQUIREMENT).unwrap_or(false)
+}
+
+// Packet sent right after the handshake to advertise our extensions
+#[derive(Clone, Debug)]
+pub struct HandshakeExtensions {
+    extensions: Vec<HandshakeExtension>
+}
+
+impl HandshakeExtensions {
+    pub fn new(extensions: Vec<HandshakeExtension>) -> Self {
+        debug_assert!(extensions.len() <= HandshakeExtension::MAX_COUNT);
+        Self { extensions }
+    }
+
+    pub fn into_inner(self) -> Vec<HandshakeExtension> {
+        self.extensions
+    }
+}
+
+impl Serializer for HandshakeExtensions {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u8(self.extensions.len() as u8);
+        for extension in self.extensions.iter() {
+            extension.write(writer);
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let count = reader.read_u8()? as usize;
+        if count > HandshakeExtension::MAX_COUNT {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        let mut ids = HashSet::with_capacity(count);
+        let mut extensions = Vec::with_capacity(count);
+        for _ in 0..count {
+            let extension = HandshakeExtension::read(reader)?;
+            if !ids.insert(extension.get_id()) {
+                debug!("Duplicated extension {} in handshake extensions packet", extension.get_id());
+                return Err(ReaderError::InvalidValue)
+            }
+            extensions.push(extension);
+        }
+
+        Ok(Self { extensions })
+    }
+
+    fn size(&self) -> usize {
+        1 + self.extensions.iter().map(|e| e.size()).sum::<usize>()
+    }
+}
+
 // this Handshake is the first data sent when connecting to the server
 // If handshake is valid, server reply with his own handshake
 // We just have to repeat this request to all peers until we reach max connection
@@ -54,7 +182,10 @@ pub struct Handshake<'a> {
     cumulative_difficulty: Cow<'a, CumulativeDifficulty>,
     // By default it's true, and peer allow to be shared to others and/or through API
     // If false, we must not share it
-    can_be_shared: bool
+    can_be_shared: bool,
+    // Extensions received in the HandshakeExtensions packet
+    // They are not part of the handshake packet itself
+    extensions: Vec<HandshakeExtension>
 } // Server reply with his own list of peers, but we remove all already known by requester for the response.
 
 impl<'a> Handshake<'a> {
@@ -82,12 +213,29 @@ impl<'a> Handshake<'a> {
             top_hash,
             genesis_hash,
             cumulative_difficulty,
-            can_be_shared
+            can_be_shared,
+            extensions: Vec::new()
         }
     }
 
+    // Set the extensions received from the peer
+    pub fn set_extensions(&mut self, extensions: Vec<HandshakeExtension>) {
+        debug_assert!(extensions.len() <= HandshakeExtension::MAX_COUNT);
+        self.extensions = extensions;
+    }
+
+    // Can we exchange the handshake extensions with this peer
+    pub fn supports_extensions(&self) -> bool {
+        is_extensions_supported(&self.version)
+    }
+
     // Create a new peer using its connection and this handshake packet
-    pub fn create_peer(self, connection: Connection, priority: bool, peer_list: SharedPeerList, propagate_txs: bool) -> (Peer, Rx) {
+    pub fn create_peer(self, connection: Connection, priority: bool, peer_list: SharedPeerList, send_queue_limit: usize, propagate_txs: bool) -> (Peer, Rx) {
+        let object_batch = self.has_extension(OBJECT_BATCH_EXTENSION_ID);
+        let ping_mempool_size = self.has_extension(MEMPOOL_SIZE_EXTENSION_ID);
+        let observer = self.is_observer();
+        // Offset between the peer clock and ours, positive if the peer is ahead
+        let time_offset = self.utc_time as i64 - get_current_time_in_seconds() as i64;
         Peer::new(
             connection,
             self.get_peer_id(),
@@ -102,6 +250,11 @@ impl<'a> Handshake<'a> {
             self.cumulative_difficulty.into_owned(),
             peer_list,
             self.can_be_shared,
+            observer,
+            object_batch,
+            ping_mempool_size,
+            time_offset,
+            send_queue_limit,
             propagate_txs
         )
     }
@@ -153,6 +306,26 @@ impl<'a> Handshake<'a> {
     pub fn get_pruned_topoheight(&self) -> &Option<u64> {
         &self.pruned_topoheight
     }
+
+    pub fn get_cumulative_difficulty(&self) -> &CumulativeDifficulty {
+        &self.cumulative_difficulty
+    }
+
+    pub fn is_observer(&self) -> bool {
+        self.has_extension(OBSERVER_EXTENSION_ID)
+    }
+
+    pub fn supports_compression(&self) -> bool {
+        self.has_extension(COMPRESSION_EXTENSION_ID)
+    }
+
+    pub fn get_extensions(&self) -> &[HandshakeExtension] {
+        &self.extensions
+    }
+
+    pub fn has_extension(&self, id: u8) -> bool {
+        self.extensions.iter().any(|e| e.get_id() == id)
+    }
 }
 
 impl Serializer for Handshake<'_> {
@@ -266,4 +439,110 @@ impl Display for Handshake<'_> {
         };
         write!(f, "Handshake[version: {}, node tag: {}, network_id: {}, peer_id: {}, utc_time: {}, block_height: {}, block_top_hash: {}]", self.get_version(), node_tag, hex::encode(self.get_network_id()), self.get_peer_id(), self.get_utc_time(), self.get_block_height(), self.get_block_top_hash())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_handshake() -> Handshake<'static> {
+        Handshake::new(
+            Cow::Owned("1.0.0".to_owned()),
+            Network::Testnet,
+            Cow::Owned(Some("node".to_owned())),
+            Cow::Owned([1u8; 16]),
+            42,
+            2125,
+            1000,
+            10,
+            9,
+            None,
+            Cow::Owned(Hash::zero()),
+            Cow::Owned(Hash::max()),
+            Cow::Owned(CumulativeDifficulty::from_u64(1000)),
+            true
+        )
+    }
+
+    // Reader of the handshake as done by the nodes
+    // released before the handshake extensions
+    fn read_baseline(bytes: &[u8]) -> Result<(), ReaderError> {
+        let mut reader = Reader::new(bytes);
+        reader.read_string()?;
+        Network::read(&mut reader)?;
+        reader.read_optional_string()?;
+        reader.read_bytes::<[u8; 16]>(16)?;
+        reader.read_u64()?;
+        reader.read_u16()?;
+        reader.read_u64()?;
+        reader.read_u64()?;
+        reader.read_u64()?;
+        Option::<u64>::read(&mut reader)?;
+        reader.read_hash()?;
+        reader.read_hash()?;
+        CumulativeDifficulty::read(&mut reader)?;
+        reader.read_bool()?;
+
+        // The packet must be fully read
+        if reader.total_read() != bytes.len() {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handshake_parsed_by_baseline_reader() {
+        let mut handshake = create_handshake();
+        handshake.set_extensions(vec![
+            HandshakeExtension::new(OBSERVER_EXTENSION_ID, Vec::new()),
+            HandshakeExtension::new(COMPRESSION_EXTENSION_ID, Vec::new())
+        ]);
+
+        let bytes = handshake.to_bytes();
+        assert_eq!(bytes.len(), handshake.size());
+        read_baseline(&bytes).unwrap();
+
+        // Extensions are not part of the handshake packet
+        let decoded = Handshake::from_bytes(&bytes).unwrap();
+        assert!(decoded.get_extensions().is_empty());
+        assert_eq!(decoded.get_peer_id(), 42);
+    }
+
+    #[test]
+    fn test_handshake_extensions_roundtrip() {
+        let extensions = vec![
+            HandshakeExtension::new(0, vec![1, 2, 3]),
+            HandshakeExtension::new(COMPRESSION_EXTENSION_ID, Vec::new()),
+            HandshakeExtension::new(200, Vec::new())
+        ];
+        let packet = HandshakeExtensions::new(extensions.clone());
+        let bytes = packet.to_bytes();
+        assert_eq!(bytes.len(), packet.size());
+
+        let decoded = HandshakeExtensions::from_bytes(&bytes).unwrap().into_inner();
+        assert_eq!(decoded, extensions);
+
+        let mut handshake = create_handshake();
+        handshake.set_extensions(decoded);
+        assert!(handshake.supports_compression());
+        assert!(!handshake.is_observer());
+    }
+
+    #[test]
+    fn test_handshake_duplicated_extension() {
+        let packet = HandshakeExtensions::new(vec![
+            HandshakeExtension::new(1, vec![1]),
+            HandshakeExtension::new(1, vec![2])
+        ]);
+        assert!(HandshakeExtensions::from_bytes(&packet.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_extensions_version_requirement() {
+        assert!(!is_extensions_supported("0.1.0-abcdef1"));
+        assert!(is_extensions_supported("0.2.0-abcdef1"));
+        assert!(!is_extensions_supported("invalid"));
+        assert!(create_handshake().supports_extensions());
+    }
+}
@@ -24,7 +24,7 @@ pub use peer_disconnected::*;
 pub use ping::Ping;
 
 // All registered packet ids
-const KEY_EXCHANGE_ID: u8 = 0;
+pub const KEY_EXCHANGE_ID: u8 = 0;
 const HANDSHAKE_ID: u8 = 1;
 const TX_PROPAGATION_ID: u8 = 2;
 const BLOCK_PROPAGATION_ID: u8 = 3;
@@ -38,6 +38,9 @@ const NOTIFY_INV_RESPONSE_ID: u8 = 10;
 const BOOTSTRAP_CHAIN_REQUEST_ID: u8 = 11;
 const BOOTSTRAP_CHAIN_RESPONSE_ID: u8 = 12;
 const PEER_DISCONNECTED_ID: u8 = 13;
+const OBJECT_BATCH_REQUEST_ID: u8 = 14;
+const OBJECT_BATCH_RESPONSE_ID: u8 = 15;
+const HANDSHAKE_EXTENSIONS_ID: u8 = 16;
 
 // PacketWrapper allows us to link any Packet to a Ping
 #[derive(Debug)]
@@ -81,6 +84,8 @@ impl<'a, T: Serializer + Clone> Serializer for PacketWrapper<'a, T> {
 #[derive(Debug)]
 pub enum Packet<'a> {
     Handshake(Cow<'a, Handshake<'a>>), // first packet to connect to a node
+    // sent after the handshake only to peers supporting it
+    HandshakeExtensions(Cow<'a, HandshakeExtensions>),
     // packet contains tx hash, view this packet as a "notification"
     // instead of sending the TX directly, we notify our peers
     // so the peer that already have this TX in mempool don't have to read it again
@@ -92,6 +97,8 @@ pub enum Packet<'a> {
     Ping(Cow<'a, Ping<'a>>),
     ObjectRequest(Cow<'a, ObjectRequest>),
     ObjectResponse(ObjectResponse<'a>),
+    ObjectBatchRequest(Cow<'a, ObjectBatchRequest>),
+    ObjectBatchResponse(ObjectBatchResponse<'a>),
     NotifyInventoryRequest(PacketWrapper<'a, NotifyInventoryRequest>),
     NotifyInventoryResponse(NotifyInventoryResponse<'a>),
     BootstrapChainRequest(BootstrapChainRequest<'a>),
@@ -105,6 +112,7 @@ impl Packet<'_> {
     pub fn get_id(&self) -> u8 {
         match self {
             Packet::Handshake(_) => HANDSHAKE_ID,
+            Packet::HandshakeExtensions(_) => HANDSHAKE_EXTENSIONS_ID,
             Packet::TransactionPropagation(_) => TX_PROPAGATION_ID,
             Packet::BlockPropagation(_) => BLOCK_PROPAGATION_ID,
             Packet::ChainRequest(_) => CHAIN_REQUEST_ID,
@@ -112,6 +120,8 @@ impl Packet<'_> {
             Packet::Ping(_) => PING_ID,
             Packet::ObjectRequest(_) => OBJECT_REQUEST_ID,
             Packet::ObjectResponse(_) => OBJECT_RESPONSE_ID,
+            Packet::ObjectBatchRequest(_) => OBJECT_BATCH_REQUEST_ID,
+            Packet::ObjectBatchResponse(_) => OBJECT_BATCH_RESPONSE_ID,
             Packet::NotifyInventoryRequest(_) => NOTIFY_INV_REQUEST_ID,
             Packet::NotifyInventoryResponse(_) => NOTIFY_INV_RESPONSE_ID,
             Packet::BootstrapChainRequest(_) => BOOTSTRAP_CHAIN_REQUEST_ID,
@@ -125,6 +135,8 @@ impl Packet<'_> {
         match self {
             Packet::ObjectRequest(_)
             | Packet::ObjectResponse(_)
+            | Packet::ObjectBatchRequest(_)
+            | Packet::ObjectBatchResponse(_)
             | Packet::ChainRequest(_) 
             | Packet::ChainResponse(_)
             | Packet::NotifyInventoryRequest(_)
@@ -148,6 +160,7 @@ impl<'a> Serializer for Packet<'a> {
         let packet = match id {
             KEY_EXCHANGE_ID => Packet::KeyExchange(Cow::Owned(EncryptionKey::read(reader)?)),
             HANDSHAKE_ID => Packet::Handshake(Cow::Owned(Handshake::read(reader)?)),
+            HANDSHAKE_EXTENSIONS_ID => Packet::HandshakeExtensions(Cow::Owned(HandshakeExtensions::read(reader)?)),
             TX_PROPAGATION_ID => Packet::TransactionPropagation(PacketWrapper::read(reader)?),
             BLOCK_PROPAGATION_ID => Packet::BlockPropagation(PacketWrapper::read(reader)?),
             CHAIN_REQUEST_ID => Packet::ChainRequest(PacketWrapper::read(reader)?),
@@ -155,6 +168,8 @@ impl<'a> Serializer for Packet<'a> {
             PING_ID => Packet::Ping(Cow::Owned(Ping::read(reader)?)),
             OBJECT_REQUEST_ID => Packet::ObjectRequest(Cow::Owned(ObjectRequest::read(reader)?)),
             OBJECT_RESPONSE_ID => Packet::ObjectResponse(ObjectResponse::read(reader)?),
+            OBJECT_BATCH_REQUEST_ID => Packet::ObjectBatchRequest(Cow::Owned(ObjectBatchRequest::read(reader)?)),
+            OBJECT_BATCH_RESPONSE_ID => Packet::ObjectBatchResponse(ObjectBatchResponse::read(reader)?),
             NOTIFY_INV_REQUEST_ID => Packet::NotifyInventoryRequest(PacketWrapper::read(reader)?), 
             NOTIFY_INV_RESPONSE_ID => Packet::NotifyInventoryResponse(NotifyInventoryResponse::read(reader)?),
             BOOTSTRAP_CHAIN_REQUEST_ID => Packet::BootstrapChainRequest(BootstrapChainRequest::read(reader)?),
@@ -177,6 +192,7 @@ impl<'a> Serializer for Packet<'a> {
         match self {
             Packet::KeyExchange(key) => Self::write_packet(writer, KEY_EXCHANGE_ID, key),
             Packet::Handshake(handshake) => Self::write_packet(writer, HANDSHAKE_ID, handshake.as_ref()),
+            Packet::HandshakeExtensions(extensions) => Self::write_packet(writer, HANDSHAKE_EXTENSIONS_ID, extensions.as_ref()),
             Packet::TransactionPropagation(tx) => Self::write_packet(writer, TX_PROPAGATION_ID, tx),
             Packet::BlockPropagation(block) => Self::write_packet(writer, BLOCK_PROPAGATION_ID, block),
             Packet::ChainRequest(request) => Self::write_packet(writer, CHAIN_REQUEST_ID, request),
@@ -184,6 +200,8 @@ impl<'a> Serializer for Packet<'a> {
             Packet::Ping(ping) => Self::write_packet(writer, PING_ID, ping.as_ref()),
             Packet::ObjectRequest(request) => Self::write_packet(writer, OBJECT_REQUEST_ID, request.as_ref()),
             Packet::ObjectResponse(response) => Self::write_packet(writer, OBJECT_RESPONSE_ID, response),
+            Packet::ObjectBatchRequest(request) => Self::write_packet(writer, OBJECT_BATCH_REQUEST_ID, request.as_ref()),
+            Packet::ObjectBatchResponse(response) => Self::write_packet(writer, OBJECT_BATCH_RESPONSE_ID, response),
             Packet::NotifyInventoryRequest(request) => Self::write_packet(writer, NOTIFY_INV_REQUEST_ID, request),
             Packet::NotifyInventoryResponse(inventory) => Self::write_packet(writer, NOTIFY_INV_RESPONSE_ID, inventory),
             Packet::BootstrapChainRequest(request) => Self::write_packet(writer, BOOTSTRAP_CHAIN_REQUEST_ID, request),
@@ -19,7 +19,10 @@ use terminos_common::{
 };
 use std::{borrow::Cow, fmt::{Display, Formatter, self}};
 
-use crate::p2p::error::P2pError;
+use crate::{
+    config::PEER_OBJECTS_BATCH_MAX_LEN,
+    p2p::error::P2pError
+};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum ObjectRequest {
@@ -223,4 +226,99 @@ impl Display for OwnedObjectResponse {
             Self::NotFound(request) => write!(f, "OwnedObjectResponse(NotFound({}))", request),
         }
     }
-}
\ No newline at end of file
+}
+
+// Request several objects at once
+// Only sent to peers advertising the OBJECT_BATCH_EXTENSION_ID handshake extension
+#[derive(Clone, Debug)]
+pub struct ObjectBatchRequest {
+    requests: Vec<ObjectRequest>
+}
+
+impl ObjectBatchRequest {
+    pub fn new(requests: Vec<ObjectRequest>) -> Self {
+        debug_assert!(!requests.is_empty() && requests.len() <= PEER_OBJECTS_BATCH_MAX_LEN);
+        Self { requests }
+    }
+
+    pub fn get_requests(&self) -> &[ObjectRequest] {
+        &self.requests
+    }
+
+    pub fn into_requests(self) -> Vec<ObjectRequest> {
+        self.requests
+    }
+}
+
+impl Serializer for ObjectBatchRequest {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u8(self.requests.len() as u8);
+        for request in self.requests.iter() {
+            request.write(writer);
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let len = reader.read_u8()? as usize;
+        if len == 0 || len > PEER_OBJECTS_BATCH_MAX_LEN {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        let mut requests = Vec::with_capacity(len);
+        for _ in 0..len {
+            requests.push(ObjectRequest::read(reader)?);
+        }
+
+        Ok(Self { requests })
+    }
+
+    fn size(&self) -> usize {
+        1 + self.requests.iter().map(|r| r.size()).sum::<usize>()
+    }
+}
+
+// Response to a batch request
+// Each requested object has its own response, in the same order,
+// including the NotFound ones
+#[derive(Debug)]
+pub struct ObjectBatchResponse<'a> {
+    responses: Vec<ObjectResponse<'a>>
+}
+
+impl<'a> ObjectBatchResponse<'a> {
+    pub fn new(responses: Vec<ObjectResponse<'a>>) -> Self {
+        debug_assert!(responses.len() <= PEER_OBJECTS_BATCH_MAX_LEN);
+        Self { responses }
+    }
+
+    pub fn into_responses(self) -> Vec<ObjectResponse<'a>> {
+        self.responses
+    }
+}
+
+impl<'a> Serializer for ObjectBatchResponse<'a> {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u8(self.responses.len() as u8);
+        for response in self.responses.iter() {
+            response.write(writer);
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let len = reader.read_u8()? as usize;
+        if len == 0 || len > PEER_OBJECTS_BATCH_MAX_LEN {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        let mut responses = Vec::with_capacity(len);
+        for _ in 0..len {
+            responses.push(ObjectResponse::read(reader)?);
+        }
+
+        Ok(Self { responses })
+    }
+
+    fn size(&self) -> usize {
+        1 + self.responses.iter().map(|r| r.size()).sum::<usize>()
+    }
+}
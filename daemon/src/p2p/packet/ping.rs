@@ -39,18 +39,23 @@ pub struct Ping<'a> {
     height: u64,
     pruned_topoheight: Option<u64>,
     cumulative_difficulty: CumulativeDifficulty,
-    peer_list: IndexSet<SocketAddr>
+    peer_list: IndexSet<SocketAddr>,
+    // Number of txs in the mempool of the sender
+    // Older versions reject any trailing byte, so it is only written
+    // for peers that advertised the MEMPOOL_SIZE_EXTENSION_ID handshake extension
+    mempool_size: Option<u64>
 }
 
 impl<'a> Ping<'a> {
-    pub fn new(top_hash: Cow<'a, Hash>, topoheight: u64, height: u64, pruned_topoheight: Option<u64>, cumulative_difficulty: CumulativeDifficulty, peer_list: IndexSet<SocketAddr>) -> Self {
+    pub fn new(top_hash: Cow<'a, Hash>, topoheight: u64, height: u64, pruned_topoheight: Option<u64>, cumulative_difficulty: CumulativeDifficulty, peer_list: IndexSet<SocketAddr>, mempool_size: Option<u64>) -> Self {
         Self {
             top_hash,
             topoheight,
             height,
             pruned_topoheight,
             cumulative_difficulty,
-            peer_list
+            peer_list,
+            mempool_size
         }
     }
 
@@ -79,6 +84,9 @@ impl<'a> Ping<'a> {
 
         peer.set_pruned_topoheight(self.pruned_topoheight);
         peer.set_cumulative_difficulty(self.cumulative_difficulty).await;
+        if let Some(mempool_size) = self.mempool_size {
+            peer.set_mempool_size(mempool_size);
+        }
 
         if peer.sharable() {
             trace!("Locking RPC Server to notify PeerStateUpdated event");
@@ -152,6 +160,25 @@ impl<'a> Ping<'a> {
         self.topoheight
     }
 
+    pub fn get_mempool_size(&self) -> Option<u64> {
+        self.mempool_size
+    }
+
+    // Remove the mempool size from the packet
+    pub fn without_mempool_size(mut self) -> Self {
+        self.mempool_size = None;
+        self
+    }
+
+    // Keep only the fields supported by the peer
+    pub fn for_peer(self, peer: &Peer) -> Self {
+        if peer.supports_mempool_size() {
+            self
+        } else {
+            self.without_mempool_size()
+        }
+    }
+
     pub fn get_peers(&self) -> &IndexSet<SocketAddr> {
         &self.peer_list
     }
@@ -172,6 +199,9 @@ impl Serializer for Ping<'_> {
         for peer in &self.peer_list {
             peer.write(writer);
         }
+        if let Some(mempool_size) = self.mempool_size {
+            writer.write_u64(&mempool_size);
+        }
     }
 
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
@@ -201,7 +231,14 @@ impl Serializer for Ping<'_> {
             }
         }
 
-        Ok(Self { top_hash, topoheight, height, pruned_topoheight, cumulative_difficulty, peer_list })
+        // Only sent by peers knowing that we support it
+        let mempool_size = if reader.size() > 0 {
+            Some(reader.read_u64()?)
+        } else {
+            None
+        };
+
+        Ok(Self { top_hash, topoheight, height, pruned_topoheight, cumulative_difficulty, peer_list, mempool_size })
     }
 
     fn size(&self) -> usize {
@@ -212,12 +249,69 @@ impl Serializer for Ping<'_> {
         self.cumulative_difficulty.size() +
         // u8 for the length of the peer list
         1 +
-        self.peer_list.iter().map(|p| p.size()).sum::<usize>()
+        self.peer_list.iter().map(|p| p.size()).sum::<usize>() +
+        self.mempool_size.map_or(0, |v| v.size())
     }
 }
 
 impl Display for Ping<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Ping[top_hash: {}, topoheight: {}, height: {}, pruned topoheight: {:?}, peers length: {}]", self.top_hash, self.topoheight, self.height, self.pruned_topoheight, self.peer_list.len())
+        write!(f, "Ping[top_hash: {}, topoheight: {}, height: {}, pruned topoheight: {:?}, peers length: {}, mempool size: {:?}]", self.top_hash, self.topoheight, self.height, self.pruned_topoheight, self.peer_list.len(), self.mempool_size)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reader of the ping as done by the nodes
+    // released before the mempool size extension
+    fn read_baseline(bytes: &[u8]) -> Result<(), ReaderError> {
+        let mut reader = Reader::new(bytes);
+        reader.read_hash()?;
+        reader.read_u64()?;
+        reader.read_u64()?;
+        Option::<u64>::read(&mut reader)?;
+        CumulativeDifficulty::read(&mut reader)?;
+        let peers_len = reader.read_u8()?;
+        for _ in 0..peers_len {
+            SocketAddr::read(&mut reader)?;
+        }
+
+        // The packet must be fully read
+        if reader.total_read() != bytes.len() {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        Ok(())
+    }
+
+    fn create_ping() -> Ping<'static> {
+        let mut peer_list = IndexSet::new();
+        peer_list.insert("1.2.3.4:2125".parse().unwrap());
+        Ping::new(Cow::Owned(Hash::zero()), 10, 9, Some(5), CumulativeDifficulty::from_u64(1000), peer_list, Some(42))
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_ping_without_mempool_size_parsed_by_baseline_reader() {
+        let ping = create_ping().without_mempool_size();
+        let bytes = ping.to_bytes();
+        assert_eq!(bytes.len(), ping.size());
+        read_baseline(&bytes).unwrap();
+
+        let decoded = Ping::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.get_mempool_size(), None);
+    }
+
+    #[test]
+    fn test_ping_with_mempool_size() {
+        let ping = create_ping();
+        let bytes = ping.to_bytes();
+        assert_eq!(bytes.len(), ping.size());
+        // Older versions can't read it, it must only be sent to peers supporting it
+        assert!(read_baseline(&bytes).is_err());
+
+        let decoded = Ping::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.get_mempool_size(), Some(42));
+        assert_eq!(decoded.get_peers().len(), 1);
+    }
+}
@@ -1,5 +1,6 @@
 mod disk_cache;
 mod peer;
+mod subnet;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -19,16 +20,23 @@ use x25519_dalek::PublicKey;
 use bytes::Bytes;
 use log::{info, debug, trace, error};
 use terminos_common::{
-    tokio::sync::{mpsc::Sender, RwLock},
+    tokio::sync::{mpsc::Sender, Mutex, RwLock},
     block::TopoHeight,
     serializer::{Reader, ReaderError, Serializer, Writer},
-    time::{get_current_time_in_seconds, TimestampSeconds}
+    time::{
+        get_current_time_in_millis,
+        get_current_time_in_seconds,
+        TimestampMillis,
+        TimestampSeconds
+    }
 };
 use crate::{
     config::{
         PEER_FAIL_TO_CONNECT_LIMIT,
         PEER_TEMP_BAN_TIME_ON_CONNECT,
-        P2P_PEERLIST_RETRY_AFTER
+        P2P_PEERLIST_RETRY_AFTER,
+        P2P_RECONNECT_BACKOFF_MIN,
+        P2P_RECONNECT_BACKOFF_MAX
     },
     p2p::packet::PacketPeerDisconnected
 };
@@ -39,6 +47,7 @@ use super::{
 
 pub use peer::*;
 pub use disk_cache::*;
+pub use subnet::*;
 
 pub type SharedPeerList = Arc<PeerList>;
 
@@ -60,6 +69,15 @@ pub struct PeerList {
     stream_concurrency: usize,
     // How many outgoing peers we currently have connected
     outgoing_peers: AtomicUsize,
+    // Banned subnets with the timestamp until they are banned
+    // These are kept in memory only
+    banned_subnets: RwLock<HashMap<Subnet, TimestampSeconds>>,
+    // IPs that can't be banned by a subnet ban
+    // This contains the IPs of the configured exclusive nodes
+    subnet_ban_exemptions: HashSet<IpAddr>,
+    // Reconnect backoff for exclusive and seed nodes
+    // Contains the current backoff in millis and when we can retry
+    reconnect_backoffs: Mutex<HashMap<SocketAddr, (u64, TimestampMillis)>>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
@@ -89,18 +107,29 @@ pub struct PeerListEntry {
     // public key used for the DH key exchange
     // It is optional because we want to create peerlist entries without a public key
     // for banlist etc
-    public_key: Option<PublicKey>
+    public_key: Option<PublicKey>,
+    // Lifetime successful connections count
+    // Unlike the fail count, it is never reset
+    connection_count: u64,
+    // Lifetime failed connection attempts count
+    // Unlike the fail count, it is never reset
+    failure_count: u64,
+    // Last error that made a connection attempt fail
+    last_error: Option<String>
 }
 
 impl PeerList {
-    pub fn new(capacity: usize, stream_concurrency: usize, filename: String, peer_disconnect_channel: Option<Sender<Arc<Peer>>>) -> Result<SharedPeerList, P2pError> {
+    pub fn new(capacity: usize, stream_concurrency: usize, filename: String, peer_disconnect_channel: Option<Sender<Arc<Peer>>>, subnet_ban_exemptions: HashSet<IpAddr>) -> Result<SharedPeerList, P2pError> {
         Ok(Arc::new(
             Self {
                 peers: RwLock::new(HashMap::with_capacity(capacity)),
                 peer_disconnect_channel,
                 cache: DiskCache::new(filename)?,
                 stream_concurrency,
-                outgoing_peers: AtomicUsize::new(0)
+                outgoing_peers: AtomicUsize::new(0),
+                banned_subnets: RwLock::new(HashMap::new()),
+                subnet_ban_exemptions,
+                reconnect_backoffs: Mutex::new(HashMap::new())
             }
         ))
     }
@@ -126,6 +155,12 @@ impl PeerList {
         self.outgoing_peers.load(Ordering::SeqCst)
     }
 
+    // Count of peers that connected to us
+    pub async fn get_inbound_peers_count(&self) -> usize {
+        let peers = self.peers.read().await;
+        peers.values().filter(|peer| !peer.is_out()).count()
+    }
+
     fn decrement_outgoing_peers_count(&self) -> usize {
         self.outgoing_peers.fetch_sub(1, Ordering::SeqCst)
     }
@@ -186,7 +221,7 @@ impl PeerList {
         }
 
         // Update the peerlist entry
-        self.update_peer(&peer).await?;
+        self.update_peer(&peer, false).await?;
         
         if let Some(peer_disconnect_channel) = &self.peer_disconnect_channel {
             debug!("Notifying server that {} disconnected", peer);
@@ -221,18 +256,24 @@ impl PeerList {
             self.increment_outgoing_peers_count();
         }
 
-        self.update_peer(&peer).await?;
+        self.update_peer(&peer, true).await?;
 
         Ok(())
     }
 
     // Update a peer in the stored peerlist
-    async fn update_peer(&self, peer: &Peer) -> Result<(), P2pError> {
+    // If this is a new connection, its lifetime connections count is increased
+    async fn update_peer(&self, peer: &Peer, new_connection: bool) -> Result<(), P2pError> {
         let addr = peer.get_outgoing_address();
         let ip = addr.ip();
         if self.cache.has_peerlist_entry(&ip)? {
             let mut entry = self.cache.get_peerlist_entry(&ip)?;
             debug!("Updating {} in stored peerlist", peer);
+            if new_connection {
+                entry.increment_connection_count();
+                peer.set_lifetime_stats(entry.get_connection_count(), entry.get_failure_count());
+            }
+
             // reset the fail count and update the last seen time
             entry.set_fail_count(0);
             if entry.get_first_seen().is_none() {
@@ -250,6 +291,11 @@ impl PeerList {
         } else {
             debug!("Saving {} in stored peerlist", peer);
             let mut entry = PeerListEntry::new(Some(peer.get_local_port()), PeerListEntryState::Graylist, peer.is_out());
+            if new_connection {
+                entry.increment_connection_count();
+                peer.set_lifetime_stats(entry.get_connection_count(), entry.get_failure_count());
+            }
+
             entry.set_first_seen(peer.get_connection().connected_on());
             entry.set_last_seen(get_current_time_in_seconds());
             entry.set_last_connection_try(None);
@@ -305,7 +351,7 @@ impl PeerList {
                     error!("Error while trying to signal exit to {}: {}", peer, e);
                 }
     
-                if let Err(e) = self.update_peer(&peer).await {
+                if let Err(e) = self.update_peer(&peer, false).await {
                     error!("Error while updating peer {}: {}", peer, e);
                 }
             })
@@ -378,8 +424,12 @@ impl PeerList {
         self.addr_has_state(ip, PeerListEntryState::Blacklist).await
     }
 
-    // Verify that the peer is not blacklisted or temp banned
+    // Verify that the peer is not blacklisted, temp banned or in a banned subnet
     pub async fn is_allowed(&self, ip: &IpAddr) -> Result<bool, P2pError> {
+        if self.is_in_banned_subnet(ip).await {
+            return Ok(false);
+        }
+
         if !self.cache.has_peerlist_entry(ip)? {
             return Ok(true);
         }
@@ -506,6 +556,99 @@ impl PeerList {
         Ok(())
     }
 
+    // Check if the IP is exempted from subnet bans
+    fn is_exempt_from_subnet_ban(&self, ip: &IpAddr) -> bool {
+        ip.is_loopback() || self.subnet_ban_exemptions.contains(ip)
+    }
+
+    // Check if the IP is part of a subnet currently banned
+    pub async fn is_in_banned_subnet(&self, ip: &IpAddr) -> bool {
+        if self.is_exempt_from_subnet_ban(ip) {
+            return false;
+        }
+
+        let now = get_current_time_in_seconds();
+        let banned_subnets = self.banned_subnets.read().await;
+        banned_subnets.iter()
+            .any(|(subnet, until)| *until > now && subnet.contains(ip))
+    }
+
+    // Ban a whole subnet for a duration in seconds
+    // All connected peers in this subnet are disconnected
+    // Loopback and exclusive nodes addresses are never banned
+    // A /0 prefix is rejected as it would match every address
+    pub async fn ban_subnet(&self, subnet: Subnet, seconds: u64) -> Result<(), P2pError> {
+        trace!("banning subnet {} for {} seconds", subnet, seconds);
+        if subnet.get_prefix() == 0 {
+            return Err(P2pError::SubnetBanTooWide(subnet));
+        }
+
+        {
+            let now = get_current_time_in_seconds();
+            let mut banned_subnets = self.banned_subnets.write().await;
+            // Clean the expired bans
+            banned_subnets.retain(|_, until| *until > now);
+            banned_subnets.insert(subnet, now.saturating_add(seconds));
+        }
+
+        let peers = self.peers.read().await;
+        for peer in peers.values() {
+            let ip = peer.get_connection().get_address().ip();
+            if subnet.contains(&ip) && !self.is_exempt_from_subnet_ban(&ip) {
+                debug!("Kicking {} due to subnet ban {}", peer, subnet);
+                peer.signal_exit().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Get all the subnets currently banned with the timestamp until they are banned
+    pub async fn get_banned_subnets(&self) -> Vec<(Subnet, TimestampSeconds)> {
+        let now = get_current_time_in_seconds();
+        let banned_subnets = self.banned_subnets.read().await;
+        banned_subnets.iter()
+            .filter(|(_, until)| **until > now)
+            .map(|(subnet, until)| (*subnet, *until))
+            .collect()
+    }
+
+    // Check if we can try to reconnect to this address
+    pub async fn can_reconnect_to(&self, addr: &SocketAddr) -> bool {
+        let backoffs = self.reconnect_backoffs.lock().await;
+        backoffs.get(addr)
+            .map(|(_, retry_at)| *retry_at <= get_current_time_in_millis())
+            .unwrap_or(true)
+    }
+
+    // Double the reconnect backoff of an address after a failed attempt
+    pub async fn increase_reconnect_backoff(&self, addr: &SocketAddr) {
+        let mut backoffs = self.reconnect_backoffs.lock().await;
+        let backoff = backoffs.get(addr)
+            .map(|(backoff, _)| (backoff * 2).min(P2P_RECONNECT_BACKOFF_MAX))
+            .unwrap_or(P2P_RECONNECT_BACKOFF_MIN);
+
+        debug!("Backing off {} for {} ms before reconnecting", addr, backoff);
+        backoffs.insert(*addr, (backoff, get_current_time_in_millis() + backoff));
+    }
+
+    // Reset the reconnect backoff of an address after a successful handshake
+    pub async fn reset_reconnect_backoff(&self, addr: &SocketAddr) {
+        let mut backoffs = self.reconnect_backoffs.lock().await;
+        backoffs.remove(addr);
+    }
+
+    // Get in how many millis the next backoff expires
+    // None if no address is currently backed off
+    pub async fn get_next_reconnect_in(&self) -> Option<u64> {
+        let now = get_current_time_in_millis();
+        let backoffs = self.reconnect_backoffs.lock().await;
+        backoffs.values()
+            .filter(|(_, retry_at)| *retry_at > now)
+            .map(|(_, retry_at)| retry_at - now)
+            .min()
+    }
+
     // whitelist a peer address
     // if this peer is already known, change its state to whitelist
     // otherwise create a new PeerListEntry with state whitelist
@@ -595,7 +738,8 @@ impl PeerList {
 
     // increase the fail count of a peer
     // If tempban is allowed, and the fail count is at the limit, temp ban the peer
-    pub async fn increase_fail_count_for_peerlist_entry(&self, ip: &IpAddr, temp_ban: bool) -> Result<(), P2pError> {
+    // The failure is also recorded in the lifetime stats of the peer
+    pub async fn increase_fail_count_for_peerlist_entry(&self, ip: &IpAddr, temp_ban: bool, error: &P2pError) -> Result<(), P2pError> {
         trace!("increasing fail count for {}, allow temp ban: {}", ip, temp_ban);
         let mut entry = if self.cache.has_peerlist_entry(ip)? {
             self.cache.get_peerlist_entry(ip)?
@@ -603,6 +747,8 @@ impl PeerList {
             PeerListEntry::new(None, PeerListEntryState::Graylist, false)
         };
 
+        entry.record_failure(error.to_string());
+
         if *entry.get_state() != PeerListEntryState::Whitelist {
             debug!("Increasing fail count for {}", ip);
             let mut fail_count = entry.get_fail_count();
@@ -623,6 +769,7 @@ impl PeerList {
             }
         } else {
             debug!("{} is whitelisted, not increasing fail count", ip);
+            self.cache.set_peerlist_entry(ip, entry)?;
         }
 
         Ok(())
@@ -676,7 +823,10 @@ impl PeerListEntry {
             local_port,
             temp_ban_until: None,
             state,
-            public_key: None
+            public_key: None,
+            connection_count: 0,
+            failure_count: 0,
+            last_error: None
         }
     }
 
@@ -747,6 +897,24 @@ impl PeerListEntry {
     pub fn set_public_key(&mut self, public_key: PublicKey) {
         self.public_key = Some(public_key);
     }
+
+    fn get_connection_count(&self) -> u64 {
+        self.connection_count
+    }
+
+    fn increment_connection_count(&mut self) {
+        self.connection_count = self.connection_count.saturating_add(1);
+    }
+
+    fn get_failure_count(&self) -> u64 {
+        self.failure_count
+    }
+
+    // Record a failed connection attempt with its error
+    fn record_failure(&mut self, error: String) {
+        self.failure_count = self.failure_count.saturating_add(1);
+        self.last_error = Some(error);
+    }
 }
 
 impl Display for PeerListEntry {
@@ -754,12 +922,15 @@ impl Display for PeerListEntry {
         let current_time = get_current_time_in_seconds();
         write!(
             f,
-            "PeerListEntry[state: {:?}, first seen: {}, last seen: {}, last try: {}, fail count: {}, ban: {}]",
+            "PeerListEntry[state: {:?}, first seen: {}, last seen: {}, last try: {}, fail count: {}, connections: {}, failures: {}, last error: {}, ban: {}]",
             self.state,
             self.first_seen.map(|v| format!("{} ago", format_duration(Duration::from_secs(current_time - v)))).unwrap_or_else(|| "never".to_string()),
             self.last_seen.map(|v| format!("{} ago", format_duration(Duration::from_secs(current_time - v)))).unwrap_or_else(|| "never".to_string()),
             self.last_connection_try.map(|v| format!("{} ago", format_duration(Duration::from_secs(current_time - v)))).unwrap_or_else(|| "never".to_string()),
             self.fail_count,
+            self.connection_count,
+            self.failure_count,
+            self.last_error.as_deref().unwrap_or("none"),
             self.temp_ban_until.map_or("none".to_string(), |temp_ban_until| {
                 if temp_ban_until > current_time {
                     format!("{} ago", format_duration(Duration::from_secs(temp_ban_until - current_time)))
@@ -805,6 +976,10 @@ impl Serializer for PeerListEntry {
         if let Some(public_key) = &self.public_key {
             public_key.as_bytes().write(writer);
         }
+
+        self.connection_count.write(writer);
+        self.failure_count.write(writer);
+        self.last_error.write(writer);
     }
 
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
@@ -818,6 +993,13 @@ impl Serializer for PeerListEntry {
         let state = PeerListEntryState::read(reader)?;
         let public_key = Option::<[u8; 32]>::read(reader)?.map(PublicKey::from);
 
+        // Lifetime stats are missing in entries stored by older versions
+        let (connection_count, failure_count, last_error) = if reader.size() > 0 {
+            (reader.read_u64()?, reader.read_u64()?, Option::read(reader)?)
+        } else {
+            (0, 0, None)
+        };
+
         Ok(Self {
             first_seen,
             last_seen,
@@ -827,7 +1009,10 @@ impl Serializer for PeerListEntry {
             out_success,
             temp_ban_until,
             state,
-            public_key
+            public_key,
+            connection_count,
+            failure_count,
+            last_error
         })
     }
 }
\ No newline at end of file
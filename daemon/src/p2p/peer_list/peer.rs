@@ -3,17 +3,19 @@ use crate::{
         PEER_FAIL_TIME_RESET, PEER_BLOCK_CACHE_SIZE,
         PEER_TX_CACHE_SIZE, PEER_TIMEOUT_BOOTSTRAP_STEP,
         PEER_TIMEOUT_REQUEST_OBJECT, CHAIN_SYNC_TIMEOUT_SECS,
-        PEER_PACKET_CHANNEL_SIZE, PEER_PEERS_CACHE_SIZE,
-        PEER_OBJECTS_CONCURRENCY
+        PEER_PEERS_CACHE_SIZE,
+        PEER_OBJECTS_CONCURRENCY,
+        PEER_OBJECTS_BATCH_MAX_LEN
     },
     p2p::packet::PacketWrapper
 };
 use anyhow::Context;
+use futures::future::try_join_all;
 use metrics::counter;
 use terminos_common::{
     tokio::{
         select,
-        sync::{broadcast, mpsc, oneshot, Mutex, Semaphore},
+        sync::{broadcast, mpsc::{self, error::TrySendError}, oneshot, Mutex, Semaphore},
         time::timeout,
     },
     api::daemon::{Direction, TimedDirection},
@@ -42,10 +44,10 @@ use std::{
     hash::{Hash as StdHash, Hasher},
     net::{IpAddr, SocketAddr},
     sync::{
-        atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering},
+        atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering},
         Arc
     },
-    time::Duration
+    time::{Duration, Instant}
 };
 use lru::LruCache;
 use bytes::Bytes;
@@ -106,6 +108,12 @@ pub struct Peer {
     last_fail_count: AtomicU64,
     // fail count: if greater than 20, we should close this connection
     fail_count: AtomicU8,
+    // count of responses received that we never requested
+    unsolicited_responses: AtomicU32,
+    // lifetime successful connections to this address, including this one
+    connection_count: AtomicU64,
+    // lifetime failed connection attempts to this address
+    failure_count: AtomicU64,
     // shared pointer to the peer list in case of disconnection
     peer_list: SharedPeerList,
     // map of requested objects from this peer
@@ -118,6 +126,15 @@ pub struct Peer {
     last_ping: AtomicU64,
     // last time we sent a ping packet to this peer
     last_ping_sent: AtomicU64,
+    // Exponential moving average of the round trip time in milliseconds
+    // measured between an object request and its response
+    latency: AtomicU64,
+    // Store if the latency has been measured at least once
+    has_latency: AtomicBool,
+    // Last mempool size advertised by the peer in a ping packet
+    mempool_size: AtomicU64,
+    // Store if the peer advertised its mempool size at least once
+    has_mempool_size: AtomicBool,
     // cumulative difficulty of peer chain
     cumulative_difficulty: Mutex<CumulativeDifficulty>,
     // All transactions propagated from/to this peer
@@ -144,6 +161,16 @@ pub struct Peer {
     outgoing_address: SocketAddr,
     // Determine if this peer allows to be shared to others and/or through API
     sharable: bool,
+    // Observer peers only receive propagated objects
+    // We never serve chain or object requests to them
+    observer: bool,
+    // Does the peer support the object batch packets
+    object_batch: bool,
+    // Does the peer accept the mempool size in ping packets
+    ping_mempool_size: bool,
+    // Offset in seconds between the peer clock and ours at handshake
+    // Positive if the peer clock is ahead of ours
+    time_offset: i64,
     // Channel to send bytes to the writer task
     tx: Tx,
     // Channel to notify the tasks to exit
@@ -175,13 +202,20 @@ impl Peer {
         cumulative_difficulty: CumulativeDifficulty,
         peer_list: SharedPeerList,
         sharable: bool,
+        observer: bool,
+        object_batch: bool,
+        ping_mempool_size: bool,
+        time_offset: i64,
+        send_queue_limit: usize,
         propagate_txs: bool
     ) -> (Self, Rx) {
         let mut outgoing_address = *connection.get_address();
         outgoing_address.set_port(local_port);
 
         let (exit_channel, _) = broadcast::channel(1);
-        let (tx, rx) = mpsc::channel(PEER_PACKET_CHANNEL_SIZE);
+        // The channel is bounded by the send queue limit
+        // to disconnect the peer instead of buffering too many packets
+        let (tx, rx) = mpsc::channel(send_queue_limit);
 
         (Self {
             connection,
@@ -195,6 +229,9 @@ impl Peer {
             priority,
             last_fail_count: AtomicU64::new(0),
             fail_count: AtomicU8::new(0),
+            unsolicited_responses: AtomicU32::new(0),
+            connection_count: AtomicU64::new(0),
+            failure_count: AtomicU64::new(0),
             last_chain_sync: AtomicU64::new(0),
             peer_list,
             objects_requested: Mutex::new(LruCache::new(NonZeroUsize::new(PEER_OBJECTS_CONCURRENCY).expect("PEER_OBJECTS_CONCURRENCY must be non-zero"))),
@@ -202,6 +239,10 @@ impl Peer {
             last_peer_list: AtomicU64::new(0),
             last_ping: AtomicU64::new(0),
             last_ping_sent: AtomicU64::new(0),
+            latency: AtomicU64::new(0),
+            has_latency: AtomicBool::new(false),
+            mempool_size: AtomicU64::new(0),
+            has_mempool_size: AtomicBool::new(false),
             cumulative_difficulty: Mutex::new(cumulative_difficulty),
             txs_cache: Mutex::new(LruCache::new(NonZeroUsize::new(PEER_TX_CACHE_SIZE).expect("PEER_TX_CACHE_SIZE must be non-zero"))),
             blocks_propagation: Mutex::new(LruCache::new(NonZeroUsize::new(PEER_BLOCK_CACHE_SIZE).expect("PEER_BLOCK_CACHE_SIZE must be non-zero"))),
@@ -213,6 +254,10 @@ impl Peer {
             sync_chain: Mutex::new(None),
             outgoing_address,
             sharable,
+            observer,
+            object_batch,
+            ping_mempool_size,
+            time_offset,
             exit_channel,
             tx,
             read_task: Mutex::new(TaskState::Inactive),
@@ -358,6 +403,26 @@ impl Peer {
         self.sharable
     }
 
+    // Is the peer connected in observer mode
+    pub fn is_observer(&self) -> bool {
+        self.observer
+    }
+
+    // Does the peer support the object batch packets
+    pub fn supports_object_batch(&self) -> bool {
+        self.object_batch
+    }
+
+    // Does the peer accept the mempool size in ping packets
+    pub fn supports_mempool_size(&self) -> bool {
+        self.ping_mempool_size
+    }
+
+    // Get the clock offset in seconds with this peer measured at handshake
+    pub fn get_time_offset(&self) -> i64 {
+        self.time_offset
+    }
+
     // Get the last time we got a fail from the peer
     pub fn get_last_fail_count(&self) -> u64 {
         self.last_fail_count.load(Ordering::SeqCst)
@@ -404,6 +469,32 @@ impl Peer {
         self.set_last_fail_count(current_time);
     }
 
+    // Get the count of unsolicited responses sent by the peer
+    pub fn get_unsolicited_responses(&self) -> u32 {
+        self.unsolicited_responses.load(Ordering::SeqCst)
+    }
+
+    // Increment the unsolicited responses counter and returns the new value
+    pub fn increment_unsolicited_responses(&self) -> u32 {
+        self.unsolicited_responses.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    // Get the lifetime successful connections count to this address
+    pub fn get_connection_count(&self) -> u64 {
+        self.connection_count.load(Ordering::SeqCst)
+    }
+
+    // Get the lifetime failed connection attempts count to this address
+    pub fn get_failure_count(&self) -> u64 {
+        self.failure_count.load(Ordering::SeqCst)
+    }
+
+    // Set the lifetime stats from the stored peerlist
+    pub fn set_lifetime_stats(&self, connection_count: u64, failure_count: u64) {
+        self.connection_count.store(connection_count, Ordering::SeqCst);
+        self.failure_count.store(failure_count, Ordering::SeqCst);
+    }
+
     // Get the last time we got a chain sync request
     // This is used to prevent spamming the chain sync packet
     pub fn get_last_chain_sync(&self) -> TimestampSeconds {
@@ -434,6 +525,8 @@ impl Peer {
         debug!("requesting {}", request);
         counter!("terminos_p2p_objects_requests", "peer" => self.get_id().to_string()).increment(1u64);
 
+        // Set only if we sent the request ourself, so the round trip time can be measured
+        let mut sent_at = None;
         let mut receiver = {
             let mut objects = self.objects_requested.lock().await;
             if let Some(sender) = objects.get(&request) {
@@ -441,6 +534,7 @@ impl Peer {
                 sender.subscribe()
             } else {
                 self.send_packet(Packet::ObjectRequest(Cow::Borrowed(&request))).await?;
+                sent_at = Some(Instant::now());
                 let (sender, receiver) = broadcast::channel(1);
                 // clone is necessary in case timeout has occured
                 if objects.put(request.clone(), sender).is_some() {
@@ -466,6 +560,12 @@ impl Peer {
         };
         debug!("received response for request {}", request);
 
+        if let Some(sent_at) = sent_at {
+            let rtt = sent_at.elapsed().as_millis() as u64;
+            trace!("{} replied to {} in {} ms", self, request, rtt);
+            self.update_latency(rtt);
+        }
+
         // Verify that the object is the one we requested
         let object_hash = object.get_hash();
         if *object_hash != *request.get_hash() {
@@ -480,6 +580,71 @@ impl Peer {
         Ok(object)
     }
 
+    // Request several objects from this peer in a single packet and wait on all of them until timeout
+    // Responses are returned in the same order as the requests
+    // NotFound responses are kept so the caller can handle them per object
+    pub async fn request_blocking_objects(&self, requests: Vec<ObjectRequest>) -> Result<Vec<OwnedObjectResponse>, P2pError> {
+        debug_assert!(self.supports_object_batch());
+        debug_assert!(!requests.is_empty() && requests.len() <= PEER_OBJECTS_BATCH_MAX_LEN);
+
+        trace!("waiting for {} permits", requests.len());
+        let _permit = self.objects_semaphore.acquire_many(requests.len() as u32).await?;
+        debug!("requesting {} objects in batch", requests.len());
+        counter!("terminos_p2p_objects_requests", "peer" => self.get_id().to_string()).increment(requests.len() as u64);
+
+        let mut receivers = {
+            let mut objects = self.objects_requested.lock().await;
+            let mut receivers = Vec::with_capacity(requests.len());
+            let mut to_request = Vec::new();
+            for request in requests.iter() {
+                if let Some(sender) = objects.get(request) {
+                    debug!("{} was already sent to {}, subscribing to the same channel", request, self);
+                    receivers.push(sender.subscribe());
+                } else {
+                    let (sender, receiver) = broadcast::channel(1);
+                    if objects.put(request.clone(), sender).is_some() {
+                        warn!("{} was already pending for {}", request, self);
+                    }
+                    to_request.push(request.clone());
+                    receivers.push(receiver);
+                }
+            }
+
+            if !to_request.is_empty() {
+                self.send_packet(Packet::ObjectBatchRequest(Cow::Owned(ObjectBatchRequest::new(to_request)))).await?;
+            }
+
+            receivers
+        };
+
+        let mut exit_channel = self.get_exit_receiver();
+        let objects = select! {
+            _ = exit_channel.recv() => return Err(P2pError::Disconnected),
+            res = timeout(Duration::from_millis(PEER_TIMEOUT_REQUEST_OBJECT), try_join_all(receivers.iter_mut().map(|r| r.recv()))) => match res {
+                Ok(res) => res.context("Error on blocking objects response")?,
+                Err(_) => {
+                    warn!("Requested batch of {} objects from {} has timed out", requests.len(), self);
+                    let mut objects = self.objects_requested.lock().await;
+                    for request in requests.iter() {
+                        objects.pop(request);
+                    }
+                    return Err(P2pError::ObjectRequestTimedOut(requests[0].clone()));
+                }
+            }
+        };
+        debug!("received responses for {} objects in batch", objects.len());
+
+        // Verify that the objects are the ones we requested
+        for (request, object) in requests.iter().zip(objects.iter()) {
+            let object_hash = object.get_hash();
+            if *object_hash != *request.get_hash() {
+                return Err(P2pError::InvalidObjectResponse(object_hash.clone()))
+            }
+        }
+
+        Ok(objects)
+    }
+
     // Request a bootstrap chain from this peer and wait on it until we receive it or until timeout
     pub async fn request_boostrap_chain(&self, step: StepRequest<'_>) -> Result<StepResponse, P2pError> {
         let step_kind = step.kind();
@@ -611,6 +776,42 @@ impl Peer {
         self.last_ping.store(value, Ordering::SeqCst)
     }
 
+    // Update the moving average of the latency with a round trip time sample in milliseconds
+    fn update_latency(&self, sample: u64) {
+        let latency = if self.has_latency.swap(true, Ordering::SeqCst) {
+            // Smooth it so a single spike doesn't dominate
+            let previous = self.latency.load(Ordering::SeqCst);
+            (previous * 3 + sample) / 4
+        } else {
+            sample
+        };
+        self.latency.store(latency, Ordering::SeqCst);
+    }
+
+    // Get the average latency in milliseconds measured with this peer
+    pub fn get_latency(&self) -> Option<u64> {
+        if self.has_latency.load(Ordering::SeqCst) {
+            Some(self.latency.load(Ordering::SeqCst))
+        } else {
+            None
+        }
+    }
+
+    // Get the last mempool size advertised by this peer
+    pub fn get_mempool_size(&self) -> Option<u64> {
+        if self.has_mempool_size.load(Ordering::SeqCst) {
+            Some(self.mempool_size.load(Ordering::SeqCst))
+        } else {
+            None
+        }
+    }
+
+    // Set the mempool size advertised by this peer
+    pub fn set_mempool_size(&self, value: u64) {
+        self.mempool_size.store(value, Ordering::SeqCst);
+        self.has_mempool_size.store(true, Ordering::SeqCst);
+    }
+
     // Get the last time a inventory has been requested
     pub fn get_last_inventory(&self) -> TimestampSeconds {
         self.last_inventory.load(Ordering::SeqCst)
@@ -683,9 +884,25 @@ impl Peer {
 
     // Send packet bytes to the peer
     // This will send the bytes to the writer task through its channel
+    // If the send queue is full, the peer is too slow and we signal its exit
     pub async fn send_bytes(&self, bytes: Bytes) -> Result<(), P2pError> {
-        self.tx.send(bytes).await
-            .map_err(|e| P2pError::SendError(e.to_string()))
+        match self.tx.try_send(bytes) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                warn!("Send queue of {} is full ({} packets pending), disconnecting it", self, self.get_pending_send());
+                counter!("terminos_p2p_send_queue_full").increment(1u64);
+                if let Err(e) = self.signal_exit().await {
+                    debug!("Error while signaling exit of {}: {}", self, e);
+                }
+                Err(P2pError::SendQueueFull)
+            },
+            Err(e) => Err(P2pError::SendError(e.to_string()))
+        }
+    }
+
+    // Get the count of packets waiting to be sent to the peer
+    pub fn get_pending_send(&self) -> usize {
+        self.tx.max_capacity() - self.tx.capacity()
     }
 
     pub async fn set_read_task_state(&self, state: TaskState) {
@@ -734,7 +951,7 @@ impl Display for Peer {
         let read_task = self.read_task.try_lock().map(|v| *v).unwrap_or(TaskState::Unknown);
         let write_task = self.write_task.try_lock().map(|v| *v).unwrap_or(TaskState::Unknown);
 
-        write!(f, "Peer[connection: {}, id: {}, topoheight: {}, top hash: {}, height: {}, pruned: {}, priority: {}, tag: {}, version: {}, fail count: {}, out: {}, peers: {}, tasks: {:?}/{:?}, txs: {}]",
+        write!(f, "Peer[connection: {}, id: {}, topoheight: {}, top hash: {}, height: {}, pruned: {}, priority: {}, tag: {}, version: {}, fail count: {}, unsolicited: {}, out: {}, peers: {}, tasks: {:?}/{:?}, txs: {}]",
             self.get_connection(),
             self.get_id(),
             self.get_topoheight(),
@@ -745,6 +962,7 @@ impl Display for Peer {
             self.get_node_tag().as_ref().unwrap_or(&"None".to_owned()),
             self.get_version(),
             self.get_fail_count(),
+            self.get_unsolicited_responses(),
             self.is_out(),
             peers,
             read_task,
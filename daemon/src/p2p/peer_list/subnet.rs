@@ -0,0 +1,137 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    net::IpAddr,
+    str::FromStr
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SubnetError {
+    #[error("Missing prefix length in subnet, expected CIDR format (ex: 192.168.1.0/24)")]
+    MissingPrefix,
+    #[error("Invalid IP address in subnet")]
+    InvalidAddress,
+    #[error("Invalid prefix length {}, maximum is {}", _0, _1)]
+    InvalidPrefix(u8, u8),
+}
+
+// A subnet in CIDR notation (IPv4 or IPv6)
+// The address is always stored with its host bits cleared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subnet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Subnet {
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<Self, SubnetError> {
+        let max = Self::max_prefix(&addr);
+        if prefix > max {
+            return Err(SubnetError::InvalidPrefix(prefix, max));
+        }
+
+        Ok(Self {
+            addr: Self::mask(&addr, prefix),
+            prefix
+        })
+    }
+
+    // Maximum prefix length based on the IP version
+    fn max_prefix(addr: &IpAddr) -> u8 {
+        match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128
+        }
+    }
+
+    // Clear the host bits of the address
+    fn mask(addr: &IpAddr, prefix: u8) -> IpAddr {
+        match addr {
+            IpAddr::V4(ip) => {
+                let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+                IpAddr::V4((u32::from(*ip) & mask).into())
+            },
+            IpAddr::V6(ip) => {
+                let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+                IpAddr::V6((u128::from(*ip) & mask).into())
+            }
+        }
+    }
+
+    // Get the network address
+    pub fn get_address(&self) -> &IpAddr {
+        &self.addr
+    }
+
+    // Get the prefix length
+    pub fn get_prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    // Check if the IP address is part of this subnet
+    // An IPv4 address is never part of an IPv6 subnet and vice versa
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(_), IpAddr::V4(_)) | (IpAddr::V6(_), IpAddr::V6(_)) => Self::mask(ip, self.prefix) == self.addr,
+            _ => false
+        }
+    }
+}
+
+impl FromStr for Subnet {
+    type Err = SubnetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = s.split_once('/')
+            .ok_or(SubnetError::MissingPrefix)?;
+
+        let addr: IpAddr = addr.parse()
+            .map_err(|_| SubnetError::InvalidAddress)?;
+        let max = Self::max_prefix(&addr);
+        let prefix: u8 = prefix.parse()
+            .map_err(|_| SubnetError::InvalidPrefix(u8::MAX, max))?;
+
+        Self::new(addr, prefix)
+    }
+}
+
+impl Display for Subnet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subnet_v4() {
+        let subnet: Subnet = "192.168.1.42/24".parse().unwrap();
+        assert_eq!(subnet.to_string(), "192.168.1.0/24");
+        assert!(subnet.contains(&"192.168.1.1".parse().unwrap()));
+        assert!(subnet.contains(&"192.168.1.255".parse().unwrap()));
+        assert!(!subnet.contains(&"192.168.2.1".parse().unwrap()));
+        assert!(!subnet.contains(&"::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_subnet_v6() {
+        let subnet: Subnet = "2001:db8::/32".parse().unwrap();
+        assert!(subnet.contains(&"2001:db8:1::1".parse().unwrap()));
+        assert!(!subnet.contains(&"2001:db9::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_subnet_edges() {
+        let all: Subnet = "0.0.0.0/0".parse().unwrap();
+        assert!(all.contains(&"1.1.1.1".parse().unwrap()));
+
+        let single: Subnet = "1.1.1.1/32".parse().unwrap();
+        assert!(single.contains(&"1.1.1.1".parse().unwrap()));
+        assert!(!single.contains(&"1.1.1.2".parse().unwrap()));
+
+        assert!("1.1.1.1/33".parse::<Subnet>().is_err());
+        assert!("1.1.1.1".parse::<Subnet>().is_err());
+    }
+}
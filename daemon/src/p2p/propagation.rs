@@ -0,0 +1,125 @@
+use terminos_common::{
+    api::daemon::PropagationStats,
+    time::TimestampMillis
+};
+
+// Entry of the blocks propagation queue
+#[derive(Debug, Clone, Copy)]
+pub struct BlockPropagationEntry {
+    // When we received the block propagation
+    first_seen: TimestampMillis,
+    // When we started to execute the block
+    processing_at: Option<TimestampMillis>,
+    // When the block got accepted in our chain
+    // None for blocks not yet accepted or mined by us
+    accepted_at: Option<TimestampMillis>,
+}
+
+impl BlockPropagationEntry {
+    // Block received from a peer and waiting to be executed
+    pub fn new(first_seen: TimestampMillis) -> Self {
+        Self {
+            first_seen,
+            processing_at: None,
+            accepted_at: None
+        }
+    }
+
+    // Block produced locally, it is never counted in the propagation stats
+    pub fn local(timestamp: TimestampMillis) -> Self {
+        Self {
+            first_seen: timestamp,
+            processing_at: Some(timestamp),
+            accepted_at: None
+        }
+    }
+
+    pub fn get_processing_at(&self) -> Option<TimestampMillis> {
+        self.processing_at
+    }
+
+    pub fn set_processing_at(&mut self, timestamp: TimestampMillis) {
+        self.processing_at = Some(timestamp);
+    }
+
+    pub fn set_accepted_at(&mut self, timestamp: TimestampMillis) {
+        self.accepted_at = Some(timestamp);
+    }
+}
+
+// Compute the propagation stats over the last `limit` accepted blocks
+// Delay is the time between the first time we saw the block and its inclusion in chain
+pub fn compute_propagation_stats<'a, I: Iterator<Item = &'a BlockPropagationEntry>>(entries: I, limit: usize) -> PropagationStats {
+    let mut accepted: Vec<(TimestampMillis, u64)> = entries.filter_map(|entry| entry.accepted_at.map(|accepted_at| (accepted_at, accepted_at.saturating_sub(entry.first_seen))))
+        .collect();
+
+    // Only keep the most recently accepted blocks
+    accepted.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+    accepted.truncate(limit);
+
+    let mut delays: Vec<u64> = accepted.into_iter()
+        .map(|(_, delay)| delay)
+        .collect();
+    delays.sort_unstable();
+
+    let count = delays.len();
+    if count == 0 {
+        return PropagationStats {
+            count,
+            mean: None,
+            median: None,
+            p95: None
+        }
+    }
+
+    let sum: u128 = delays.iter().map(|v| *v as u128).sum();
+    // Nearest-rank percentile
+    let percentile = |p: usize| delays[(count * p).div_ceil(100).max(1) - 1];
+
+    PropagationStats {
+        count,
+        mean: Some((sum / count as u128) as u64),
+        median: Some(percentile(50)),
+        p95: Some(percentile(95))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepted(first_seen: TimestampMillis, delay: u64) -> BlockPropagationEntry {
+        let mut entry = BlockPropagationEntry::new(first_seen);
+        entry.set_processing_at(first_seen);
+        entry.set_accepted_at(first_seen + delay);
+        entry
+    }
+
+    #[test]
+    fn test_propagation_stats() {
+        let mut entries: Vec<_> = (1..=100).map(|i| accepted(i * 1000, i)).collect();
+        // Not accepted and local blocks are ignored
+        entries.push(BlockPropagationEntry::new(500_000));
+        entries.push(BlockPropagationEntry::local(500_000));
+
+        let stats = compute_propagation_stats(entries.iter(), 100);
+        assert_eq!(stats.count, 100);
+        assert_eq!(stats.mean, Some(50));
+        assert_eq!(stats.median, Some(50));
+        assert_eq!(stats.p95, Some(95));
+
+        // Only the 10 most recent blocks
+        let stats = compute_propagation_stats(entries.iter(), 10);
+        assert_eq!(stats.count, 10);
+        assert_eq!(stats.median, Some(95));
+        assert_eq!(stats.p95, Some(100));
+    }
+
+    #[test]
+    fn test_propagation_stats_empty() {
+        let stats = compute_propagation_stats([BlockPropagationEntry::new(0)].iter(), 10);
+        assert_eq!(stats.count, 0);
+        assert!(stats.mean.is_none());
+        assert!(stats.p95.is_none());
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    net::SocketAddr,
+    sync::atomic::{AtomicUsize, Ordering}
+};
+use rand::Rng;
+use crate::core::config::{ProxyKind, ProxySelection};
+
+// Pool of proxies used for the outgoing connections
+// All proxies share the same protocol and credentials
+// A proxy is selected for each new outgoing connection
+pub struct ProxyPool {
+    kind: ProxyKind,
+    // Never empty
+    addresses: Vec<SocketAddr>,
+    auth: Option<(String, String)>,
+    selection: ProxySelection,
+    // Index of the next proxy in round-robin mode
+    next: AtomicUsize,
+}
+
+impl ProxyPool {
+    // Returns None if no address is provided
+    pub fn new(kind: ProxyKind, addresses: Vec<SocketAddr>, auth: Option<(String, String)>, selection: ProxySelection) -> Option<Self> {
+        if addresses.is_empty() {
+            return None
+        }
+
+        Some(Self {
+            kind,
+            addresses,
+            auth,
+            selection,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    // Get the proxy protocol
+    pub fn kind(&self) -> ProxyKind {
+        self.kind
+    }
+
+    // Get the credentials used for the authentication
+    pub fn auth(&self) -> Option<&(String, String)> {
+        self.auth.as_ref()
+    }
+
+    // Get all the proxies addresses
+    pub fn addresses(&self) -> &[SocketAddr] {
+        &self.addresses
+    }
+
+    // Select the proxy to use for a new connection
+    pub fn select(&self) -> &SocketAddr {
+        let index = match self.selection {
+            ProxySelection::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % self.addresses.len(),
+            ProxySelection::Random => rand::thread_rng().gen_range(0..self.addresses.len()),
+        };
+
+        &self.addresses[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_pool() {
+        assert!(ProxyPool::new(ProxyKind::Socks5, Vec::new(), None, ProxySelection::RoundRobin).is_none());
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let addresses: Vec<SocketAddr> = vec!["127.0.0.1:9050".parse().unwrap(), "127.0.0.1:9052".parse().unwrap()];
+        let pool = ProxyPool::new(ProxyKind::Socks5, addresses.clone(), None, ProxySelection::RoundRobin).unwrap();
+
+        assert_eq!(pool.select(), &addresses[0]);
+        assert_eq!(pool.select(), &addresses[1]);
+        assert_eq!(pool.select(), &addresses[0]);
+    }
+
+    #[test]
+    fn test_random() {
+        let addresses: Vec<SocketAddr> = vec!["127.0.0.1:9050".parse().unwrap(), "127.0.0.1:9052".parse().unwrap()];
+        let pool = ProxyPool::new(ProxyKind::Socks5, addresses.clone(), None, ProxySelection::Random).unwrap();
+
+        for _ in 0..10 {
+            assert!(addresses.contains(pool.select()));
+        }
+    }
+}
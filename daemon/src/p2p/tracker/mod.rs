@@ -7,6 +7,7 @@ use std::{
     time::{Duration, Instant}
 };
 use bytes::Bytes;
+use metrics::{counter, gauge};
 use log::{
     trace,
     debug,
@@ -36,7 +37,6 @@ use super::{
     error::P2pError,
     peer_list::Peer
 };
-use crate::config::PEER_TIMEOUT_REQUEST_OBJECT;
 use request::*;
 
 pub type SharedObjectTracker = Arc<ObjectTracker>;
@@ -82,24 +82,27 @@ pub struct ObjectTracker {
     group_id: AtomicU64,
     // Requests that should be ignored
     // They got canceled but already requested
-    cache: ExpirableCache
+    cache: ExpirableCache,
+    // How many requests timed out since the start
+    timed_out_requests: AtomicU64,
+    // Maximum time to wait for a response before dropping the request
+    request_timeout: Duration
 }
 
 // How many requests can be queued in the channel
 const REQUESTER_CHANNEL_BUFFER: usize = 8;
 
-// Duration constant for timeout instead of building it at each iteration
-const TIME_OUT: Duration = Duration::from_millis(PEER_TIMEOUT_REQUEST_OBJECT);
-
 impl ObjectTracker {
-    pub fn new(server_exit: broadcast::Receiver<()>) -> SharedObjectTracker {
+    pub fn new(server_exit: broadcast::Receiver<()>, request_timeout: Duration) -> SharedObjectTracker {
         let (request_sender, request_receiver) = mpsc::channel(REQUESTER_CHANNEL_BUFFER);
 
         let zelf: Arc<ObjectTracker> = Arc::new(Self {
             request_sender,
             queue: Mutex::new(Queue::new()),
             group_id: AtomicU64::new(0),
-            cache: ExpirableCache::new()
+            cache: ExpirableCache::new(),
+            timed_out_requests: AtomicU64::new(0),
+            request_timeout
         });
 
         // start the requester task loop which send requests to peers
@@ -134,6 +137,34 @@ impl ObjectTracker {
         self.group_id.fetch_add(1, Ordering::SeqCst)
     }
 
+    // Get the count of requests still waiting for a response
+    pub async fn get_pending_requests_count(&self) -> usize {
+        let queue = self.queue.lock().await;
+        queue.len()
+    }
+
+    // Get the age of the oldest request sent and still waiting for a response
+    pub async fn get_oldest_request_age(&self) -> Option<Duration> {
+        let queue = self.queue.lock().await;
+        Self::oldest_request_age(&queue)
+    }
+
+    // Get the count of requests that timed out since the start
+    pub fn get_timed_out_requests_count(&self) -> u64 {
+        self.timed_out_requests.load(Ordering::SeqCst)
+    }
+
+    // Get the configured timeout for a request
+    pub fn get_request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    fn oldest_request_age(queue: &Queue<Hash, Request>) -> Option<Duration> {
+        queue.values()
+            .filter_map(|request| request.get_requested().map(|requested_at| requested_at.elapsed()))
+            .max()
+    }
+
     // Task to clean the expired cache
     async fn task_clean_cache(&self, mut on_exit: broadcast::Receiver<()>) {
         let mut interval = interval(Duration::from_secs(5));
@@ -144,7 +175,7 @@ impl ObjectTracker {
                     break;
                 },
                 _ = interval.tick() => {
-                    self.cache.clean(TIME_OUT).await;
+                    self.cache.clean(self.request_timeout).await;
                 }
             }
         }
@@ -173,8 +204,10 @@ impl ObjectTracker {
                     while let Some(request) = queue.peek_mut() {
                         if let Some(requested_at) = request.get_requested() {
                             // check if the request is timed out
-                            if requested_at.elapsed() > TIME_OUT {
+                            if requested_at.elapsed() > self.request_timeout {
                                 warn!("Request timed out for object {}", request.get_hash());
+                                self.timed_out_requests.fetch_add(1, Ordering::SeqCst);
+                                counter!("terminos_p2p_object_tracker_timeouts_total").increment(1u64);
                                 let peer_id = request.get_peer().get_id();
                                 let group_id = request.get_group_id();
 
@@ -188,6 +221,10 @@ impl ObjectTracker {
                             break;
                         }
                     }
+
+                    gauge!("terminos_p2p_object_tracker_pending").set(queue.len() as f64);
+                    let oldest = Self::oldest_request_age(&queue).unwrap_or_default();
+                    gauge!("terminos_p2p_object_tracker_oldest_request_ms").set(oldest.as_millis() as f64);
                 }
             }
         }
@@ -283,7 +320,7 @@ impl ObjectTracker {
             }
 
             if let Some(requested_at) = request.get_requested() {
-                if requested_at.elapsed() > TIME_OUT {
+                if requested_at.elapsed() > self.request_timeout {
                     return true;
                 }
             }
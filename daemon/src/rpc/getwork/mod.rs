@@ -25,6 +25,7 @@ use terminos_common::{
     api::daemon::{
         GetBlockTemplateResult,
         GetMinerWorkResult,
+        NewBlockTemplateEvent,
         NotifyEvent,
         SubmitMinerWorkParams
     },
@@ -125,6 +126,23 @@ impl<S: Storage> GetWorkServer<S> {
         now - last_notify < self.notify_rate_limit_ms
     }
 
+    // Check if the last template sent is stale compared to the new one
+    // A template is stale if its height, tips or TXs set changed
+    async fn is_previous_template_stale(&self, header: &BlockHeader) -> bool {
+        let last_header_hash = self.last_header_hash.lock().await.clone();
+        let Some(last_header_hash) = last_header_hash else {
+            return true;
+        };
+
+        let mining_jobs = self.mining_jobs.lock().await;
+        match mining_jobs.peek(&last_header_hash) {
+            Some((previous, _)) => previous.get_height() != header.get_height()
+                || previous.get_tips() != header.get_tips()
+                || previous.get_txs_hashes() != header.get_txs_hashes(),
+            None => true
+        }
+    }
+
     // notify every miners connected to the getwork server
     // each miner have his own task so nobody wait on other
     pub async fn notify_new_job_rate_limited(&self) -> Result<(), InternalRpcError> {
@@ -286,14 +304,20 @@ impl<S: Storage> GetWorkServer<S> {
 
         if is_event_tracked {
             debug!("Notifying RPC clients for new block template");
+            let previous_stale = self.is_previous_template_stale(&header).await;
             let rpc = self.blockchain.get_rpc().read().await;
             if let Some(rpc) = rpc.as_ref() {
-                let value = GetBlockTemplateResult {
-                    template: header.to_hex(),
-                    algorithm,
-                    height,
-                    topoheight,
-                    difficulty
+                let value = NewBlockTemplateEvent {
+                    template: GetBlockTemplateResult {
+                        template: header.to_hex(),
+                        algorithm,
+                        height,
+                        topoheight,
+                        difficulty
+                    },
+                    tips: Cow::Borrowed(header.get_tips()),
+                    txs_count: header.get_txs_count(),
+                    previous_stale
                 };
 
                 rpc.notify_clients_with(&NotifyEvent::NewBlockTemplate, value).await;
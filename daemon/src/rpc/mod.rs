@@ -19,8 +19,12 @@ use actix_web::{
         Data,
         Payload
     },
-    dev::ServerHandle,
-    error::Error
+    dev::{ServerHandle, ServiceRequest},
+    error::{Error, ErrorBadGateway, ErrorBadRequest, ErrorUnauthorized}
+};
+use actix_web_httpauth::{
+    middleware::HttpAuthentication,
+    extractors::basic::BasicAuth
 };
 use anyhow::Context;
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
@@ -29,6 +33,7 @@ use terminos_common::{
     tokio::sync::Mutex,
     api::daemon::NotifyEvent,
     config,
+    crypto::{hash, Hash},
     rpc::{
         server::{
             json_rpc,
@@ -65,6 +70,35 @@ pub struct DaemonRpcServer<S: Storage> {
     getwork: Option<WebSocketServerShared<GetWorkServer<S>>>
 }
 
+// Admin RPC server, available only through HTTP with basic auth
+// Only the hashes of the credentials are kept
+pub struct AdminRpcServer<S: Storage> {
+    rpc_handler: RPCHandler<Arc<Blockchain<S>>>,
+    username: Hash,
+    password: Hash
+}
+
+impl<S: Storage> AdminRpcServer<S> {
+    fn authenticate(&self, credentials: &BasicAuth) -> Result<(), Error> {
+        let password = credentials.password().ok_or(ErrorBadRequest("Missing password"))?;
+        // Compare the hashes so the time taken doesn't depend on how many bytes are matching,
+        // and don't short-circuit to not leak which one is invalid
+        let valid_username = self.username == hash(credentials.user_id().as_bytes());
+        let valid_password = self.password == hash(password.as_bytes());
+        if !(valid_username & valid_password) {
+            return Err(ErrorUnauthorized("Username/password are invalid"))
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: Storage> RPCServerHandler<Arc<Blockchain<S>>> for AdminRpcServer<S> {
+    fn get_rpc_handler(&self) -> &RPCHandler<Arc<Blockchain<S>>> {
+        &self.rpc_handler
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ApiError {
     #[error("client not registered")]
@@ -74,7 +108,11 @@ pub enum ApiError {
     #[error("P2p engine is not running")]
     NoP2p,
     #[error("WebSocket server is not started")]
-    NoWebSocketServer
+    NoWebSocketServer,
+    #[error("Chain is being synced with a peer")]
+    ChainSyncing,
+    #[error("Node is not ready, it is not synced or connected to enough peers")]
+    NodeNotReady
 }
 
 impl<S: Storage> DaemonRpcServer<S> {
@@ -93,6 +131,21 @@ impl<S: Storage> DaemonRpcServer<S> {
             None
         };
 
+        // create the admin RPC handler only if credentials are configured
+        let admin = match (config.admin_username, config.admin_password) {
+            (Some(username), Some(password)) => {
+                info!("Admin RPC endpoint enabled");
+                let mut rpc_handler = RPCHandler::new(blockchain.clone());
+                rpc::register_admin_methods(&mut rpc_handler);
+                Some(Arc::new(AdminRpcServer {
+                    rpc_handler,
+                    username: hash(username.as_bytes()),
+                    password: hash(password.as_bytes())
+                }))
+            },
+            _ => None
+        };
+
         // create the RPC Handler which will register and contains all available methods
         let mut rpc_handler = RPCHandler::new(blockchain);
         rpc::register_methods(&mut rpc_handler, !config.getwork.disable);
@@ -138,6 +191,15 @@ impl<S: Storage> DaemonRpcServer<S> {
                 if let Some((route, _)) = &prometheus {
                     app = app.route(route, web::get().to(prometheus_metrics));
                 }
+
+                if let Some(admin) = &admin {
+                    app = app.app_data(web::Data::from(Arc::clone(admin)))
+                        .service(
+                            web::resource("/admin/json_rpc")
+                                .wrap(HttpAuthentication::basic(admin_auth::<S>))
+                                .route(web::post().to(json_rpc::<Arc<Blockchain<S>>, AdminRpcServer<S>>))
+                        );
+                }
                 app
             })
             .disable_signals()
@@ -221,6 +283,17 @@ async fn prometheus_metrics(handle: Data<Option<PrometheusHandle>>) -> Result<Ht
     })
 }
 
+async fn admin_auth<S: Storage>(request: ServiceRequest, credentials: BasicAuth) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    let data: Option<&Data<AdminRpcServer<S>>> = request.app_data();
+    match data {
+        Some(server) => match server.authenticate(&credentials) {
+            Ok(_) => Ok(request),
+            Err(e) => Err((e, request))
+        },
+        None => Err((ErrorBadGateway("Admin RPC Server was not found"), request))
+    }
+}
+
 async fn getwork_endpoint<S: Storage>(server: Data<DaemonRpcServer<S>>, request: HttpRequest, stream: Payload) -> Result<HttpResponse, Error> {
     match &server.getwork {
         Some(getwork) => getwork.handle_connection(request, stream).await,
@@ -15,24 +15,31 @@ use crate::{
         error::BlockchainError,
         hard_fork::{
             get_block_time_target_for_version,
+            get_hard_fork_at_height,
             get_pow_algorithm_for_version,
-            get_version_at_height
+            get_version_at_height,
+            is_version_matching_requirement
         },
         mempool::Mempool,
         storage::*,
     },
-    p2p::peer_list::Peer,
+    p2p::{
+        error::P2pError,
+        peer_list::{Peer, Subnet, SubnetError}
+    },
 };
 use super::{InternalRpcError, ApiError};
 use terminos_common::{
     api::{
         daemon::*,
+        DataElement,
         RPCContractOutput,
         RPCTransaction,
         SplitAddressParams,
         SplitAddressResult,
     },
     asset::RPCAssetData,
+    account::FreezeRecord,
     async_handler,
     block::{
         Block,
@@ -42,12 +49,13 @@ use terminos_common::{
     },
     config::{
         MAXIMUM_SUPPLY,
+        MAX_BLOCK_SIZE,
         MAX_TRANSACTION_SIZE,
         VERSION,
         TERMINOS_ASSET
     },
     context::Context,
-    crypto::{Address, AddressType, Hash},
+    crypto::{Address, AddressType, Hash, Hashable},
     difficulty::{
         CumulativeDifficulty,
         Difficulty
@@ -61,6 +69,7 @@ use terminos_common::{
     serializer::Serializer,
     time::TimestampSeconds,
     transaction::{
+        EnergyPayload,
         Transaction,
         TransactionType
     },
@@ -68,8 +77,9 @@ use terminos_common::{
 };
 use anyhow::Context as AnyContext;
 use human_bytes::human_bytes;
+use indexmap::IndexMap;
 use serde_json::{json, Value};
-use std::{borrow::Cow, collections::HashMap, sync::Arc};
+use std::{borrow::Cow, collections::{HashMap, HashSet}, sync::Arc};
 use log::{info, debug, trace};
 
 // Get the block type using the block hash and the blockchain current state
@@ -291,6 +301,13 @@ pub async fn get_peer_entry(peer: &Peer) -> PeerEntry {
         connected_on: peer.get_connection().connected_on(),
         bytes_recv: peer.get_connection().bytes_in(),
         bytes_sent: peer.get_connection().bytes_out(),
+        send_rate: peer.get_connection().send_rate(),
+        recv_rate: peer.get_connection().recv_rate(),
+        latency_ms: peer.get_latency(),
+        mempool_size: peer.get_mempool_size(),
+        pending_send: peer.get_pending_send(),
+        connection_count: peer.get_connection_count(),
+        failure_count: peer.get_failure_count(),
     }
 }
 
@@ -303,6 +320,7 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
     handler.register_method("get_pruned_topoheight", async_handler!(get_pruned_topoheight::<S>));
     handler.register_method("get_info", async_handler!(get_info::<S>));
     handler.register_method("get_difficulty", async_handler!(get_difficulty::<S>));
+    handler.register_method("get_difficulty_history", async_handler!(get_difficulty_history::<S>));
     handler.register_method("get_tips", async_handler!(get_tips::<S>));
     handler.register_method("get_dev_fee_thresholds", async_handler!(get_dev_fee_thresholds::<S>));
     handler.register_method("get_size_on_disk", async_handler!(get_size_on_disk::<S>));
@@ -312,6 +330,7 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
     handler.register_method("get_stable_height", async_handler!(get_stable_height::<S>));
     handler.register_method("get_stable_topoheight", async_handler!(get_stable_topoheight::<S>));
     handler.register_method("get_hard_forks", async_handler!(get_hard_forks::<S>));
+    handler.register_method("get_hard_forks_schedule", async_handler!(get_hard_forks_schedule::<S>));
 
     handler.register_method("get_block_at_topoheight", async_handler!(get_block_at_topoheight::<S>));
     handler.register_method("get_blocks_at_height", async_handler!(get_blocks_at_height::<S>));
@@ -324,12 +343,14 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
     handler.register_method("get_balance_at_topoheight", async_handler!(get_balance_at_topoheight::<S>));
 
     handler.register_method("get_nonce", async_handler!(get_nonce::<S>));
+    handler.register_method("get_pending_nonce", async_handler!(get_pending_nonce::<S>));
     handler.register_method("has_nonce", async_handler!(has_nonce::<S>));
     handler.register_method("get_nonce_at_topoheight", async_handler!(get_nonce_at_topoheight::<S>));
 
     // Assets
     handler.register_method("get_asset", async_handler!(get_asset::<S>));
     handler.register_method("get_asset_supply", async_handler!(get_asset_supply::<S>));
+    handler.register_method("get_asset_supply_history", async_handler!(get_asset_supply_history::<S>));
     handler.register_method("get_assets", async_handler!(get_assets::<S>));
 
     handler.register_method("count_assets", async_handler!(count_assets::<S>));
@@ -338,6 +359,8 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
     handler.register_method("count_contracts", async_handler!(count_contracts::<S>));
 
     handler.register_method("submit_transaction", async_handler!(submit_transaction::<S>));
+    handler.register_method("verify_transaction", async_handler!(verify_transaction::<S>));
+    handler.register_method("decode_transaction", async_handler!(decode_transaction::<S>));
     handler.register_method("get_transaction_executor", async_handler!(get_transaction_executor::<S>));
     handler.register_method("get_transaction", async_handler!(get_transaction::<S>));
     handler.register_method("get_transactions", async_handler!(get_transactions::<S>));
@@ -349,14 +372,19 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
 
     handler.register_method("get_mempool", async_handler!(get_mempool::<S>));
     handler.register_method("get_mempool_summary", async_handler!(get_mempool_summary::<S>));
+    handler.register_method("get_mempool_stats", async_handler!(get_mempool_stats::<S>));
     handler.register_method("get_mempool_cache", async_handler!(get_mempool_cache::<S>));
+    handler.register_method("get_mempool_by_source", async_handler!(get_mempool_by_source::<S>));
     handler.register_method("get_estimated_fee_rates", async_handler!(get_estimated_fee_rates::<S>));
+    handler.register_method("estimate_fee_rates", async_handler!(estimate_fee_rates::<S>));
 
     handler.register_method("get_dag_order", async_handler!(get_dag_order::<S>));
     handler.register_method("get_blocks_range_by_topoheight", async_handler!(get_blocks_range_by_topoheight::<S>));
     handler.register_method("get_blocks_range_by_height", async_handler!(get_blocks_range_by_height::<S>));
+    handler.register_method("get_blocks_at_topoheight_range", async_handler!(get_blocks_at_topoheight_range::<S>));
 
     handler.register_method("get_account_history", async_handler!(get_account_history::<S>));
+    handler.register_method("get_account_history_page", async_handler!(get_account_history_page::<S>));
     handler.register_method("get_account_assets", async_handler!(get_account_assets::<S>));
     handler.register_method("get_accounts", async_handler!(get_accounts::<S>));
     handler.register_method("is_account_registered", async_handler!(is_account_registered::<S>));
@@ -377,18 +405,30 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
 
     // Contracts
     handler.register_method("get_contract_outputs", async_handler!(get_contract_outputs::<S>));
+    handler.register_method("get_block_execution", async_handler!(get_block_execution::<S>));
+    handler.register_method("get_contract_outputs_by_contract", async_handler!(get_contract_outputs_by_contract::<S>));
     handler.register_method("get_contract_module", async_handler!(get_contract_module::<S>));
     handler.register_method("get_contract_data", async_handler!(get_contract_data::<S>));
     handler.register_method("get_contract_data_at_topoheight", async_handler!(get_contract_data_at_topoheight::<S>));
+    handler.register_method("get_contract_data_range", async_handler!(get_contract_data_range::<S>));
     handler.register_method("get_contract_balance", async_handler!(get_contract_balance::<S>));
     handler.register_method("get_contract_balance_at_topoheight", async_handler!(get_contract_balance_at_topoheight::<S>));
+    handler.register_method("get_contract_balances_for_assets", async_handler!(get_contract_balances_for_assets::<S>));
     handler.register_method("get_contract_assets", async_handler!(get_contract_assets::<S>));
 
     // P2p
     handler.register_method("get_p2p_block_propagation", async_handler!(get_p2p_block_propagation::<S>));
+    handler.register_method("get_propagation_stats", async_handler!(get_propagation_stats::<S>));
+    handler.register_method("p2p_probe", async_handler!(p2p_probe::<S>));
+    handler.register_method("p2p_resync_mempool", async_handler!(p2p_resync_mempool::<S>));
+    handler.register_method("get_sync_status", async_handler!(get_sync_status::<S>));
+    handler.register_method("get_network_time_offset", async_handler!(get_network_time_offset::<S>));
+    handler.register_method("get_object_tracker_status", async_handler!(get_object_tracker_status::<S>));
+    handler.register_method("get_p2p_fail_count_whitelist", async_handler!(get_p2p_fail_count_whitelist::<S>));
 
     // Energy management
     handler.register_method("get_energy", async_handler!(get_energy::<S>));
+    handler.register_method("get_freeze_history", async_handler!(get_freeze_history::<S>));
 
     if allow_mining_methods {
         handler.register_method("get_block_template", async_handler!(get_block_template::<S>));
@@ -397,6 +437,19 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
     }
 }
 
+// Methods only available through the authenticated admin endpoint
+pub fn register_admin_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>) {
+    info!("Registering admin RPC methods...");
+    handler.register_method("add_checkpoint", async_handler!(add_checkpoint::<S>));
+    handler.register_method("p2p_sync_from_peer", async_handler!(p2p_sync_from_peer::<S>));
+    handler.register_method("rewind_chain", async_handler!(rewind_chain::<S>));
+    handler.register_method("storage_compact", async_handler!(storage_compact::<S>));
+    handler.register_method("create_snapshot", async_handler!(create_snapshot::<S>));
+    handler.register_method("p2p_ban_subnet", async_handler!(p2p_ban_subnet::<S>));
+    handler.register_method("p2p_disconnect_peer", async_handler!(p2p_disconnect_peer::<S>));
+    handler.register_method("p2p_set_tx_propagation", async_handler!(p2p_set_tx_propagation::<S>));
+}
+
 async fn version<S: Storage>(_: &Context, body: Value) -> Result<Value, InternalRpcError> {
     require_no_params(body)?;
     Ok(json!(VERSION))
@@ -444,6 +497,33 @@ async fn get_hard_forks<S: Storage>(context: &Context, body: Value) -> Result<Va
     Ok(json!(hard_forks))
 }
 
+// Same as get_hard_forks but mark the active one and the next scheduled height
+async fn get_hard_forks_schedule<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    require_no_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let network = blockchain.get_network();
+    let height = blockchain.get_height();
+
+    let active_height = get_hard_fork_at_height(network, height).map(|hard_fork| hard_fork.height);
+    let hard_forks = get_configured_hard_forks(network);
+    let next_height = hard_forks.iter()
+        .map(|hard_fork| hard_fork.height)
+        .find(|v| *v > height);
+
+    let hard_forks = hard_forks.iter()
+        .map(|hard_fork| HardForkEntry {
+            hard_fork: Cow::Borrowed(hard_fork),
+            active: Some(hard_fork.height) == active_height
+        })
+        .collect();
+
+    Ok(json!(GetHardForksScheduleResult {
+        hard_forks,
+        height,
+        next_height
+    }))
+}
+
 
 async fn get_block_at_topoheight<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetBlockAtTopoHeightParams = parse_params(body)?;
@@ -538,6 +618,7 @@ async fn submit_block<S: Storage>(context: &Context, body: Value) -> Result<Valu
 async fn get_balance<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetBalanceParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    require_node_ready(blockchain).await?;
     if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
         return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
     }
@@ -553,17 +634,30 @@ async fn get_balance<S: Storage>(context: &Context, body: Value) -> Result<Value
 async fn get_stable_balance<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetBalanceParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    require_node_ready(blockchain).await?;
     if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
         return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
     }
 
     let top_topoheight = blockchain.get_topo_height();
-    let stable_topoheight = blockchain.get_stable_topoheight();
     let storage = blockchain.get_storage().read().await;
 
+    // A custom confirmation depth can only be deeper than the stable one
+    let stable_topoheight = blockchain.get_stable_topoheight();
+    let confirmed_topoheight = match params.confirmations {
+        Some(confirmations) => {
+            let pruned_topoheight = storage.get_pruned_topoheight().await?.unwrap_or(0);
+            match top_topoheight.checked_sub(confirmations) {
+                Some(topoheight) if topoheight >= pruned_topoheight => topoheight.min(stable_topoheight),
+                _ => return Err(InternalRpcError::InvalidJSONRequest).context(format!("Requested {} confirmations but only {} are available", confirmations, top_topoheight - pruned_topoheight))?
+            }
+        },
+        None => stable_topoheight
+    };
+
     let mut stable_version = None;
     if let Some((output_topoheight, version)) = storage.get_output_balance_at_maximum_topoheight(params.address.get_public_key(), &params.asset, top_topoheight).await? {
-        if output_topoheight >= stable_topoheight {
+        if output_topoheight >= confirmed_topoheight {
             stable_version = Some((output_topoheight, version));
         }
     }
@@ -571,20 +665,22 @@ async fn get_stable_balance<S: Storage>(context: &Context, body: Value) -> Resul
     let (stable_topoheight, version) = if let Some((topoheight, version)) = stable_version {
         (topoheight, version)
     } else {
-        storage.get_balance_at_maximum_topoheight(params.address.get_public_key(), &params.asset, stable_topoheight).await?
+        storage.get_balance_at_maximum_topoheight(params.address.get_public_key(), &params.asset, confirmed_topoheight).await?
             .ok_or(InternalRpcError::InvalidRequestStr("no stable balance found for this account"))?
     };
 
     Ok(json!(GetStableBalanceResult {
         version,
         stable_topoheight,
-        stable_block_hash: storage.get_hash_at_topo_height(stable_topoheight).await.context("Error while retrieving hash at topo height")?
+        stable_block_hash: storage.get_hash_at_topo_height(stable_topoheight).await.context("Error while retrieving hash at topo height")?,
+        confirmed_topoheight
     }))
 }
 
 async fn has_balance<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: HasBalanceParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    require_node_ready(blockchain).await?;
     if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
         return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
     }
@@ -652,6 +748,7 @@ async fn get_info<S: Storage>(context: &Context, body: Value) -> Result<Value, I
 async fn get_balance_at_topoheight<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetBalanceAtTopoHeightParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    require_node_ready(blockchain).await?;
     let topoheight = blockchain.get_topo_height();
     if params.topoheight > topoheight {
         return Err(InternalRpcError::UnexpectedParams).context("Topoheight cannot be greater than current chain topoheight")?
@@ -669,6 +766,7 @@ async fn get_balance_at_topoheight<S: Storage>(context: &Context, body: Value) -
 async fn has_nonce<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: HasNonceParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    require_node_ready(blockchain).await?;
     if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
         return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
     }
@@ -686,6 +784,7 @@ async fn has_nonce<S: Storage>(context: &Context, body: Value) -> Result<Value,
 async fn get_nonce<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetNonceParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    require_node_ready(blockchain).await?;
     if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
         return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
     }
@@ -697,9 +796,46 @@ async fn get_nonce<S: Storage>(context: &Context, body: Value) -> Result<Value,
     Ok(json!(GetNonceResult { topoheight, version }))
 }
 
+// Get the on-chain nonce of an account and the next one to use including its TXs in mempool
+async fn get_pending_nonce<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetNonceParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    require_node_ready(blockchain).await?;
+    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    let key = params.address.get_public_key();
+    let (topoheight, version) = {
+        let storage = blockchain.get_storage().read().await;
+        storage.get_last_nonce(key).await
+            .context("Error while retrieving nonce for account")?
+    };
+
+    let mempool_nonce = {
+        let mempool = blockchain.get_mempool().read().await;
+        mempool.get_cache_for(key).map(|cache| cache.get_max())
+    };
+
+    // The on-chain nonce is the one expected by the next TX
+    let nonce = version.get_nonce();
+    let next_nonce = match mempool_nonce {
+        Some(max) => (max + 1).max(nonce),
+        None => nonce
+    };
+
+    Ok(json!(GetPendingNonceResult {
+        topoheight,
+        nonce,
+        mempool_nonce,
+        next_nonce
+    }))
+}
+
 async fn get_nonce_at_topoheight<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetNonceAtTopoHeightParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    require_node_ready(blockchain).await?;
     let topoheight = blockchain.get_topo_height();
     if params.topoheight > topoheight {
         return Err(InternalRpcError::UnexpectedParams).context("Topoheight cannot be greater than current chain topoheight")?
@@ -740,6 +876,42 @@ async fn get_asset_supply<S: Storage>(context: &Context, body: Value) -> Result<
     }))
 }
 
+const MAX_SUPPLY_HISTORY_POINTS: u64 = 1000;
+
+async fn get_asset_supply_history<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetAssetSupplyHistoryParams = parse_params(body)?;
+    let step = params.step.unwrap_or(1);
+    if step == 0 {
+        return Err(InternalRpcError::InvalidJSONRequest).context("Step must be above 0")?
+    }
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let storage = blockchain.get_storage().read().await;
+
+    let current_topoheight = blockchain.get_topo_height();
+    let pruned_topoheight = storage.get_pruned_topoheight().await
+        .context("Error while retrieving pruned topoheight")?
+        .unwrap_or(0);
+
+    let start_topoheight = params.start_topoheight.unwrap_or(pruned_topoheight);
+    let end_topoheight = params.end_topoheight.unwrap_or(current_topoheight);
+    if start_topoheight < pruned_topoheight || end_topoheight > current_topoheight || start_topoheight > end_topoheight {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Invalid topoheight range, must be between {} and {}", pruned_topoheight, current_topoheight))?
+    }
+
+    let count = (end_topoheight - start_topoheight) / step + 1;
+    if count > MAX_SUPPLY_HISTORY_POINTS {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Too many points requested, max is {}", MAX_SUPPLY_HISTORY_POINTS))?
+    }
+
+    let points: Vec<AssetSupplyPoint> = storage.get_asset_supply_history(&params.asset, start_topoheight, end_topoheight, step).await
+        .context("Error while retrieving asset supply history")?
+        .into_iter()
+        .map(|(topoheight, supply)| AssetSupplyPoint { topoheight, supply })
+        .collect();
+
+    Ok(json!(points))
+}
 
 const MAX_ASSETS: usize = 100;
 
@@ -811,6 +983,11 @@ async fn count_contracts<S: Storage>(context: &Context, body: Value) -> Result<V
 
 async fn submit_transaction<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: SubmitTransactionParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    if !blockchain.is_accepting_transactions() {
+        return Err(BlockchainError::MempoolClosed.into())
+    }
+
     // x2 because of hex encoding
     if params.data.len() > MAX_TRANSACTION_SIZE * 2 {
         return Err(InternalRpcError::InvalidJSONRequest).context(format!("Transaction size cannot be greater than {}", human_bytes(MAX_TRANSACTION_SIZE as f64)))?
@@ -819,12 +996,54 @@ async fn submit_transaction<S: Storage>(context: &Context, body: Value) -> Resul
     let transaction = Transaction::from_hex(&params.data)
         .map_err(|err| InternalRpcError::InvalidParamsAny(err.into()))?;
 
-    let blockchain: &Arc<Blockchain<S>> = context.get()?;
     blockchain.add_tx_to_mempool(transaction, true).await?;
 
     Ok(json!(true))
 }
 
+// Verify a transaction against the current chain state without adding it to the mempool
+async fn verify_transaction<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SubmitTransactionParams = parse_params(body)?;
+    // x2 because of hex encoding
+    if params.data.len() > MAX_TRANSACTION_SIZE * 2 {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Transaction size cannot be greater than {}", human_bytes(MAX_TRANSACTION_SIZE as f64)))?
+    }
+
+    let transaction = Transaction::from_hex(&params.data)
+        .map_err(|err| InternalRpcError::InvalidParamsAny(err.into()))?;
+    let hash = transaction.hash();
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let (error_code, error) = match blockchain.verify_tx(Arc::new(transaction), &hash).await {
+        Ok(()) => (None, None),
+        Err(e) => (Some(200 + e.id() as i16), Some(e.to_string()))
+    };
+
+    Ok(json!(VerifyTransactionResult {
+        hash: Cow::Owned(hash),
+        valid: error.is_none(),
+        error_code,
+        error
+    }))
+}
+
+// Decode a transaction in hex format into its RPC representation
+// It doesn't touch the mempool or the chain state
+async fn decode_transaction<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SubmitTransactionParams = parse_params(body)?;
+    // x2 because of hex encoding
+    if params.data.len() > MAX_TRANSACTION_SIZE * 2 {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Transaction size cannot be greater than {}", human_bytes(MAX_TRANSACTION_SIZE as f64)))?
+    }
+
+    let transaction = Transaction::from_hex(&params.data)
+        .map_err(|err| InternalRpcError::InvalidParamsAny(err.into()))?;
+    let hash = transaction.hash();
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    Ok(json!(RPCTransaction::from_tx(&transaction, &hash, blockchain.get_network().is_mainnet())))
+}
+
 async fn get_transaction<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetTransactionParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
@@ -866,6 +1085,9 @@ async fn p2p_status<S: Storage>(context: &Context, body: Value) -> Result<Value,
             let max_peers = p2p.get_max_peers();
             let our_topoheight = blockchain.get_topo_height();
             let peer_count = p2p.get_peer_count().await;
+            let inbound_peers = p2p.get_inbound_peers_count().await;
+            let outbound_peers = p2p.get_outbound_peers_count();
+            let max_inbound_peers = p2p.get_max_inbound_peers();
 
             Ok(json!(P2pStatusResult {
                 peer_count,
@@ -874,7 +1096,10 @@ async fn p2p_status<S: Storage>(context: &Context, body: Value) -> Result<Value,
                 our_topoheight,
                 best_topoheight,
                 median_topoheight,
-                max_peers
+                max_peers,
+                inbound_peers,
+                outbound_peers,
+                max_inbound_peers
             }))
         },
         None => Err(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))
@@ -882,7 +1107,12 @@ async fn p2p_status<S: Storage>(context: &Context, body: Value) -> Result<Value,
 }
 
 async fn get_peers<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
-    require_no_params(body)?;
+    let params: GetPeersParams = parse_params(body)?;
+    if let Some(req) = params.version_requirement.as_deref() {
+        semver::VersionReq::parse(req)
+            .map_err(|e| InternalRpcError::InvalidParamsAny(e.into()))?;
+    }
+
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
     let p2p = { blockchain.get_p2p().read().await.clone() };
     match p2p.as_ref() {
@@ -891,16 +1121,26 @@ async fn get_peers<S: Storage>(context: &Context, body: Value) -> Result<Value,
             let peers_availables = peer_list.get_cloned_peers().await;
 
             let mut peers = Vec::new();
-            for p in peers_availables.iter().filter(|p| p.sharable()) {
+            let mut filtered_peers = 0;
+            for p in peers_availables.iter().filter(|p| p.sharable() && !p.is_observer()) {
+                if let Some(req) = params.version_requirement.as_deref() {
+                    // Peers with an invalid version are considered as not matching
+                    if !is_version_matching_requirement(p.get_version(), req).unwrap_or(false) {
+                        filtered_peers += 1;
+                        continue;
+                    }
+                }
+
                 peers.push(get_peer_entry(p).await);
             }
 
             let total_peers = peers_availables.len();
-            let sharable_peers = peers.len();
+            let sharable_peers = peers.len() + filtered_peers;
             Ok(json!(GetPeersResponse {
                 peers,
                 total_peers,
                 hidden_peers: total_peers - sharable_peers,
+                filtered_peers,
             }))
         },
         None => Err(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))
@@ -934,6 +1174,46 @@ async fn get_mempool<S: Storage>(context: &Context, body: Value) -> Result<Value
 
 pub const MAX_SUMMARY: usize = 1024;
 
+// Breakdown of the mempool TXs per type and per asset for transfers
+async fn get_mempool_stats<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    require_no_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let mempool = blockchain.get_mempool().read().await;
+    let txs = mempool.get_txs();
+
+    let mut total_size = 0;
+    let mut by_type = MempoolTxTypeStats::default();
+    let mut transfers_by_asset = HashMap::new();
+    for sorted_tx in txs.values() {
+        total_size += sorted_tx.get_size();
+        match sorted_tx.get_tx().get_data() {
+            TransactionType::Transfers(transfers) => {
+                by_type.transfers += 1;
+                let assets: HashSet<&Hash> = transfers.iter()
+                    .map(|transfer| transfer.get_asset())
+                    .collect();
+
+                for asset in assets {
+                    *transfers_by_asset.entry(asset.clone()).or_insert(0) += 1;
+                }
+            },
+            TransactionType::Burn(_) => by_type.burns += 1,
+            TransactionType::MultiSig(_) => by_type.multisig += 1,
+            TransactionType::InvokeContract(_) => by_type.invoke_contracts += 1,
+            TransactionType::DeployContract(_) => by_type.deploy_contracts += 1,
+            TransactionType::Energy(_) => by_type.energy += 1,
+        }
+    }
+
+    Ok(json!(GetMempoolStatsResult {
+        total: txs.len(),
+        total_size,
+        by_type,
+        transfers_by_asset
+    }))
+}
+
 async fn get_mempool_summary<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetMempoolParams = parse_params(body)?;
 
@@ -977,6 +1257,43 @@ async fn get_estimated_fee_rates<S: Storage>(context: &Context, body: Value) ->
     Ok(json!(estimated))
 }
 
+const DEFAULT_FEE_RATES_BLOCKS: u64 = 10;
+const MAX_FEE_RATES_BLOCKS: u64 = 100;
+
+// Estimate the fee rates from the distribution of the fees
+// in the mempool and in the last blocks ordered
+async fn estimate_fee_rates<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: EstimateFeeRatesParams = parse_params(body)?;
+    let blocks = params.blocks.unwrap_or(DEFAULT_FEE_RATES_BLOCKS);
+    if blocks > MAX_FEE_RATES_BLOCKS {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Too many requested blocks: {}, maximum is {}", blocks, MAX_FEE_RATES_BLOCKS))?
+    }
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let storage = blockchain.get_storage().read().await;
+
+    let mut fee_rates = Vec::new();
+    {
+        let mempool = blockchain.get_mempool().read().await;
+        fee_rates.extend(mempool.get_txs().values().map(|tx| Mempool::compute_fee_rate_per_kb(tx.get_fee(), tx.get_size())));
+    }
+
+    // Don't go below the pruned topoheight
+    let topoheight = blockchain.get_topo_height();
+    let pruned_topoheight = storage.get_pruned_topoheight().await
+        .context("Error while retrieving pruned topoheight")?
+        .unwrap_or(0);
+    let start = topoheight.saturating_sub(blocks.saturating_sub(1)).max(pruned_topoheight);
+
+    for topo in (start..=topoheight).take(blocks as usize) {
+        let hash = storage.get_hash_at_topo_height(topo).await.context("Error while retrieving hash at topo height")?;
+        let block = storage.get_block_by_hash(&hash).await.context("Error while retrieving block")?;
+        fee_rates.extend(block.get_transactions().iter().map(|tx| Mempool::compute_fee_rate_per_kb(tx.get_fee(), tx.size())));
+    }
+
+    Ok(json!(Mempool::estimate_fee_rates_from_distribution(fee_rates)))
+}
+
 async fn get_blocks_at_height<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetBlocksAtHeightParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
@@ -1086,6 +1403,49 @@ async fn get_blocks_range_by_height<S: Storage>(context: &Context, body: Value)
     Ok(json!(blocks))
 }
 
+const MAX_BLOCKS_BATCH: u64 = 1000;
+// Maximum size in bytes of the blocks returned in a batch
+const MAX_BLOCKS_BATCH_SIZE: usize = MAX_BLOCK_SIZE * 4;
+
+// get blocks in order between start and end topoheights (both included) for bulk export
+// response is stopped once the blocks size reach the limit,
+// `next_topoheight` is set so the caller can continue from there
+async fn get_blocks_at_topoheight_range<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetBlocksAtTopoHeightRangeParams = parse_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let current_topoheight = blockchain.get_topo_height();
+    let (start_topoheight, end_topoheight) = get_range(Some(params.start), Some(params.end), MAX_BLOCKS_BATCH, current_topoheight)?;
+
+    let storage = blockchain.get_storage().read().await;
+    let mut blocks = Vec::new();
+    let mut total_size = 0;
+    let mut next_topoheight = None;
+    for i in start_topoheight..=end_topoheight {
+        // Stop once the size limit is reached, at least one block is always returned
+        if total_size >= MAX_BLOCKS_BATCH_SIZE {
+            next_topoheight = Some(i);
+            break;
+        }
+
+        let hash = storage.get_hash_at_topo_height(i).await.context("Error while retrieving hash at topo height")?;
+        let response = get_block_response_for_hash(&blockchain, &storage, &hash, params.include_txs).await?;
+
+        // Only the blocks with their TXs are heavy
+        if params.include_txs {
+            total_size += response.get("total_size_in_bytes")
+                .and_then(Value::as_u64)
+                .unwrap_or(0) as usize;
+        }
+        blocks.push(response);
+    }
+
+    Ok(json!(GetBlocksAtTopoHeightRangeResult {
+        blocks,
+        next_topoheight
+    }))
+}
+
 const MAX_TXS: usize = 20;
 // get up to 20 transactions at once
 // if a tx hash is not present, we keep the order and put json "null" value
@@ -1147,10 +1507,41 @@ async fn get_transactions_summary<S: Storage>(context: &Context, body: Value) ->
 }
 
 const MAX_HISTORY: usize = 20;
+// Maximum entries that can be requested with a history limit
+const MAX_HISTORY_ENTRIES: usize = 100;
+// Maximum topoheight versions walked to fill a history limit
+// If reached, the page is returned with a cursor to continue from
+const MAX_HISTORY_SCAN: usize = 1000;
+
 // retrieve all history changes for an account on an asset
 async fn get_account_history<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetAccountHistoryParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let storage = blockchain.get_storage().read().await;
+    let (history, _) = build_account_history(blockchain, &*storage, &params).await?;
+
+    Ok(json!(history))
+}
+
+// Same as get_account_history but also returns the cursor for the next page
+async fn get_account_history_page<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetAccountHistoryParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let storage = blockchain.get_storage().read().await;
+    let (entries, next_before_topoheight) = build_account_history(blockchain, &*storage, &params).await?;
+
+    Ok(json!(GetAccountHistoryPageResult {
+        entries,
+        next_before_topoheight
+    }))
+}
+
+// Walk the account history in descending topoheight order
+// Without a limit, at most MAX_HISTORY topoheight versions are walked
+// With a limit, versions are walked until the limit is reached or MAX_HISTORY_SCAN versions were walked
+// Entries of a same topoheight are never split across pages
+// Returns the entries and the `before_topoheight` to use for the next page if any
+async fn build_account_history<S: Storage>(blockchain: &Blockchain<S>, storage: &S, params: &GetAccountHistoryParams) -> Result<(Vec<AccountHistoryEntry>, Option<TopoHeight>), InternalRpcError> {
     if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
         return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
     }
@@ -1159,11 +1550,24 @@ async fn get_account_history<S: Storage>(context: &Context, body: Value) -> Resu
         return Err(InternalRpcError::InvalidParams("No history type was selected"));
     }
 
+    let limit = match params.limit {
+        Some(limit) if limit == 0 || limit > MAX_HISTORY_ENTRIES => {
+            return Err(InternalRpcError::InvalidJSONRequest).context(format!("Invalid history limit {}, maximum is {}", limit, MAX_HISTORY_ENTRIES))?
+        },
+        limit => limit
+    };
+
+    // The cursor is exclusive and acts as an upper bound
+    let maximum_topoheight = match params.before_topoheight {
+        Some(0) => return Ok((Vec::new(), None)),
+        Some(before) => Some(params.maximum_topoheight.map_or(before - 1, |max| max.min(before - 1))),
+        None => params.maximum_topoheight
+    };
+
     let key = params.address.get_public_key();
     let minimum_topoheight = params.minimum_topoheight.unwrap_or(0);
-    let storage = blockchain.get_storage().read().await;
     let pruned_topoheight = storage.get_pruned_topoheight().await.context("Error while retrieving pruned topoheight")?.unwrap_or(0);
-    let mut version: Option<(u64, Option<u64>, _)> = if let Some(topo) = params.maximum_topoheight {
+    let mut version: Option<(u64, Option<u64>, _)> = if let Some(topo) = maximum_topoheight {
         if topo < pruned_topoheight {
             return Err(InternalRpcError::InvalidParams("Maximum topoheight is lower than pruned topoheight"));
         }
@@ -1201,6 +1605,7 @@ async fn get_account_history<S: Storage>(context: &Context, body: Value) -> Resu
 
     let mut history_count = 0;
     let mut history = Vec::new();
+    let mut next_before_topoheight = None;
 
     let is_dev_address = *key == *DEV_PUBLIC_KEY;
     while let Some((topo, prev_nonce, versioned_balance)) = version.take() {
@@ -1333,7 +1738,8 @@ async fn get_account_history<S: Storage>(context: &Context, body: Value) -> Resu
                     }
                 },
                 TransactionType::Energy(payload) => {
-                    if is_sender {
+                    // Energy is only obtained by freezing TOS
+                    if is_sender && params.asset == TERMINOS_ASSET {
                         match payload {
                             terminos_common::transaction::EnergyPayload::FreezeTos { amount, duration } => {
                                 history.push(AccountHistoryEntry {
@@ -1361,9 +1767,10 @@ async fn get_account_history<S: Storage>(context: &Context, body: Value) -> Resu
         }
 
         history_count += 1;
-        if history_count >= MAX_HISTORY {
-            break;
-        }
+        let is_page_full = match limit {
+            Some(limit) => history.len() >= limit || history_count >= MAX_HISTORY_SCAN,
+            None => history_count >= MAX_HISTORY
+        };
 
         // if incoming flows aren't accepted
         // use nonce versions to determine topoheight
@@ -1377,9 +1784,17 @@ async fn get_account_history<S: Storage>(context: &Context, body: Value) -> Resu
 
             version = Some((previous, None, storage.get_balance_at_exact_topoheight(key, &params.asset, previous).await.context(format!("Error while retrieving previous balance at topo height {previous}"))?));
         }
+
+        if is_page_full {
+            // Only provide a cursor if there is still something to walk
+            if version.as_ref().is_some_and(|(previous, _, _)| *previous >= minimum_topoheight) {
+                next_before_topoheight = Some(topo);
+            }
+            break;
+        }
     }
 
-    Ok(json!(history))
+    Ok((history, next_before_topoheight))
 }
 
 async fn get_account_assets<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
@@ -1401,12 +1816,29 @@ async fn get_account_assets<S: Storage>(context: &Context, body: Value) -> Resul
 
     let key = params.address.get_public_key();
     let storage = blockchain.get_storage().read().await;
-    let assets: Vec<_> = storage.get_assets_for(key).await?
+    let assets: Vec<Hash> = storage.get_assets_for(key).await?
         .skip(skip)
         .take(maximum)
         .collect::<Result<_, BlockchainError>>()
         .context("Error while retrieving assets for account")?;
-    Ok(json!(assets))
+
+    if !params.include_balances {
+        return Ok(json!(assets))
+    }
+
+    let mut balances = Vec::with_capacity(assets.len());
+    for asset in assets {
+        let (topoheight, version) = storage.get_last_balance(key, &asset).await
+            .context("Error while retrieving last balance")?;
+
+        balances.push(AccountAssetBalance {
+            asset,
+            version,
+            topoheight
+        });
+    }
+
+    Ok(json!(balances))
 }
 
 const MAX_ACCOUNTS: usize = 100;
@@ -1513,6 +1945,26 @@ async fn get_size_on_disk<S: Storage>(context: &Context, body: Value) -> Result<
     }))
 }
 
+async fn storage_compact<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    require_no_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let elapsed = blockchain.compact_storage().await?;
+
+    Ok(json!(StorageCompactResult {
+        elapsed_ms: elapsed.as_millis() as u64
+    }))
+}
+
+async fn create_snapshot<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: CreateSnapshotParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let topoheight = blockchain.create_snapshot(&params.path).await?;
+
+    Ok(json!(CreateSnapshotResult {
+        topoheight
+    }))
+}
+
 // Retrieve the mempool cache for an account
 async fn get_mempool_cache<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetMempoolCacheParams = parse_params(body)?;
@@ -1532,6 +1984,48 @@ async fn get_mempool_cache<S: Storage>(context: &Context, body: Value) -> Result
     Ok(json!(cache))
 }
 
+// Get the TXs of a source in mempool ordered by nonce
+async fn get_mempool_by_source<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetMempoolBySourceParams = parse_params(body)?;
+    if !params.address.is_normal() {
+        return Err(InternalRpcError::InvalidParamsAny(ApiError::ExpectedNormalAddress.into()))
+    }
+
+    let maximum = params.maximum.filter(|v| *v <= MAX_TXS)
+        .unwrap_or(MAX_TXS);
+    let skip = params.skip.unwrap_or(0);
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    let storage = blockchain.get_storage().read().await;
+    let mempool = blockchain.get_mempool().read().await;
+
+    // Use the per-source cache to not iterate over the whole mempool
+    let Some(cache) = mempool.get_cache_for(params.address.get_public_key()) else {
+        return Ok(json!(GetMempoolResult {
+            transactions: Vec::new(),
+            total: 0
+        }))
+    };
+
+    let hashes = cache.get_txs();
+    let total = hashes.len();
+    let mut transactions = Vec::with_capacity(maximum.min(total));
+    for hash in hashes.iter().skip(skip).take(maximum) {
+        let sorted_tx = mempool.get_sorted_tx(hash)?;
+        let tx = get_transaction_response(&*storage, sorted_tx.get_tx(), hash, true, Some(sorted_tx.get_first_seen())).await?;
+        transactions.push(tx);
+    }
+
+    Ok(json!(GetMempoolResult {
+        transactions,
+        total
+    }))
+}
+
 async fn get_difficulty<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     require_no_params(body)?;
 
@@ -1549,6 +2043,62 @@ async fn get_difficulty<S: Storage>(context: &Context, body: Value) -> Result<Va
     }))
 }
 
+const MAX_DIFFICULTY_HISTORY_POINTS: u64 = 1000;
+
+async fn get_difficulty_history<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetDifficultyHistoryParams = parse_params(body)?;
+    let step = params.step.unwrap_or(1);
+    if step == 0 {
+        return Err(InternalRpcError::InvalidJSONRequest).context("Step must be above 0")?
+    }
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let current_height = blockchain.get_height();
+    let start_height = params.start_height.unwrap_or(0);
+    let end_height = params.end_height.unwrap_or(current_height);
+    if end_height > current_height || start_height > end_height {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Invalid height range, must be between 0 and {}", current_height))?
+    }
+
+    let count = (end_height - start_height) / step + 1;
+    if count > MAX_DIFFICULTY_HISTORY_POINTS {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Too many points requested, max is {}", MAX_DIFFICULTY_HISTORY_POINTS))?
+    }
+
+    let storage = blockchain.get_storage().read().await;
+    let mut points = Vec::with_capacity(count as usize);
+    for height in (start_height..=end_height).step_by(step as usize) {
+        // Heights below the pruned point may not have any block anymore
+        if !storage.has_blocks_at_height(height).await.context("Error while checking blocks at height")? {
+            continue;
+        }
+
+        let mut difficulty = None;
+        for hash in storage.get_blocks_at_height(height).await.context("Error while retrieving blocks at height")? {
+            let block_difficulty = storage.get_difficulty_for_block_hash(&hash).await.context("Error while retrieving difficulty")?;
+            if difficulty.map_or(true, |d| block_difficulty > d) {
+                difficulty = Some(block_difficulty);
+            }
+        }
+
+        let Some(difficulty) = difficulty else {
+            continue;
+        };
+
+        let version = get_version_at_height(blockchain.get_network(), height);
+        let block_time_target = get_block_time_target_for_version(version);
+        let hashrate = difficulty / (block_time_target / MILLIS_PER_SECOND);
+        points.push(DifficultyPoint {
+            height,
+            difficulty,
+            hashrate,
+            hashrate_formatted: format_hashrate(hashrate.into())
+        });
+    }
+
+    Ok(json!(points))
+}
+
 async fn validate_address<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: ValidateAddressParams = parse_params(body)?;
 
@@ -1619,11 +2169,33 @@ async fn make_integrated_address<S: Storage>(context: &Context, body: Value) ->
 
 async fn decrypt_extra_data<S: Storage>(_: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: DecryptExtraDataParams = parse_params(body)?;
-    let data = params.extra_data
-        .decrypt_with_shared_key(&params.shared_key)
+    if !params.parse {
+        let data = params.extra_data
+            .decrypt_with_shared_key(&params.shared_key)
+            .context("Error while decrypting using provided shared key")?;
+
+        return Ok(json!(data))
+    }
+
+    let plaintext = params.extra_data
+        .decrypt_plaintext_with_shared_key(&params.shared_key)
         .context("Error while decrypting using provided shared key")?;
 
-    Ok(json!(data))
+    // Payload may be opaque bytes, don't fail if it can't be decoded
+    let data = DataElement::from_bytes(&plaintext.0).ok();
+    let integrated_data = match &data {
+        Some(DataElement::Fields(fields)) => Some(fields.iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect()
+        ),
+        _ => None
+    };
+
+    Ok(json!(DecryptExtraDataResult {
+        plaintext,
+        data,
+        integrated_data
+    }))
 }
 
 async fn get_multisig_at_topoheight<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
@@ -1710,6 +2282,105 @@ async fn get_contract_outputs<S: Storage>(context: &Context, body: Value) -> Res
     Ok(json!(rpc_outputs))
 }
 
+// Get all the transactions executed by a block with their fees and contract outputs
+async fn get_block_execution<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetBlockExecutionParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let is_mainnet = blockchain.get_network().is_mainnet();
+    let storage = blockchain.get_storage().read().await;
+
+    let header = storage.get_block_header_by_hash(&params.block_hash).await
+        .context("Error while retrieving block header")?;
+    let topoheight = if storage.is_block_topological_ordered(&params.block_hash).await.context("Error while checking if block is ordered")? {
+        Some(storage.get_topo_height_for_hash(&params.block_hash).await.context("Error while retrieving block topoheight")?)
+    } else {
+        None
+    };
+
+    // Load everything first as the RPC outputs borrow the contract outputs
+    let mut executed = Vec::new();
+    let mut skipped = Vec::new();
+    let mut total_fees = 0u64;
+    for hash in header.get_txs_hashes() {
+        if topoheight.is_none() || !storage.is_tx_executed_in_block(hash, &params.block_hash).context("Error while checking if tx was executed in block")? {
+            let executed_in_block = storage.get_block_executor_for_tx(hash).ok();
+            skipped.push(SkippedTransaction {
+                hash: Cow::Borrowed(hash),
+                executed_in_block: executed_in_block.map(Cow::Owned)
+            });
+            continue;
+        }
+
+        let tx = storage.get_transaction(hash).await
+            .context("Error while retrieving transaction")?;
+        let fee_type = tx.get_fee_type().clone();
+        let fee = tx.get_fee();
+        if fee_type.is_tos() {
+            total_fees = total_fees.saturating_add(fee);
+        }
+
+        let outputs = if storage.has_contract_outputs_for_tx(hash).await.context("Error while checking contract outputs")? {
+            storage.get_contract_outputs_for_tx(hash).await
+                .context("Error while retrieving contract outputs")?
+        } else {
+            Vec::new()
+        };
+
+        executed.push((hash, fee, fee_type, outputs));
+    }
+
+    Ok(json!(GetBlockExecutionResult {
+        topoheight,
+        executed: executed.iter()
+            .map(|(hash, fee, fee_type, outputs)| ExecutedTransaction {
+                hash: Cow::Borrowed(*hash),
+                fee: *fee,
+                fee_type: fee_type.clone(),
+                outputs: outputs.iter()
+                    .map(|output| RPCContractOutput::from_output(output, is_mainnet))
+                    .collect()
+            })
+            .collect(),
+        skipped,
+        total_fees
+    }))
+}
+
+const MAX_CONTRACT_OUTPUTS_TXS: usize = 100;
+
+async fn get_contract_outputs_by_contract<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetContractOutputsByContractParams = parse_params(body)?;
+    let limit = params.limit.unwrap_or(MAX_CONTRACT_OUTPUTS_TXS);
+    if limit > MAX_CONTRACT_OUTPUTS_TXS {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Limit cannot be greater than {}", MAX_CONTRACT_OUTPUTS_TXS))?
+    }
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let is_mainnet = blockchain.get_network().is_mainnet();
+    let storage = blockchain.get_storage().read().await;
+    let txs = storage.get_contract_transactions(&params.contract, params.start_topoheight.unwrap_or(0), limit).await
+        .context("Error while retrieving contract transactions")?;
+
+    let mut entries = Vec::with_capacity(txs.len());
+    for (topoheight, tx_hash) in txs {
+        let outputs = storage.get_contract_outputs_for_tx(&tx_hash).await
+            .context("Error while retrieving contract outputs")?;
+        entries.push((topoheight, tx_hash, outputs));
+    }
+
+    let response = entries.iter()
+        .map(|(topoheight, tx_hash, outputs)| ContractTransactionOutputs {
+            topoheight: *topoheight,
+            tx_hash: Cow::Borrowed(tx_hash),
+            outputs: outputs.iter()
+                .map(|output| RPCContractOutput::from_output(output, is_mainnet))
+                .collect()
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!(response))
+}
+
 async fn get_contract_module<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetContractModuleParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
@@ -1717,10 +2388,38 @@ async fn get_contract_module<S: Storage>(context: &Context, body: Value) -> Resu
     let Some(topoheight) = storage.get_last_topoheight_for_contract(&params.contract).await? else {
         return Err(InternalRpcError::InvalidParams("no contract module available"));
     };
-    let module = storage.get_contract_at_topoheight_for(&params.contract, topoheight).await
+    let version = storage.get_contract_at_topoheight_for(&params.contract, topoheight).await
         .context("Error while retrieving contract module")?;
 
-    Ok(json!(module))
+    // The contract hash is the hash of the TX that deployed it
+    let (deployer, deployed_topoheight) = if storage.has_transaction(&params.contract).await.context("Error while checking if deploy TX exists")? {
+        let tx = storage.get_transaction(&params.contract).await.context("Error while retrieving deploy TX")?;
+        let deployer = tx.get_source().as_address(blockchain.get_network().is_mainnet());
+
+        let deployed_topoheight = if storage.is_tx_executed_in_a_block(&params.contract).context("Error while checking if deploy TX was executed")? {
+            let block_hash = storage.get_block_executor_for_tx(&params.contract).context("Error while retrieving deploy TX executor")?;
+            Some(storage.get_topo_height_for_hash(&block_hash).await.context("Error while retrieving deploy block topoheight")?)
+        } else {
+            None
+        };
+
+        (Some(deployer), deployed_topoheight)
+    } else {
+        (None, None)
+    };
+
+    let previous_topoheight = version.get_previous_topoheight();
+    let module = version.take();
+    let module_size = module.as_ref().map(|module| module.size());
+
+    Ok(json!(GetContractModuleResult {
+        topoheight,
+        previous_topoheight,
+        data: module.filter(|_| params.include_module),
+        module_size,
+        deployed_topoheight,
+        deployer
+    }))
 }
 
 async fn get_contract_data<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
@@ -1750,6 +2449,36 @@ async fn get_contract_data_at_topoheight<S: Storage>(context: &Context, body: Va
     Ok(json!(version))
 }
 
+const MAX_CONTRACT_DATA_ENTRIES: usize = 100;
+
+// Enumerate the contract data entries at a topoheight snapshot
+async fn get_contract_data_range<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetContractDataRangeParams = parse_params(body)?;
+    let limit = params.limit.filter(|v| *v > 0 && *v <= MAX_CONTRACT_DATA_ENTRIES)
+        .unwrap_or(MAX_CONTRACT_DATA_ENTRIES);
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let topoheight = params.topoheight.unwrap_or_else(|| blockchain.get_topo_height());
+    let storage = blockchain.get_storage().read().await;
+
+    // Fetch one more entry to know where the next page starts
+    let mut entries = storage.get_contract_data_entries_range_at_maximum_topoheight(&params.contract, params.start_key.as_deref(), limit + 1, topoheight).await
+        .context("Error while retrieving contract data entries")?;
+
+    let next_key = if entries.len() > limit {
+        entries.pop().map(|(key, _)| key)
+    } else {
+        None
+    };
+
+    Ok(json!(GetContractDataRangeResult {
+        entries: entries.into_iter()
+            .map(|(key, value)| ContractDataEntry { key, value })
+            .collect(),
+        next_key
+    }))
+}
+
 async fn get_contract_balance<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetContractBalanceParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
@@ -1799,6 +2528,35 @@ async fn get_contract_balance_at_topoheight<S: Storage>(context: &Context, body:
     Ok(json!(version))
 }
 
+const MAX_CONTRACT_BALANCES_ASSETS: usize = 64;
+
+// Retrieve the balances of a contract for the requested assets
+// Each asset is mapped to its versioned balance, or null if the contract has no balance for it
+async fn get_contract_balances_for_assets<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetContractBalancesForAssetsParams = parse_params(body)?;
+    if params.assets.len() > MAX_CONTRACT_BALANCES_ASSETS {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Too many requested assets: {}, maximum is {}", params.assets.len(), MAX_CONTRACT_BALANCES_ASSETS))?
+    }
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let topoheight = params.topoheight.unwrap_or_else(|| blockchain.get_topo_height());
+    let storage = blockchain.get_storage().read().await;
+
+    let mut balances = IndexMap::with_capacity(params.assets.len());
+    for asset in params.assets.iter() {
+        let balance = storage.get_contract_balance_at_maximum_topoheight(&params.contract, asset, topoheight).await
+            .context(format!("Error while retrieving contract balance for asset {}", asset))?
+            .map(|(topoheight, version)| RPCVersioned {
+                topoheight,
+                version
+            });
+
+        balances.insert(asset, balance);
+    }
+
+    Ok(json!(balances))
+}
+
 async fn get_p2p_block_propagation<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetP2pBlockPropagation = parse_params(body)?;
 
@@ -1840,6 +2598,179 @@ async fn get_p2p_block_propagation<S: Storage>(context: &Context, body: Value) -
     }))
 }
 
+const MAX_PROPAGATION_STATS_BLOCKS: usize = 100;
+
+// Compute the propagation delays over the last N blocks accepted from the network
+async fn get_propagation_stats<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetPropagationStatsParams = parse_params(body)?;
+    let count = params.count.filter(|v| *v <= MAX_PROPAGATION_STATS_BLOCKS)
+        .unwrap_or(MAX_PROPAGATION_STATS_BLOCKS);
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))?;
+
+    Ok(json!(p2p.get_propagation_stats(count).await))
+}
+
+async fn p2p_ban_subnet<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: P2pBanSubnetParams = parse_params(body)?;
+    let subnet: Subnet = params.subnet.parse()
+        .map_err(|e: SubnetError| InternalRpcError::InvalidParamsAny(e.into()))?;
+
+    if params.duration == 0 {
+        return Err(InternalRpcError::InvalidParamsAny(P2pError::InvalidTempBanTime.into()))
+    }
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))?;
+
+    p2p.get_peer_list().ban_subnet(subnet, params.duration).await
+        .map_err(|e| InternalRpcError::InvalidParamsAny(e.into()))?;
+
+    Ok(json!(true))
+}
+
+async fn p2p_disconnect_peer<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: P2pDisconnectPeerParams = parse_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))?;
+
+    let found = p2p.disconnect_peer(params.peer_id).await;
+    Ok(json!(found))
+}
+
+async fn p2p_probe<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: P2pProbeParams = parse_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))?;
+
+    let result = p2p.probe(params.address).await
+        .map_err(|e| InternalRpcError::InvalidParamsAny(e.into()))?;
+    Ok(json!(result))
+}
+
+// Pause or resume the propagation of transactions
+// Transactions propagated by our peers are still fetched
+async fn p2p_set_tx_propagation<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: P2pSetTxPropagationParams = parse_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))?;
+
+    p2p.set_tx_propagation_enabled(params.enabled);
+    Ok(json!(true))
+}
+
+// Request again the whole mempool of a peer from the first inventory page
+// Returns false if an inventory request is already in progress with it
+// or if it advertised an empty mempool
+async fn p2p_resync_mempool<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: P2pResyncMempoolParams = parse_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))?;
+
+    let started = p2p.resync_mempool_of(params.peer_id).await
+        .map_err(|e| InternalRpcError::InvalidParamsAny(e.into()))?;
+    Ok(json!(started))
+}
+
+// Force the next chain sync cycle to be done with the requested peer
+// This is a debug tool to resolve a stuck chain split
+async fn p2p_sync_from_peer<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: P2pSyncFromPeerParams = parse_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))?;
+
+    p2p.sync_from_peer(params.peer_id).await
+        .map_err(|e| InternalRpcError::InvalidParamsAny(e.into()))?;
+    Ok(json!(true))
+}
+
+// Check that the node is connected to enough peers and synced
+// before answering the balance and nonce queries, if configured
+async fn require_node_ready<S: Storage>(blockchain: &Blockchain<S>) -> Result<(), InternalRpcError> {
+    let Some(min_peers) = blockchain.get_rpc_ready_min_peers() else {
+        return Ok(())
+    };
+
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NodeNotReady.into()))?;
+
+    let (synced, _, peers) = p2p.get_sync_status().await;
+    if !synced || peers < min_peers {
+        return Err(InternalRpcError::InvalidParamsAny(ApiError::NodeNotReady.into()))
+    }
+
+    Ok(())
+}
+
+async fn get_sync_status<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    require_no_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))?;
+
+    let (synced, network_topoheight, peers) = p2p.get_sync_status().await;
+    Ok(json!(GetSyncStatusResult {
+        synced,
+        syncing: p2p.is_syncing_chain(),
+        topoheight: blockchain.get_topo_height(),
+        network_topoheight,
+        peers
+    }))
+}
+
+async fn get_network_time_offset<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    require_no_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))?;
+
+    let (offset, peers) = p2p.get_network_time_offset().await;
+    Ok(json!(GetNetworkTimeOffsetResult {
+        offset,
+        peers
+    }))
+}
+
+async fn get_object_tracker_status<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    require_no_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))?;
+
+    let tracker = p2p.get_object_tracker();
+    Ok(json!(GetObjectTrackerStatusResult {
+        pending_requests: tracker.get_pending_requests_count().await,
+        oldest_request_age_ms: tracker.get_oldest_request_age().await.map(|age| age.as_millis() as u64),
+        timed_out_requests: tracker.get_timed_out_requests_count(),
+    }))
+}
+
+async fn get_p2p_fail_count_whitelist<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    require_no_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() }
+        .ok_or(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))?;
+
+    Ok(json!(p2p.get_fail_count_whitelist()))
+}
+
 // Energy management RPC methods
 
 /// Get energy information for an account
@@ -1893,4 +2824,131 @@ async fn get_energy<S: Storage>(context: &Context, body: Value) -> Result<Value,
     };
 
     Ok(result)
-}
\ No newline at end of file
+}
+
+// Maximum nonce versions scanned per get_freeze_history call
+const MAX_FREEZE_HISTORY_SCAN: usize = 1000;
+
+// Retrieve the freeze / unfreeze history of an account
+// Energy TXs are only sent by the account itself, so we walk its nonce versions
+async fn get_freeze_history<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetFreezeHistoryParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    let limit = match params.limit {
+        Some(limit) if limit == 0 || limit > MAX_HISTORY_ENTRIES => {
+            return Err(InternalRpcError::InvalidJSONRequest).context(format!("Invalid history limit {}, maximum is {}", limit, MAX_HISTORY_ENTRIES))?
+        },
+        limit => limit.unwrap_or(MAX_HISTORY_ENTRIES)
+    };
+
+    let key = params.address.get_public_key();
+    let storage = blockchain.get_storage().read().await;
+    let pruned_topoheight = storage.get_pruned_topoheight().await.context("Error while retrieving pruned topoheight")?.unwrap_or(0);
+
+    let mut version = match params.start_topoheight {
+        Some(topo) => {
+            if topo < pruned_topoheight {
+                return Err(InternalRpcError::InvalidParams("Start topoheight is lower than pruned topoheight"));
+            }
+
+            storage.get_nonce_at_maximum_topoheight(key, topo).await.context("Error while retrieving nonce")?
+        },
+        None => if storage.has_nonce(key).await.context("Error while checking nonce")? {
+            Some(storage.get_last_nonce(key).await.context("Error while retrieving last nonce")?)
+        } else {
+            None
+        }
+    };
+
+    let mut entries = Vec::new();
+    let mut next_start_topoheight = None;
+    let mut scanned = 0;
+    while let Some((topo, nonce)) = version.take() {
+        if topo < pruned_topoheight {
+            break;
+        }
+
+        let (hash, block_header) = storage.get_block_header_at_topoheight(topo).await.context(format!("Error while retrieving block header at topo height {topo}"))?;
+        for tx_hash in block_header.get_transactions().iter().rev() {
+            if !storage.is_tx_executed_in_block(tx_hash, &hash)? {
+                continue;
+            }
+
+            let tx = storage.get_transaction(tx_hash).await.context(format!("Error while retrieving transaction {tx_hash} from block {hash}"))?;
+            if *tx.get_source() != *key {
+                continue;
+            }
+
+            let history_type = match tx.get_data() {
+                TransactionType::Energy(EnergyPayload::FreezeTos { amount, duration }) => {
+                    let record = FreezeRecord::new(*amount, *duration, topo);
+                    FreezeHistoryType::Freeze {
+                        amount: *amount,
+                        duration: format!("{}_days", duration.get_days()),
+                        energy_gained: record.energy_gained,
+                        unlock_topoheight: record.unlock_topoheight,
+                    }
+                },
+                TransactionType::Energy(EnergyPayload::UnfreezeTos { amount }) => FreezeHistoryType::Unfreeze { amount: *amount },
+                _ => continue
+            };
+
+            entries.push(FreezeHistoryEntry {
+                topoheight: topo,
+                hash: tx_hash.clone(),
+                block_timestamp: block_header.get_timestamp(),
+                history_type
+            });
+        }
+
+        if let Some(previous) = nonce.get_previous_topoheight().filter(|previous| *previous >= pruned_topoheight) {
+            // Entries of a same topoheight are never split across pages
+            scanned += 1;
+            if entries.len() >= limit || scanned >= MAX_FREEZE_HISTORY_SCAN {
+                next_start_topoheight = Some(previous);
+                break;
+            }
+
+            let nonce = storage.get_nonce_at_exact_topoheight(key, previous).await.context(format!("Error while retrieving nonce at topo height {previous}"))?;
+            version = Some((previous, nonce));
+        }
+    }
+
+    Ok(json!(GetFreezeHistoryResult {
+        entries,
+        next_start_topoheight
+    }))
+}
+
+async fn add_checkpoint<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AddCheckpointParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+
+    let topoheight = blockchain.add_checkpoint(params.block_hash.into_owned()).await?;
+    Ok(json!(AddCheckpointResult {
+        topoheight
+    }))
+}
+
+// Rewind the chain until the requested topoheight
+// Refused while the chain is being synced with a peer
+async fn rewind_chain<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: RewindChainParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+
+    if let Some(p2p) = blockchain.get_p2p().read().await.as_ref() {
+        if p2p.is_syncing_chain() {
+            return Err(InternalRpcError::InvalidParamsAny(ApiError::ChainSyncing.into()))
+        }
+    }
+
+    let (topoheight, tips) = blockchain.rewind_chain_to_topoheight(params.topoheight).await?;
+    Ok(json!(RewindChainResult {
+        topoheight,
+        tips
+    }))
+}
@@ -139,6 +139,12 @@ impl DaemonAPI {
         Ok(receiver)
     }
 
+    pub async fn on_mempool_transaction_evicted_event(&self) -> Result<EventReceiver<MempoolTransactionEvictedEvent<'static>>> {
+        trace!("on_mempool_transaction_evicted_event");
+        let receiver = self.client.subscribe_event(NotifyEvent::MempoolTransactionEvicted, self.capacity).await?;
+        Ok(receiver)
+    }
+
     pub async fn on_stable_topoheight_changed_event(&self) -> Result<EventReceiver<StableTopoHeightChangedEvent>> {
         trace!("on_stable_topoheight_changed_event");
         let receiver = self.client.subscribe_event(NotifyEvent::StableTopoHeightChanged, self.capacity).await?;
@@ -182,7 +188,8 @@ impl DaemonAPI {
         let assets = self.client.call_with("get_account_assets", &GetAccountAssetsParams {
             address: Cow::Borrowed(address),
             maximum,
-            skip
+            skip,
+            include_balances: false
         }).await?;
         Ok(assets)
     }
@@ -209,6 +216,7 @@ impl DaemonAPI {
         let balance = self.client.call_with("get_balance", &GetBalanceParams {
             address: Cow::Borrowed(address),
             asset: Cow::Borrowed(asset),
+            confirmations: None,
         }).await?;
         Ok(balance)
     }
@@ -310,6 +318,7 @@ impl DaemonAPI {
         let balance = self.client.call_with("get_stable_balance", &GetBalanceParams {
             address: Cow::Borrowed(address),
             asset: Cow::Borrowed(asset),
+            confirmations: None,
         }).await?;
         Ok(balance)
     }
@@ -1045,7 +1045,7 @@ impl Wallet {
         };
 
         let builder = TransactionBuilder::new(version, self.get_public_key().clone(), threshold, tx_type, fee);
-        let estimated_fees = builder.estimate_fees(&mut state)
+        let estimated_fees = builder.estimate_fees_value(&mut state)
             .map_err(|e| WalletError::Any(e.into()))?;
 
         Ok(estimated_fees)